@@ -1,9 +1,12 @@
 use eframe::egui;
 use egui::Color32;
-use std::{cmp::max, cmp::min, fs, path::Path, path::PathBuf};
+use std::{cmp::max, cmp::min, env, fs, path::Path, path::PathBuf};
 
 use crate::core;
-use crate::editor::themes::DEFAULT_THEMES;
+use crate::editor::position::{self, Column};
+use crate::editor::symbols;
+use crate::editor::themes::color_from_hex;
+use crate::lsp;
 use crate::panels;
 use crate::save_path;
 use crate::sub_windows;
@@ -21,6 +24,99 @@ impl Calcifer {
             self.refresh_confirm.close();
             self.tabs[self.selected_tab].refresh();
         }
+
+        if self.conflict_confirm.proceed {
+            self.conflict_confirm.close();
+            if let Some(path) = self.conflict_path.take() {
+                if let Some(index) = self.tabs.iter().position(|tab| tab.path == path) {
+                    match self.conflict_confirm.choice {
+                        1 => self.tabs[index].refresh(),
+                        2 => {
+                            if let Some(diff_path) = self.open_diff(index) {
+                                self.conflict_diffs.push((path, diff_path));
+                            }
+                        }
+                        _ => self.tabs[index].keep_over_disk(),
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn handle_file_changes(&mut self, ctx: &egui::Context) {
+        let paths: Vec<PathBuf> = self.tabs.iter().map(|tab| tab.path.clone()).collect();
+        self.file_watcher.watch(ctx, &paths);
+
+        for path in self.file_watcher.changed_files() {
+            for tab in self.tabs.iter_mut().filter(|tab| tab.path == path) {
+                if !tab.changed_on_disk() {
+                    continue;
+                }
+                if tab.saved {
+                    tab.refresh();
+                } else if !self.conflicts.contains(&path)
+                    && !self
+                        .conflict_diffs
+                        .iter()
+                        .any(|(pending, _)| *pending == path)
+                {
+                    self.conflicts.push(path.clone());
+                }
+            }
+        }
+
+        // asked again once the diff is closed, unless the buffer was saved or closed meanwhile
+        let tabs = &self.tabs;
+        let (closed, open): (Vec<_>, Vec<_>) = self
+            .conflict_diffs
+            .drain(..)
+            .partition(|(_, diff_path)| !tabs.iter().any(|tab| tab.path == *diff_path));
+        self.conflict_diffs = open;
+        for (path, _) in closed {
+            let unsaved = tabs.iter().any(|tab| tab.path == path && !tab.saved);
+            if unsaved && !self.conflicts.contains(&path) {
+                self.conflicts.push(path);
+            }
+        }
+
+        if self.conflict_path.is_some() && !self.conflict_confirm.visible {
+            // the window was closed without choosing, keep the buffer as is
+            self.conflict_path = None;
+        }
+
+        if self.conflict_path.is_none() && !self.conflicts.is_empty() {
+            let path = self.conflicts.remove(0);
+            self.conflict_confirm.prompt = format!(
+                "{} was modified on disk but has unsaved changes here.",
+                path.display()
+            );
+            self.conflict_confirm.ask();
+            self.conflict_path = Some(path);
+        }
+    }
+
+    /// Open the diff of a tab with its file in a tab of its own, returns the path of the diff
+    fn open_diff(&mut self, index: usize) -> Option<PathBuf> {
+        let name = self.tabs[index].get_name();
+        let theirs = fs::read_to_string(&self.tabs[index].path).unwrap_or_default();
+        let diff = core::line_diff(&self.tabs[index].code, &theirs, &name);
+
+        let diff_path = env::temp_dir()
+            .join("calcifer")
+            .join(format!("{}.diff", name));
+        if let Some(parent) = diff_path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Err(err) = fs::write(&diff_path, diff) {
+            eprintln!("Error writing diff: {}", err);
+            return None;
+        }
+
+        if let Some(diff_index) = self.tabs.iter().position(|tab| tab.path == diff_path) {
+            self.tabs[diff_index].refresh();
+        }
+        self.open_file(Some(&diff_path));
+        Some(diff_path)
     }
 
     pub fn save_tab(&self) -> Option<PathBuf> {
@@ -68,8 +164,10 @@ impl Calcifer {
     pub fn handle_save_file(&mut self, path_option: Option<PathBuf>) {
         if let Some(path) = path_option {
             println!("File saved successfully at: {:?}", path);
+            self.tabs[self.selected_tab].last_modified = panels::modified_time(&path);
             self.tabs[self.selected_tab].path = path;
             self.tabs[self.selected_tab].saved = true;
+            self.lsp.saved(&self.tabs[self.selected_tab]);
         } else {
             println!("File save failed.");
         }
@@ -77,16 +175,29 @@ impl Calcifer {
 
     pub fn from_app_state(app_state: core::AppState, file_to_open: Option<PathBuf>) -> Self {
         let mut new = Self {
-            theme: DEFAULT_THEMES[min(app_state.theme, DEFAULT_THEMES.len() - 1)],
             tabs: Vec::new(),
-            settings_menu: sub_windows::SettingsWindow::new(DEFAULT_THEMES[app_state.theme]),
             ..Default::default()
         };
 
+        if let Some(theme) = new
+            .settings_menu
+            .themes
+            .iter()
+            .find(|theme| theme.name == app_state.theme)
+        {
+            new.theme = theme.clone();
+            new.settings_menu.theme = theme.clone();
+        }
+
         if app_state.zoom != 0.0 {
             new.zoom = app_state.zoom;
         }
 
+        new.command_history = panels::CommandHistory::new(app_state.commands);
+        if app_state.scrollback != 0 {
+            new.settings_menu.scrollback = app_state.scrollback;
+        }
+
         for path in app_state.tabs {
             if !path
                 .file_name()
@@ -96,6 +207,12 @@ impl Calcifer {
             }
         }
 
+        for tab in new.tabs.iter_mut() {
+            if let Some(lines) = app_state.folds.get(&tab.path) {
+                tab.folds = lines.iter().copied().collect();
+            }
+        }
+
         if let Some(path) = file_to_open {
             new.open_file(Some(&path));
         }
@@ -108,11 +225,6 @@ impl Calcifer {
     }
 
     pub fn save_state(&self) {
-        let mut state_theme: usize = 0;
-        if let Some(theme) = DEFAULT_THEMES.iter().position(|&r| r == self.theme) {
-            state_theme = theme;
-        }
-
         let mut state_tabs = vec![];
 
         for tab in &self.tabs {
@@ -120,8 +232,16 @@ impl Calcifer {
         }
         let app_state = core::AppState {
             tabs: state_tabs,
-            theme: state_theme,
+            theme: self.theme.name.clone(),
             zoom: self.zoom,
+            commands: self.command_history.commands().clone(),
+            scrollback: self.settings_menu.scrollback,
+            folds: self
+                .tabs
+                .iter()
+                .filter(|tab| !tab.folds.is_empty())
+                .map(|tab| (tab.path.clone(), tab.folds.iter().copied().collect()))
+                .collect(),
         };
 
         let _ = core::save_state(&app_state, save_path().as_path());
@@ -140,11 +260,9 @@ impl Calcifer {
 
     pub fn open_file(&mut self, path_option: Option<&Path>) {
         if let Some(path) = path_option {
-            for (index, tab) in self.tabs.clone().iter().enumerate() {
-                if tab.path == path {
-                    self.selected_tab = index;
-                    return;
-                }
+            if let Some(index) = self.tabs.iter().position(|tab| tab.path == path) {
+                self.selected_tab = index;
+                return;
             }
         }
         if let Some(path) = path_option {
@@ -155,7 +273,131 @@ impl Calcifer {
         self.selected_tab = self.tabs.len() - 1;
     }
 
+    pub fn go_to_location(&mut self, location: lsp::Location) {
+        self.go_to_position(
+            &location.path,
+            (location.line, location.character),
+            Column::Utf16,
+        );
+    }
+
+    /// Open a file and place the cursor at a zero based line and column
+    pub fn go_to_position(&mut self, path: &Path, line_col: (usize, usize), column: Column) {
+        self.open_file(Some(path));
+        let tab = &mut self.tabs[self.selected_tab];
+        if tab.path == path {
+            tab.pending_cursor = Some(position::line_col_to_char(&tab.code, line_col, column));
+        }
+    }
+
+    /// Start a shell if there is none, or move the selected one to `cwd` while it waits for a command
+    pub fn open_terminal(&mut self, ctx: &egui::Context, cwd: &Path) {
+        match self.terminals.get_mut(self.selected_terminal) {
+            Some(terminal) if terminal.exited => self.restart_terminal(ctx),
+            Some(terminal) => {
+                if !terminal.is_running() && cwd.is_dir() {
+                    terminal.change_directory(cwd);
+                }
+            }
+            None => self.add_terminal(ctx, cwd),
+        }
+    }
+
+    /// Open a new terminal tab, named after the first free number
+    pub fn add_terminal(&mut self, ctx: &egui::Context, cwd: &Path) {
+        let name = (1..)
+            .map(|number| format!("shell {}", number))
+            .find(|name| self.terminals.iter().all(|terminal| &terminal.name != name))
+            .unwrap_or_default();
+        if let Some(terminal) = self.start_terminal(ctx, name, cwd) {
+            self.terminals.push(terminal);
+            self.selected_terminal = self.terminals.len() - 1;
+        }
+    }
+
+    /// Start a new shell in place of the selected one once it exited, keeping its history
+    pub fn restart_terminal(&mut self, ctx: &egui::Context) {
+        let Some(old) = self.terminals.get(self.selected_terminal) else {
+            return;
+        };
+        if let Some(mut terminal) = self.start_terminal(ctx, old.name.clone(), &old.cwd.clone()) {
+            let old = &mut self.terminals[self.selected_terminal];
+            terminal.entries = std::mem::take(&mut old.entries);
+            *old = terminal;
+        }
+    }
+
+    pub fn close_terminal(&mut self, index: usize) {
+        self.terminals.remove(index);
+        if self.selected_terminal > index {
+            self.selected_terminal -= 1;
+        }
+        self.selected_terminal = min(
+            self.selected_terminal,
+            self.terminals.len().saturating_sub(1),
+        );
+    }
+
+    fn start_terminal(
+        &self,
+        ctx: &egui::Context,
+        name: String,
+        cwd: &Path,
+    ) -> Option<panels::TerminalSession> {
+        let cwd = if cwd.is_dir() {
+            cwd
+        } else {
+            self.home.as_path()
+        };
+        match panels::TerminalSession::new(ctx, name, cwd, &self.home) {
+            Ok(terminal) => Some(terminal),
+            Err(err) => {
+                eprintln!("Could not start the terminal : {}", err);
+                None
+            }
+        }
+    }
+
+    /// Show the symbols of the current tab
+    pub fn open_outline(&mut self) {
+        let tab = &self.tabs[self.selected_tab];
+        let syntax = self
+            .languages
+            .syntax(&tab.path, &tab.code, tab.language_override.as_deref());
+        self.outline_menu.load(symbols::symbols(&syntax, &tab.code));
+        self.outline_menu.visible = true;
+    }
+
+    /// Show the tasks of the project of the current tab
+    pub fn open_task_menu(&mut self) {
+        let tab = &self.tabs[self.selected_tab];
+        self.task_menu.load(&tab.path, &tab.language);
+        self.task_menu.visible = true;
+    }
+
+    /// Run a task in the selected terminal, or in a new one while that one is busy
+    pub fn run_task(&mut self, ctx: &egui::Context, task: panels::Task) {
+        self.terminal_visible = true;
+        match self.terminals.get(self.selected_terminal) {
+            Some(terminal) if terminal.exited => self.restart_terminal(ctx),
+            Some(terminal) if !terminal.is_running() => (),
+            _ => self.add_terminal(ctx, &task.root),
+        }
+        if let Some(terminal) = self.terminals.get_mut(self.selected_terminal) {
+            terminal.send_command(task.shell_command());
+            self.last_task = Some(task);
+        }
+    }
+
+    pub fn go_to_problem(&mut self, forward: bool) {
+        if let Some(problem) = self.problems.select(forward) {
+            let (path, start) = (problem.path.clone(), problem.start);
+            self.go_to_position(&path, start, Column::Chars);
+        }
+    }
+
     pub fn delete_tab(&mut self, index: usize) {
+        self.lsp.closed(&self.tabs[index]);
         self.tabs.remove(index);
         if self.tabs.len() != 0 {
             self.selected_tab = min(index, self.tabs.len() - 1);
@@ -167,11 +409,11 @@ impl Calcifer {
         let text_color: Color32;
 
         if display {
-            bg_color = hex_str_to_color(self.theme.functions);
-            text_color = hex_str_to_color(self.theme.bg);
+            bg_color = self.theme.color(&self.theme.functions);
+            text_color = self.theme.bg();
         } else {
-            bg_color = hex_str_to_color(self.theme.bg);
-            text_color = hex_str_to_color(self.theme.literals);
+            bg_color = self.theme.bg();
+            text_color = self.theme.color(&self.theme.literals);
         };
 
         ui.style_mut().visuals.override_text_color = Some(text_color);
@@ -245,8 +487,3 @@ impl Calcifer {
         max(6, self.tabs.len() + 1)
     }
 }
-
-#[allow(clippy::unnecessary_lazy_evaluations)]
-pub fn hex_str_to_color(hex_str: &str) -> Color32 {
-    Color32::from_hex(hex_str).unwrap_or_else(|_| Color32::BLACK)
-}
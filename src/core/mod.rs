@@ -1,9 +1,11 @@
 #[allow(unused_imports)]
 mod app;
-pub use app::*;
 
 mod ui;
 pub use ui::*;
 
 mod state;
 pub use state::*;
+
+mod watcher;
+pub use watcher::*;
@@ -1,8 +1,13 @@
+#[cfg(test)]
+mod tests;
+
 use eframe::egui;
 use image::GenericImageView;
 use serde::Deserialize;
+use serde::Deserializer;
 use serde::Serialize;
 use std::{
+    collections::BTreeMap,
     error::Error,
     fs,
     fs::{read_to_string, OpenOptions},
@@ -10,11 +15,40 @@ use std::{
     path::{Path, PathBuf},
 };
 
+use crate::editor::themes::default_themes;
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Default)]
 pub struct AppState {
     pub tabs: Vec<PathBuf>,
-    pub theme: usize,
+    #[serde(deserialize_with = "theme_name")]
+    pub theme: String,
     pub zoom: f32,
+    /// Terminal commands typed in each working directory, oldest first
+    #[serde(default)]
+    pub commands: BTreeMap<PathBuf, Vec<String>>,
+    /// Output lines kept by each terminal, 0 in older states
+    #[serde(default)]
+    pub scrollback: usize,
+    /// Folded lines of the open files
+    #[serde(default)]
+    pub folds: BTreeMap<PathBuf, Vec<usize>>,
+}
+
+/// Older states stored the index of the theme among the built-in ones
+fn theme_name<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Theme {
+        Name(String),
+        Index(usize),
+    }
+    Ok(match Theme::deserialize(deserializer)? {
+        Theme::Name(name) => name,
+        Theme::Index(index) => default_themes()
+            .get(index)
+            .map(|theme| theme.name.clone())
+            .unwrap_or_default(),
+    })
 }
 
 pub fn save_state(state: &AppState, file_path: &Path) -> Result<(), std::io::Error> {
@@ -45,7 +79,7 @@ pub fn load_state(file_path: &Path) -> Result<AppState, std::io::Error> {
 
 pub fn load_icon() -> Result<egui::IconData, Box<dyn Error>> {
     let (icon_rgba, icon_width, icon_height) = {
-        let icon = include_bytes!("../../assets/icon.png");
+        let icon = include_bytes!("../../../assets/icon.png");
         let image = image::load_from_memory(icon)?;
         let rgba = image.clone().into_rgba8().to_vec();
         let (width, height) = image.dimensions();
@@ -0,0 +1,29 @@
+use super::*;
+
+#[test]
+fn theme_saved_by_name() {
+    let state: AppState =
+        serde_json::from_str(r#"{"tabs":["/a.rs"],"theme":"Sonokai","zoom":1.5}"#).unwrap();
+    assert_eq!(state.theme, "Sonokai");
+    assert_eq!(state.tabs, vec![PathBuf::from("/a.rs")]);
+    assert_eq!(state.scrollback, 0);
+    assert!(state.commands.is_empty() && state.folds.is_empty());
+
+    let saved = serde_json::to_string(&state).unwrap();
+    assert_eq!(serde_json::from_str::<AppState>(&saved).unwrap(), state);
+}
+
+#[test]
+fn theme_saved_by_index_in_older_states() {
+    let themes = default_themes();
+    for (index, theme) in themes.iter().enumerate() {
+        let json = format!(r#"{{"tabs":[],"theme":{},"zoom":1.0}}"#, index);
+        let state: AppState = serde_json::from_str(&json).unwrap();
+        assert_eq!(state.theme, theme.name);
+    }
+    let json = format!(r#"{{"tabs":[],"theme":{},"zoom":1.0}}"#, themes.len());
+    let state: AppState = serde_json::from_str(&json).unwrap();
+    assert_eq!(state.theme, "");
+
+    assert!(serde_json::from_str::<AppState>(r#"{"tabs":[],"theme":-1,"zoom":1.0}"#).is_err());
+}
@@ -1,18 +1,25 @@
 use eframe::egui;
 use egui::{text::CCursor, text_edit::CCursorRange, Rangef};
 use egui_extras::{Size, StripBuilder};
-use std::{cmp::max, cmp::min, env, ffi::OsStr, path::Component, path::Path, path::PathBuf};
+use std::{
+	cmp::max, cmp::min, ffi::OsStr, path::Component, path::Path, path::PathBuf,
+	time::Duration,
+};
 
-use crate::core;
 use crate::editor;
+use crate::lsp;
 use crate::panels;
 use crate::Calcifer;
 use crate::DISPLAY_PATH_DEPTH;
+use crate::HOVER_DELAY;
 use crate::PROJECT_EXTENSION;
 use crate::RED;
 use crate::TERMINAL_HEIGHT;
+use crate::TERMINAL_INPUT;
 use crate::TERMINAL_RANGE;
-use editor::{CodeEditor, Syntax};
+use crate::YELLOW;
+use editor::position::{self, Column};
+use editor::{CodeEditor, EditKind, Underline, UndoHistory};
 
 impl Calcifer {
 	pub fn draw_settings(&mut self, ctx: &egui::Context) {
@@ -39,11 +46,26 @@ impl Calcifer {
 					if toggle_terminal && !self.terminal_visible {
 						let mut path = self.tabs[self.selected_tab].path.clone();
 						path.pop();
-						panels::send_command(format!("cd {}", path.display()));
+						self.open_terminal(ctx, &path);
 					}
 					self.terminal_visible = toggle_terminal;
 					ui.separator();
 
+					let toggle_problems = self.toggle(ui, self.problems.visible, "⚠");
+					if toggle_problems && !self.problems.visible {
+						let path = self.tabs[self.selected_tab].path.clone();
+						self.problems.check(ctx, &path);
+					}
+					self.problems.visible = toggle_problems;
+					ui.separator();
+
+					let toggle_tasks = self.toggle(ui, self.task_menu.visible, "▶");
+					if toggle_tasks && !self.task_menu.visible {
+						self.open_task_menu();
+					}
+					self.task_menu.visible = toggle_tasks;
+					ui.separator();
+
 					self.search_menu.visible = self.toggle(ui, self.search_menu.visible, "🔍");
 					ui.separator();
 
@@ -107,6 +129,52 @@ impl Calcifer {
 		});
 	}
 
+	pub fn draw_search_panel(&mut self, ctx: &egui::Context) {
+		let search = &mut self.search_menu.folder_search;
+		if !search.visible {
+			return;
+		}
+		search.poll();
+
+		let mut opened: Option<(PathBuf, usize, usize)> = None;
+		egui::SidePanel::right("search_panel").show(ctx, |ui| {
+			ui.horizontal(|ui| {
+				ui.label(format!(
+					"Found {} in {} files{}",
+					search.hit_count(),
+					search.results.len(),
+					if search.searching { " ..." } else { "" },
+				));
+				if ui.button("🗙").clicked() {
+					search.stop();
+					search.visible = false;
+				}
+			});
+			ui.separator();
+
+			egui::ScrollArea::vertical().show(ui, |ui| {
+				for file in &search.results {
+					let name = file.path.strip_prefix(&search.root).unwrap_or(&file.path);
+					egui::CollapsingHeader::new(format!("{} ({})", name.display(), file.hits.len()))
+						.id_source(&file.path)
+						.default_open(true)
+						.show(ui, |ui| {
+							for hit in &file.hits {
+								let label = format!("{}: {}", hit.line + 1, hit.preview);
+								if ui.selectable_label(false, label).clicked() {
+									opened = Some((file.path.clone(), hit.line, hit.column));
+								}
+							}
+						});
+				}
+			});
+		});
+
+		if let Some((path, line, column)) = opened {
+			self.go_to_position(&path, (line, column), Column::Chars);
+		}
+	}
+
 	pub fn draw_bottom_tray(&mut self, ctx: &egui::Context) {
 		egui::TopBottomPanel::bottom("tray")
 			.default_height(self.font_size * 1.2)
@@ -117,89 +185,477 @@ impl Calcifer {
 	}
 
 	pub fn draw_terminal_panel(&mut self, ctx: &egui::Context) {
+		// sessions in other tabs keep running
+		for terminal in &mut self.terminals {
+			terminal.update();
+		}
 		if !self.terminal_visible {
 			return;
 		}
+		let mut open_location = None;
 		egui::TopBottomPanel::bottom("terminal")
 			.default_height(TERMINAL_HEIGHT)
 			.height_range(Rangef::new(TERMINAL_RANGE.start, TERMINAL_RANGE.end))
 			.resizable(true)
 			.show(ctx, |ui| {
+				self.draw_terminal_tabs(ui);
 				ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
-					let command_color = core::hex_str_to_color(self.theme.functions);
-					let entry_color = core::hex_str_to_color(self.theme.literals);
-					let bg_color = core::hex_str_to_color(self.theme.bg);
+					let command_color = self.theme.color(&self.theme.functions);
+					let entry_color = self.theme.color(&self.theme.literals);
+					let bg_color = self.theme.bg();
+					let status_color = self.theme.color(&self.theme.comments);
 
 					ui.label("");
 
 					ui.horizontal(|ui| {
 						if ui.add(egui::Button::new("⟳")).clicked() {
-							self.command_history.retain(|e| !e.finished);
+							if let Some(terminal) = self.terminals.get_mut(self.selected_terminal) {
+								terminal.entries.retain(|e| !e.finished);
+							}
+							self.terminal_selection = None;
 						}
 						ui.style_mut().visuals.extreme_bg_color = bg_color;
-						let Self { command, .. } = self;
-						ui.colored_label(
-							command_color,
-							format_path(&env::current_dir().unwrap_or_else(|_| PathBuf::from("/"))),
-						);
-						let response = ui.add(
-							egui::TextEdit::singleline(command)
-								.desired_width(f32::INFINITY)
-								.lock_focus(true),
-						);
+						let cwd = match self.terminals.get(self.selected_terminal) {
+							// the line is typed into the running program
+							Some(terminal) if terminal.is_running() => "stdin>".to_string(),
+							Some(terminal) if !terminal.exited => format_path(&terminal.cwd),
+							_ => "(exited)>".to_string(),
+						};
+						let history_cwd = self
+							.terminals
+							.get(self.selected_terminal)
+							.map(|terminal| terminal.cwd.clone())
+							.unwrap_or_default();
+						let input_id = egui::Id::new(TERMINAL_INPUT);
+						let recalled = ui.memory(|m| m.has_focus(input_id))
+							&& self.recall_command(ctx, &history_cwd);
+
+						let searching = self.command_history.search.is_some();
+						let response = if let Some(search) = &mut self.command_history.search {
+							ui.colored_label(command_color, "(reverse-i-search)");
+							ui.add(
+								egui::TextEdit::singleline(&mut search.query)
+									.id(input_id)
+									.desired_width(self.font_size * 10.0)
+									.lock_focus(true),
+							)
+						} else {
+							ui.colored_label(command_color, cwd);
+							ui.add(
+								egui::TextEdit::singleline(&mut self.command)
+									.id(input_id)
+									.desired_width(f32::INFINITY)
+									.lock_focus(true),
+							)
+						};
+						if searching {
+							if response.changed() {
+								if let Some(search) = &mut self.command_history.search {
+									search.restart();
+								}
+							}
+							let found = self.command_history.search_match(&history_cwd).unwrap_or_default();
+							ui.label(found.to_string());
+						}
+						if recalled {
+							if let Some(mut state) = egui::TextEdit::load_state(ctx, input_id) {
+								let end = CCursor::new(self.command.chars().count());
+								state.set_ccursor_range(Some(CCursorRange::one(end)));
+								state.store(ctx, input_id);
+							}
+						}
 
 						if response.lost_focus() && ctx.input(|i| i.key_pressed(egui::Key::Enter)) {
-							self.command_history
-								.push(panels::send_command(self.command.clone()));
+							if let Some(command) = self.command_history.end_search(&history_cwd) {
+								self.command = command;
+							}
+							if !matches!(self.terminals.get(self.selected_terminal), Some(terminal) if !terminal.exited)
+							{
+								let home = self.home.clone();
+								self.open_terminal(ctx, &home);
+							}
+							if let Some(terminal) = self.terminals.get_mut(self.selected_terminal) {
+								if terminal.is_running() {
+									terminal.write(format!("{}\r", self.command).as_bytes());
+								} else {
+									self.command_history.push(&terminal.cwd, &self.command);
+									terminal.send_command(self.command.clone());
+								}
+							}
 							self.command = "".into();
 							response.request_focus();
 						}
 					});
 					ui.separator();
-					egui::ScrollArea::vertical()
+
+					let Some(terminal) = self.terminals.get_mut(self.selected_terminal) else {
+						return;
+					};
+					terminal.set_scrollback(self.settings_menu.scrollback);
+
+					// keys typed while the output has focus go straight to the shell
+					let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+					let (char_width, line_height) =
+						ui.fonts(|f| (f.glyph_width(&font_id, 'M'), f.row_height(&font_id)));
+					let rect = ui.available_rect_before_wrap();
+					terminal.resize(
+						(rect.width() / char_width) as usize,
+						(rect.height() / line_height) as usize,
+					);
+					let rows = panels::output_rows(terminal);
+					let id = ui.id().with("terminal_output");
+					let response = ui.interact(rect, id, egui::Sense::click_and_drag());
+					if response.clicked() {
+						response.request_focus();
+						self.terminal_selection = None;
+					}
+					if response.hovered() {
+						ui.ctx().set_cursor_icon(egui::CursorIcon::Text);
+					}
+					let focused = response.has_focus();
+					if focused {
+						ui.memory_mut(|m| {
+							m.set_focus_lock_filter(
+								id,
+								egui::EventFilter {
+									tab: true,
+									horizontal_arrows: true,
+									vertical_arrows: true,
+									escape: true,
+								},
+							)
+						});
+						let application_cursor = terminal.screen.application_cursor;
+						for event in ui.input(|i| i.events.clone()) {
+							// Ctrl+C copies the selection if there is one, as in most terminals
+							if let (egui::Event::Copy, Some(selection)) = (&event, &self.terminal_selection) {
+								let text = selection.text(terminal, &rows);
+								ui.output_mut(|o| o.copied_text = text);
+								continue;
+							}
+							if let Some(bytes) = panels::event_bytes(&event, application_cursor) {
+								terminal.write(&bytes);
+							}
+						}
+						ui.painter()
+							.rect_stroke(rect, 0.0, egui::Stroke::new(1.0, command_color));
+					}
+
+					if let Some(page) = &terminal.screen.alternate {
+						ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
+							ui.spacing_mut().item_spacing.y = 0.0;
+							for (row, line) in page.lines.iter().enumerate() {
+								let cursor = (focused && terminal.screen.cursor_visible && row == page.row)
+									.then_some(page.col);
+								ui.add(
+									egui::Label::new(terminal_line(line, cursor, entry_color, &font_id))
+										.wrap(false),
+								);
+							}
+						});
+						return;
+					}
+
+					// headers are written in the proportional font, the rows must fit both
+					let row_height = line_height.max(ui.text_style_height(&egui::TextStyle::Body));
+					let selection_color = ui.visuals().selection.bg_fill;
+					let mut grid = None;
+					let mut stop = false;
+					egui::ScrollArea::both()
+						.auto_shrink([false, false])
 						.stick_to_bottom(true)
-						.show(ui, |ui| {
-							ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
-								ui.separator();
-								ui.spacing_mut().item_spacing.y = 0.0;
-								ui.style_mut().visuals.hyperlink_color =
-									core::hex_str_to_color(self.theme.keywords);
-
-								let mut running_command = false;
-
-								for entry in &mut self.command_history {
-									ui.label("");
-									ui.horizontal(|ui| {
-										if !entry.finished {
-											running_command = true;
-											entry.update();
-											let _ = ui.link("(⌛)");
-										} else if ui.link("(🗐)").clicked() {
-											entry.copy_error_code();
-										}
-										ui.colored_label(
-											command_color,
-											format!("{} {}", entry.env, entry.command),
-										);
-									});
-
-									for line in &entry.result {
-										let color = if line.error { RED } else { entry_color };
-										ui.add(
-											egui::Label::new(
-												egui::RichText::new(&line.text)
-													.monospace()
-													.color(color),
-											), //.selectable(true)
-										);
-									}
+						.drag_to_scroll(false)
+						.show_rows(ui, row_height, rows.len(), |ui, visible| {
+							ui.spacing_mut().item_spacing.y = 0.0;
+							ui.style_mut().visuals.hyperlink_color =
+								self.theme.color(&self.theme.keywords);
+							let origin = ui.cursor().min - egui::vec2(0.0, visible.start as f32 * row_height);
+							grid = Some(origin);
+
+							for index in visible {
+								let row_rect = egui::Rect::from_min_size(
+									origin + egui::vec2(0.0, index as f32 * row_height),
+									egui::vec2(ui.available_width(), row_height),
+								);
+								let selected = self.terminal_selection.and_then(|selection| selection.columns(index));
+								let row = rows[index];
+								if let (Some(columns), panels::OutputRow::Line(entry, line)) = (&selected, row) {
+									let length = terminal.output(entry)[line].cells.len();
+									let start = columns.start.min(length) as f32 * char_width;
+									let end = columns.end.min(length + 1) as f32 * char_width;
+									let highlight = egui::Rect::from_x_y_ranges(
+										row_rect.left() + start..=row_rect.left() + end,
+										row_rect.y_range(),
+									);
+									ui.painter().rect_filled(highlight, 0.0, selection_color);
+								} else if selected.is_some() && matches!(row, panels::OutputRow::Header(_)) {
+									ui.painter().rect_filled(row_rect, 0.0, selection_color);
 								}
 
-								self.running_command = running_command;
-							});
+								ui.allocate_ui_with_layout(
+									row_rect.size(),
+									egui::Layout::left_to_right(egui::Align::Center),
+									|ui| {
+										ui.set_min_height(row_height);
+										match row {
+											panels::OutputRow::Gap => (),
+											panels::OutputRow::Header(index) => {
+												let entry = &terminal.entries[index];
+												if entry.finished {
+													if ui.link("(🗐)").clicked() {
+														entry.copy_output();
+													}
+												} else if terminal.is_stopping() {
+													let _ = ui.link("(⌛)");
+												} else if ui
+													.link("(⏹)")
+													.on_hover_text("Interrupt, then kill if it does not stop")
+													.clicked()
+												{
+													stop = true;
+												}
+												ui.add(
+													egui::Label::new(
+														egui::RichText::new(format!("{} {}", entry.env, entry.command))
+															.color(command_color),
+													)
+													.wrap(false),
+												);
+												match entry.status {
+													Some(0) => {
+														ui.colored_label(status_color, "[exit 0]");
+													}
+													Some(status) => {
+														ui.colored_label(RED, format!("[exit {}]", status));
+													}
+													None if entry.finished => {
+														ui.colored_label(RED, "[shell exited]");
+													}
+													None => (),
+												}
+											}
+											panels::OutputRow::Dropped(index) => {
+												let dropped = terminal.entries[index].dropped;
+												ui.colored_label(
+													status_color,
+													format!("[{} earlier lines dropped]", dropped),
+												);
+											}
+											panels::OutputRow::Line(index, line) => {
+												let entry = &terminal.entries[index];
+												let line = &terminal.output(index)[line];
+												let Some(location) =
+													terminal_output_line(ui, line, entry_color, &font_id)
+												else {
+													return;
+												};
+												// after a `cd` in the same command, try from where the shell ended up
+												let path = [&entry.cwd, &terminal.cwd]
+													.iter()
+													.map(|cwd| cwd.join(&location.path))
+													.find(|path| path.is_file());
+												if let Some(path) = path {
+													open_location = Some((path, location));
+												}
+											}
+										}
+									},
+								);
+							}
 						});
+					if stop {
+						terminal.stop();
+					}
+					if terminal.is_stopping() {
+						ctx.request_repaint_after(panels::STOP_TIMEOUT);
+					}
+
+					// (row, char column) of the boundary nearest to a point of the output
+					let cell_at = |pos: egui::Pos2| {
+						let origin = grid?;
+						let row = ((pos.y - origin.y) / row_height).floor().max(0.0) as usize;
+						let column = ((pos.x - origin.x) / char_width).round().max(0.0) as usize;
+						Some((row.min(rows.len().checked_sub(1)?), column))
+					};
+					if response.drag_started() {
+						let origin = ui.input(|i| i.pointer.press_origin());
+						self.terminal_selection =
+							origin.and_then(cell_at).map(panels::OutputSelection::new);
+					} else if response.dragged() {
+						let head = ui.input(|i| i.pointer.interact_pos()).and_then(cell_at);
+						if let (Some(selection), Some(head)) = (&mut self.terminal_selection, head) {
+							selection.head = head;
+						}
+					}
+					response.context_menu(|ui| {
+						if let Some(selection) = self.terminal_selection {
+							if ui.button("Copy").clicked() {
+								let text = selection.text(terminal, &rows);
+								ui.output_mut(|o| o.copied_text = text);
+								ui.close_menu();
+							}
+						}
+						if ui.button("Copy all").clicked() {
+							let lines: Vec<String> =
+								rows.iter().map(|row| panels::row_text(terminal, *row)).collect();
+							ui.output_mut(|o| o.copied_text = lines.join("\n"));
+							ui.close_menu();
+						}
+					});
 				});
 			});
+
+		if let Some((path, location)) = open_location {
+			self.go_to_position(
+				&path,
+				(location.line - 1, location.column - 1),
+				Column::Chars,
+			);
+		}
+	}
+
+	pub fn draw_problems_panel(&mut self, ctx: &egui::Context) {
+		self.problems.poll();
+		if !self.problems.visible {
+			return;
+		}
+		let mut selected = None;
+		egui::TopBottomPanel::bottom("problems")
+			.default_height(TERMINAL_HEIGHT)
+			.height_range(Rangef::new(TERMINAL_RANGE.start, TERMINAL_RANGE.end))
+			.resizable(true)
+			.show(ctx, |ui| {
+				let hint_color = self.theme.color(&self.theme.comments);
+				ui.horizontal(|ui| {
+					if ui.add(egui::Button::new("⟳")).on_hover_text("cargo check").clicked() {
+						let path = self.tabs[self.selected_tab].path.clone();
+						self.problems.check(ctx, &path);
+					}
+					if self.problems.running {
+						ui.spinner();
+					}
+					ui.label(format_path(&self.problems.root));
+					ui.colored_label(RED, format!("{} errors", self.problems.count(1)));
+					ui.colored_label(YELLOW, format!("{} warnings", self.problems.count(2)));
+					if let Some(error) = &self.problems.error {
+						ui.colored_label(RED, error);
+					}
+				});
+				ui.separator();
+				egui::ScrollArea::vertical()
+					.auto_shrink([false, false])
+					.show(ui, |ui| {
+						for (index, problem) in self.problems.items.iter().enumerate() {
+							let path = problem
+								.path
+								.strip_prefix(&self.problems.root)
+								.unwrap_or(&problem.path);
+							ui.horizontal(|ui| {
+								ui.colored_label(severity_color(problem.severity, hint_color), "●");
+								let text = format!(
+									"{}  {}:{}:{}",
+									problem.message,
+									path.display(),
+									problem.start.0 + 1,
+									problem.start.1 + 1
+								);
+								if ui
+									.selectable_label(self.problems.selected == Some(index), text)
+									.clicked()
+								{
+									selected = Some(index);
+								}
+							});
+						}
+					});
+			});
+
+		if let Some(index) = selected {
+			self.problems.selected = Some(index);
+			let problem = &self.problems.items[index];
+			let (path, start) = (problem.path.clone(), problem.start);
+			self.go_to_position(&path, start, Column::Chars);
+		}
+	}
+
+	/// Up/Down and Ctrl+R in the terminal input, true when they replaced the typed line
+	fn recall_command(&mut self, ctx: &egui::Context, cwd: &Path) -> bool {
+		let pressed = |key| ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, key));
+		if ctx.input_mut(|i| i.consume_key(egui::Modifiers::CTRL, egui::Key::R)) {
+			self.command_history.search_older(cwd);
+			return false;
+		}
+		let recalled = if self.command_history.search.is_some() {
+			if !pressed(egui::Key::Escape) {
+				return false;
+			}
+			// like the shell, Escape keeps the match to edit it
+			self.command_history.end_search(cwd)
+		} else if pressed(egui::Key::ArrowUp) {
+			self.command_history.previous(cwd, &self.command)
+		} else if pressed(egui::Key::ArrowDown) {
+			self.command_history.next(cwd)
+		} else {
+			None
+		};
+		match recalled {
+			Some(command) => {
+				self.command = command;
+				true
+			}
+			None => false,
+		}
+	}
+
+	fn draw_terminal_tabs(&mut self, ui: &mut egui::Ui) {
+		let mut selected = None;
+		let mut closed = None;
+		ui.horizontal(|ui| {
+			for (index, terminal) in self.terminals.iter_mut().enumerate() {
+				let state = if terminal.exited {
+					" (exited)"
+				} else if terminal.is_running() {
+					" ⌛"
+				} else {
+					""
+				};
+				let response = ui
+					.selectable_label(
+						index == self.selected_terminal,
+						format!("{}{}", terminal.name, state),
+					)
+					.on_hover_text("Right click to rename or close");
+				if response.clicked() {
+					selected = Some(index);
+				}
+				response.context_menu(|ui| {
+					ui.text_edit_singleline(&mut terminal.name);
+					if ui.button("Close").clicked() {
+						closed = Some(index);
+						ui.close_menu();
+					}
+				});
+			}
+			if ui.button("➕").on_hover_text("New terminal").clicked() {
+				let cwd = self
+					.terminals
+					.get(self.selected_terminal)
+					.map_or(self.home.clone(), |terminal| terminal.cwd.clone());
+				self.add_terminal(ui.ctx(), &cwd);
+				self.terminal_selection = None;
+			}
+		});
+		// selections are rows of the shown terminal
+		if selected.is_some() || closed.is_some() {
+			self.terminal_selection = None;
+		}
+		if let Some(index) = selected {
+			self.selected_terminal = index;
+		}
+		if let Some(index) = closed {
+			self.close_terminal(index);
+		}
+		ui.separator();
 	}
 
 	pub fn draw_tab_panel(&mut self, ctx: &egui::Context) {
@@ -209,24 +665,26 @@ impl Calcifer {
 				ui.painter().rect_filled(
 					ui.available_rect_before_wrap(),
 					0.0,
-					core::hex_str_to_color(self.theme.bg),
+					self.theme.bg(),
 				);
 				let response = StripBuilder::new(ui)
 					.sizes(Size::remainder(), self.tab_area_size())
 					.sense(egui::Sense::click())
 					.horizontal(|mut strip| {
-						for (index, tab) in self.tabs.clone().iter().enumerate() {
+						let labels: Vec<(bool, String)> =
+							self.tabs.iter().map(|tab| (tab.saved, tab.get_name())).collect();
+						for (index, (saved, name)) in labels.into_iter().enumerate() {
 							strip.cell(|ui| {
-								let mut color = core::hex_str_to_color(self.theme.comments);
+								let mut color = self.theme.color(&self.theme.comments);
 								if self.selected_tab == index {
 									ui.painter().rect_filled(
 										ui.available_rect_before_wrap(),
 										0.0,
-										core::hex_str_to_color(self.theme.functions),
+										self.theme.color(&self.theme.functions),
 									);
-									color = core::hex_str_to_color(self.theme.bg)
+									color = self.theme.bg()
 								}
-								let unsaved_indicator = if tab.saved { "" } else { "~ " };
+								let unsaved_indicator = if saved { "" } else { "~ " };
 								ui.with_layout(
 									egui::Layout::right_to_left(egui::Align::TOP),
 									|ui| {
@@ -241,7 +699,7 @@ impl Calcifer {
 											.clicked()
 											&& !self.close_tab_confirm.visible
 										{
-											if tab.saved {
+											if saved {
 												self.delete_tab(index);
 											} else {
 												self.close_tab_confirm.ask();
@@ -257,7 +715,7 @@ impl Calcifer {
 															egui::RichText::new(format!(
 																" {}{}",
 																unsaved_indicator,
-																tab.get_name()
+																name
 															))
 															.color(color),
 														)
@@ -301,7 +759,7 @@ impl Calcifer {
 			}
 			ui.horizontal(|ui| {
 				ui.style_mut().visuals.hyperlink_color =
-					core::hex_str_to_color(self.theme.comments);
+					self.theme.color(&self.theme.comments);
 				if ui
 					.link(
 						self.tabs[self.selected_tab]
@@ -325,6 +783,31 @@ impl Calcifer {
 					self.tree_visible = true;
 					self.file_tree = None;
 				}
+
+				if self.tabs[self.selected_tab].language == PROJECT_EXTENSION {
+					return;
+				}
+				ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+					let tab = &mut self.tabs[self.selected_tab];
+					let auto = format!(
+						"Auto ({})",
+						self.languages
+							.detect(&tab.path, &tab.code)
+							.map_or("Text", |language| language.name())
+					);
+					egui::ComboBox::from_id_source("language_override")
+						.selected_text(tab.language_override.clone().unwrap_or(auto.clone()))
+						.show_ui(ui, |ui| {
+							ui.selectable_value(&mut tab.language_override, None, auto);
+							for name in self.languages.names() {
+								ui.selectable_value(
+									&mut tab.language_override,
+									Some(name.clone()),
+									name,
+								);
+							}
+						});
+				});
 			});
 
 			ui.separator();
@@ -342,57 +825,141 @@ impl Calcifer {
 		let mut override_cursor: Option<CCursorRange> = None;
 
 		if !self.search_menu.result_selected {
-			override_cursor = Some(CCursorRange::two(
-				CCursor::new(self.search_menu.get_cursor_start()),
-				CCursor::new(self.search_menu.get_cursor_end()),
-			));
+			let (start, end) = self.search_menu.get_cursor_range(&current_tab.code);
+			override_cursor = Some(CCursorRange::two(CCursor::new(start), CCursor::new(end)));
 			self.search_menu.result_selected = true;
 		}
 
+		if let Some(index) = current_tab.pending_cursor.take() {
+			override_cursor = Some(CCursorRange::one(CCursor::new(index)));
+			let (row, _) = position::char_to_line_col(&current_tab.code, index, Column::Chars);
+			// folded lines above take no room
+			let syntax = self.languages.syntax(
+				&current_tab.path,
+				&current_tab.code,
+				current_tab.language_override.as_deref(),
+			);
+			let ranges = editor::folds::cached_fold_ranges(ui.ctx(), &syntax, &current_tab.code);
+			let hidden: usize = editor::folds::hidden_lines(&current_tab.folds, &ranges)
+				.iter()
+				.filter(|lines| *lines.end() < row)
+				.map(|lines| lines.clone().count())
+				.sum();
+			let row_height = ui.fonts(|f| f.row_height(&egui::FontId::monospace(self.font_size)));
+			current_tab.scroll_offset = row_height * (row - hidden).saturating_sub(5) as f32;
+		}
+
 		let tab_id = current_tab.path.clone().to_string_lossy().to_string();
+		let diagnostics = self.lsp.diagnostics(current_tab);
+		let problems: Vec<&panels::Problem> = self.problems.in_file(&current_tab.path).collect();
+		let hint_color = self.theme.color(&self.theme.comments);
+		let mut underlines = diagnostic_underlines(&current_tab.code, &diagnostics, hint_color);
+		underlines.extend(problem_underlines(&current_tab.code, &problems, hint_color));
 
-		if self.got_focus {
-			CodeEditor::default()
-				.id_source(&tab_id)
-				.with_rows(max(45, lines))
-				.with_fontsize(self.font_size)
-				.with_theme(self.theme)
-				.with_syntax(to_syntax(&current_tab.language))
-				.with_numlines(true)
-				.show(
-					ui,
-					&mut current_tab.code.clone(),
-					&mut current_tab.saved.clone(),
-					&mut current_tab.last_cursor.clone(),
-					&mut current_tab.scroll_offset.clone(),
-					override_cursor.clone(),
-				);
-			return;
-		}
+		// the most severe problem of a line gives its marker
+		let mut severities: Vec<(usize, u8)> = diagnostics
+			.iter()
+			.map(|diagnostic| (diagnostic.start.0, diagnostic.severity))
+			.chain(problems.iter().map(|problem| (problem.start.0, problem.severity)))
+			.collect();
+		severities.sort_by_key(|&(_, severity)| severity);
+		let markers = severities
+			.into_iter()
+			.map(|(line, severity)| (line, severity_color(severity, hint_color)))
+			.collect();
 
-		CodeEditor::default()
+		let mut editor = CodeEditor::default()
 			.id_source(&tab_id)
 			.with_rows(max(45, lines))
 			.with_fontsize(self.font_size)
-			.with_theme(self.theme)
-			.with_syntax(to_syntax(&current_tab.language))
-			.with_numlines(true)
-			.show(
+			.with_theme(self.theme.clone())
+			.with_syntax(self.languages.syntax(
+				&current_tab.path,
+				&current_tab.code,
+				current_tab.language_override.as_deref(),
+			))
+			.with_indentation(current_tab.indentation)
+			.with_underlines(underlines)
+			.with_markers(markers)
+			.with_highlights(self.search_menu.highlights(ui.ctx(), &current_tab.code))
+			.with_numlines(true);
+
+		if self.got_focus {
+			editor.show(
 				ui,
-				&mut current_tab.code,
-				&mut current_tab.saved,
-				&mut current_tab.last_cursor,
-				&mut current_tab.scroll_offset,
-				override_cursor,
+				&mut current_tab.code.clone(),
+				&mut current_tab.saved.clone(),
+				&mut current_tab.last_cursor.clone(),
+				&mut current_tab.scroll_offset.clone(),
+				override_cursor.clone(),
+				&mut UndoHistory::new(&current_tab.code),
+				&mut current_tab.extra_cursors.clone(),
+				&mut current_tab.folds.clone(),
 			);
+			return;
+		}
+
+		let hovered = editor.show(
+			ui,
+			&mut current_tab.code,
+			&mut current_tab.saved,
+			&mut current_tab.last_cursor,
+			&mut current_tab.scroll_offset,
+			override_cursor,
+			&mut current_tab.history,
+			&mut current_tab.extra_cursors,
+			&mut current_tab.folds,
+		);
+
+		let Some(index) = hovered else {
+			return;
+		};
+		if ui.input(|i| i.pointer.time_since_last_movement()) < HOVER_DELAY as f64 {
+			ui.ctx()
+				.request_repaint_after(Duration::from_secs_f32(HOVER_DELAY));
+			return;
+		}
+
+		let mut messages: Vec<String> = diagnostics
+			.iter()
+			.filter(|diagnostic| {
+				let start = lsp::position_to_char(&current_tab.code, diagnostic.start);
+				let end = lsp::position_to_char(&current_tab.code, diagnostic.end);
+				start <= index && index <= max(end, start + 1)
+			})
+			.map(|diagnostic| diagnostic.message.clone())
+			.collect();
+		if let Some(text) = self.lsp.hover(current_tab, index) {
+			messages.push(text);
+		}
+		if !messages.is_empty() {
+			egui::show_tooltip_at_pointer(ui.ctx(), egui::Id::new("hover_tooltip"), |ui| {
+				ui.label(egui::RichText::new(messages.join("\n\n")).monospace());
+			});
+		}
 	}
 
 	fn draw_project_file(&mut self, ctx: &egui::Context, ui: &mut egui::Ui) {
 		let current_tab = &mut self.tabs[self.selected_tab];
 
+		// the board has no text edit to catch Ctrl+Z, its changes are undone on the code
+		if ctx.memory(|m| m.focus().is_none()) {
+			let pressed = |key| ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, key));
+			let restored = if pressed(egui::Key::Z) {
+				current_tab.history.undo(&mut current_tab.code)
+			} else if pressed(egui::Key::Y) {
+				current_tab.history.redo(&mut current_tab.code)
+			} else {
+				None
+			};
+			if restored.is_some() {
+				current_tab.saved = false;
+			}
+		}
+
 		self.project_content
 			.update_from_code(current_tab.code.clone());
-		panels::draw_project(ui, self.theme, &mut self.project_content);
+		panels::draw_project(ui, &self.theme, &mut self.project_content);
 
 		self.project_content.selected_item.category = min(
 			self.project_content.categories.len() - 2,
@@ -458,6 +1025,7 @@ impl Calcifer {
 				if current_tab.code != code {
 					current_tab.code = code;
 					current_tab.saved = false;
+					current_tab.history.record(&current_tab.code, None, EditKind::Typing);
 				}
 			}
 			Err(_err) => (),
@@ -467,7 +1035,7 @@ impl Calcifer {
 	pub fn draw_windows(&mut self, ctx: &egui::Context) {
 		if self.search_menu.visible {
 			self.search_menu
-				.show(ctx, &mut self.tabs, &mut self.selected_tab);
+				.show(ctx, &mut self.tabs, &mut self.selected_tab, &self.home);
 		}
 		if self.close_tab_confirm.visible {
 			self.close_tab_confirm.show(ctx);
@@ -478,6 +1046,9 @@ impl Calcifer {
 		if self.exit_confirm.visible {
 			self.exit_confirm.show(ctx);
 		}
+		if self.conflict_confirm.visible {
+			self.conflict_confirm.show(ctx);
+		}
 		if self.exit_confirm.proceed {
 			for tab in self.tabs.iter_mut() {
 				tab.saved = true;
@@ -487,11 +1058,30 @@ impl Calcifer {
 		if self.shortcuts_menu.visible {
 			self.shortcuts_menu.show(ctx);
 		}
+		if self.task_menu.visible {
+			self.task_menu.show(ctx);
+		}
+		if let Some(task) = self.task_menu.chosen.take() {
+			self.run_task(ctx, task);
+		}
+		if self.goto_menu.visible {
+			self.goto_menu.show(ctx);
+		}
+		if let Some(line_col) = self.goto_menu.chosen.take() {
+			let tab = &mut self.tabs[self.selected_tab];
+			tab.pending_cursor = Some(position::line_col_to_char(&tab.code, line_col, Column::Chars));
+		}
+		if self.outline_menu.visible {
+			self.outline_menu.show(ctx);
+		}
+		if let Some(index) = self.outline_menu.chosen.take() {
+			self.tabs[self.selected_tab].pending_cursor = Some(index);
+		}
 		if self.settings_menu.visible {
 			self.settings_menu.show(ctx);
 		}
 		if self.settings_menu.updated {
-			self.theme = self.settings_menu.theme;
+			self.theme = self.settings_menu.theme.clone();
 		}
 
 		self.handle_confirm();
@@ -512,13 +1102,13 @@ impl Calcifer {
 							.show(ctx, |ui| {
 								let (bg_color, text_color) = if self.selected_tab == index {
 									(
-										core::hex_str_to_color(self.theme.functions),
-										core::hex_str_to_color(self.theme.bg),
+										self.theme.color(&self.theme.functions),
+										self.theme.bg(),
 									)
 								} else {
 									(
-										core::hex_str_to_color(self.theme.bg),
-										core::hex_str_to_color(self.theme.comments),
+										self.theme.bg(),
+										self.theme.color(&self.theme.comments),
 									)
 								};
 
@@ -580,15 +1170,9 @@ impl Calcifer {
 							let final_index = drop_index.min(self.tabs.len() - 1);
 							if final_index == initial_index {
 								return;
-							} else if final_index < initial_index {
-								self.tabs
-									.insert(final_index, self.tabs[initial_index].clone());
-								self.tabs.remove(initial_index + 1);
-							} else {
-								self.tabs
-									.insert(final_index + 1, self.tabs[initial_index].clone());
-								self.tabs.remove(initial_index);
 							}
+							let tab = self.tabs.remove(initial_index);
+							self.tabs.insert(final_index, tab);
 
 							if self.selected_tab == initial_index {
 								self.selected_tab = final_index;
@@ -613,13 +1197,55 @@ impl Calcifer {
 	}
 }
 
-fn to_syntax(language: &str) -> Syntax {
-	match language {
-		"py" => Syntax::python(),
-		"rs" => Syntax::rust(),
-		"js" => Syntax::javascript(),
-		"dr" => Syntax::pendragon(),
-		_ => Syntax::shell(),
+fn diagnostic_underlines(
+	code: &str,
+	diagnostics: &[lsp::Diagnostic],
+	hint_color: egui::Color32,
+) -> Vec<Underline> {
+	diagnostics
+		.iter()
+		.map(|diagnostic| {
+			let start = lsp::position_to_byte(code, diagnostic.start);
+			let mut end = lsp::position_to_byte(code, diagnostic.end);
+			if end <= start {
+				// make empty ranges visible by underlining the next char
+				end = start + code[start..].chars().next().map_or(0, |c| c.len_utf8());
+			}
+			Underline {
+				range: start..end,
+				color: severity_color(diagnostic.severity, hint_color),
+			}
+		})
+		.collect()
+}
+
+fn problem_underlines(
+	code: &str,
+	problems: &[&panels::Problem],
+	hint_color: egui::Color32,
+) -> Vec<Underline> {
+	problems
+		.iter()
+		.map(|problem| {
+			let start = position::line_col_to_byte(code, problem.start, Column::Chars);
+			let mut end = position::line_col_to_byte(code, problem.end, Column::Chars);
+			if end <= start {
+				end = start + code[start..].chars().next().map_or(0, |c| c.len_utf8());
+			}
+			Underline {
+				range: start..end,
+				color: severity_color(problem.severity, hint_color),
+			}
+		})
+		.collect()
+}
+
+/// Diagnostics and build problems share the language server severities
+fn severity_color(severity: u8, hint_color: egui::Color32) -> egui::Color32 {
+	match severity {
+		1 => RED,
+		2 => YELLOW,
+		_ => hint_color,
 	}
 }
 
@@ -662,3 +1288,100 @@ fn floor_f32(value: f32) -> Option<usize> {
 		Some(value.floor() as usize)
 	}
 }
+
+/// A line of terminal output with its colors, `cursor` is drawn as an inverted cell
+fn terminal_line(
+	line: &panels::Line,
+	cursor: Option<usize>,
+	foreground: egui::Color32,
+	font_id: &egui::FontId,
+) -> egui::text::LayoutJob {
+	let mut line = line.clone();
+	if let Some(col) = cursor {
+		if line.cells.len() <= col {
+			line.cells.resize(col + 1, panels::Cell::default());
+		}
+		line.cells[col].style.inverse = !line.cells[col].style.inverse;
+	}
+
+	let color = |color: panels::TermColor, default: egui::Color32| {
+		color
+			.rgb()
+			.map_or(default, |(r, g, b)| egui::Color32::from_rgb(r, g, b))
+	};
+	let mut job = egui::text::LayoutJob::default();
+	for (text, style) in line.spans() {
+		let mut text_color = color(style.foreground, foreground);
+		let mut background = color(style.background, egui::Color32::TRANSPARENT);
+		if style.inverse {
+			(text_color, background) = (
+				color(style.background, egui::Color32::BLACK),
+				color(style.foreground, foreground),
+			);
+		}
+		job.append(
+			&text,
+			0.0,
+			egui::TextFormat {
+				font_id: font_id.clone(),
+				color: text_color,
+				background,
+				italics: style.italic,
+				underline: if style.underline {
+					egui::Stroke::new(1.0, text_color)
+				} else {
+					egui::Stroke::NONE
+				},
+				..Default::default()
+			},
+		);
+	}
+	job
+}
+
+/// A line of terminal output where file locations can be clicked, returns the clicked one
+fn terminal_output_line(
+	ui: &mut egui::Ui,
+	line: &panels::Line,
+	foreground: egui::Color32,
+	font_id: &egui::FontId,
+) -> Option<panels::FileLocation> {
+	let locations: Vec<&panels::FileLocation> = line
+		.locations
+		.iter()
+		.filter(|location| location.range.end <= line.cells.len())
+		.collect();
+	if locations.is_empty() {
+		ui.add(egui::Label::new(terminal_line(line, None, foreground, font_id)).wrap(false));
+		return None;
+	}
+
+	let link = ui.visuals().hyperlink_color;
+	let mut line = line.clone();
+	for location in &locations {
+		for cell in &mut line.cells[location.range.clone()] {
+			cell.style.foreground = panels::TermColor::Rgb(link.r(), link.g(), link.b());
+			cell.style.underline = true;
+		}
+	}
+	let job = terminal_line(&line, None, foreground, font_id);
+	let galley = ui.fonts(|f| f.layout_job(job));
+	let (rect, response) = ui.allocate_exact_size(galley.size(), egui::Sense::click());
+	let hovered = response.hover_pos().and_then(|pos| {
+		let index = galley.cursor_from_pos(pos - rect.min).ccursor.index;
+		locations
+			.into_iter()
+			.find(|location| location.range.contains(&index))
+			.cloned()
+	});
+	ui.painter().galley(rect.min, galley, foreground);
+
+	if hovered.is_some() {
+		ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+	}
+	if response.clicked() {
+		hovered
+	} else {
+		None
+	}
+}
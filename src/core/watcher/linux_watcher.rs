@@ -0,0 +1,127 @@
+use eframe::egui;
+use nix::{
+    errno::Errno,
+    sys::inotify::{AddWatchFlags, InitFlags, Inotify, WatchDescriptor},
+};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+};
+
+pub struct FileWatcher {
+    started: bool,
+    inotify: Option<Arc<Inotify>>,
+    directories: Arc<Mutex<HashMap<WatchDescriptor, PathBuf>>>,
+    receiver: Option<mpsc::Receiver<PathBuf>>,
+}
+
+impl FileWatcher {
+    pub fn new() -> Self {
+        Self {
+            started: false,
+            inotify: None,
+            directories: Arc::new(Mutex::new(HashMap::new())),
+            receiver: None,
+        }
+    }
+
+    /// Keep one watch per directory containing an open file.
+    /// Directories are watched rather than files so that atomic replaces
+    /// (write to a temporary file then rename) are still noticed.
+    pub fn watch(&mut self, ctx: &egui::Context, files: &[PathBuf]) {
+        if !self.started {
+            self.start(ctx);
+        }
+        let Some(inotify) = &self.inotify else {
+            return;
+        };
+
+        let mut wanted: Vec<PathBuf> = vec![];
+        for file in files {
+            if let Some(parent) = file.parent() {
+                if !parent.as_os_str().is_empty() && !wanted.iter().any(|dir| dir == parent) {
+                    wanted.push(parent.to_path_buf());
+                }
+            }
+        }
+
+        let mut directories = self.directories.lock().unwrap();
+        directories.retain(|descriptor, dir| {
+            if wanted.contains(dir) {
+                return true;
+            }
+            let _ = inotify.rm_watch(*descriptor);
+            false
+        });
+
+        for dir in wanted {
+            if directories.values().any(|watched| watched == &dir) {
+                continue;
+            }
+            match inotify.add_watch(
+                &dir,
+                AddWatchFlags::IN_CLOSE_WRITE | AddWatchFlags::IN_MOVED_TO,
+            ) {
+                Ok(descriptor) => {
+                    directories.insert(descriptor, dir);
+                }
+                Err(err) => eprintln!("Could not watch {} : {}", dir.display(), err),
+            }
+        }
+    }
+
+    /// Files written or replaced since the last call
+    pub fn changed_files(&self) -> Vec<PathBuf> {
+        let mut changed: Vec<PathBuf> = vec![];
+        if let Some(receiver) = &self.receiver {
+            for path in receiver.try_iter() {
+                if !changed.contains(&path) {
+                    changed.push(path);
+                }
+            }
+        }
+        changed
+    }
+
+    fn start(&mut self, ctx: &egui::Context) {
+        self.started = true;
+
+        let inotify = match Inotify::init(InitFlags::IN_CLOEXEC) {
+            Ok(inotify) => Arc::new(inotify),
+            Err(err) => {
+                eprintln!("Could not start file watcher : {}", err);
+                return;
+            }
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        let thread_inotify = inotify.clone();
+        let directories = self.directories.clone();
+        let ctx = ctx.clone();
+
+        thread::spawn(move || loop {
+            let events = match thread_inotify.read_events() {
+                Ok(events) => events,
+                Err(Errno::EINTR) => continue,
+                Err(_) => return,
+            };
+            for event in events {
+                let Some(name) = event.name else {
+                    continue;
+                };
+                let dir = directories.lock().unwrap().get(&event.wd).cloned();
+                if let Some(dir) = dir {
+                    if sender.send(dir.join(name)).is_err() {
+                        return;
+                    }
+                }
+            }
+            ctx.request_repaint();
+        });
+
+        self.inotify = Some(inotify);
+        self.receiver = Some(receiver);
+    }
+}
@@ -0,0 +1,85 @@
+#[allow(unused_imports)]
+#[cfg(target_os = "linux")]
+mod linux_watcher;
+#[cfg(target_os = "linux")]
+pub use linux_watcher::*;
+
+#[cfg(target_os = "windows")]
+mod windows_watcher;
+#[cfg(target_os = "windows")]
+pub use windows_watcher::*;
+
+#[cfg(test)]
+mod tests;
+
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Line based diff between the buffer and the file on disk, in unified style
+pub fn line_diff(mine: &str, theirs: &str, name: &str) -> String {
+    let old: Vec<&str> = mine.lines().collect();
+    let new: Vec<&str> = theirs.lines().collect();
+
+    let prefix = old
+        .iter()
+        .zip(new.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let mut result = format!("--- {} (unsaved)\n+++ {} (on disk)\n", name, name);
+    result.push_str(&format!(
+        "@@ -{},{} +{},{} @@\n",
+        prefix + 1,
+        old_middle.len(),
+        prefix + 1,
+        new_middle.len()
+    ));
+
+    if old_middle.len() * new_middle.len() > MAX_DIFF_CELLS {
+        for line in old_middle {
+            result.push_str(&format!("-{}\n", line));
+        }
+        for line in new_middle {
+            result.push_str(&format!("+{}\n", line));
+        }
+        return result;
+    }
+
+    // longest common subsequence table, lcs[i][j] is computed on old[i..] and new[j..]
+    let mut lcs = vec![vec![0_usize; new_middle.len() + 1]; old_middle.len() + 1];
+    for i in (0..old_middle.len()).rev() {
+        for j in (0..new_middle.len()).rev() {
+            lcs[i][j] = if old_middle[i] == new_middle[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < old_middle.len() || j < new_middle.len() {
+        if i < old_middle.len() && j < new_middle.len() && old_middle[i] == new_middle[j] {
+            result.push_str(&format!(" {}\n", old_middle[i]));
+            i += 1;
+            j += 1;
+        } else if i < old_middle.len() && (j == new_middle.len() || lcs[i + 1][j] >= lcs[i][j + 1])
+        {
+            result.push_str(&format!("-{}\n", old_middle[i]));
+            i += 1;
+        } else {
+            result.push_str(&format!("+{}\n", new_middle[j]));
+            j += 1;
+        }
+    }
+
+    result
+}
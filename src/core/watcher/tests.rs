@@ -0,0 +1,27 @@
+use super::*;
+
+const HEADER: &str = "--- main.rs (unsaved)\n+++ main.rs (on disk)\n";
+
+#[test]
+fn identical_texts_have_no_change() {
+    let diff = line_diff("a\nb\n", "a\nb\n", "main.rs");
+    assert_eq!(diff, format!("{}@@ -3,0 +3,0 @@\n", HEADER));
+}
+
+#[test]
+fn inserted_and_removed_lines() {
+    let inserted = line_diff("a\nc", "a\nb\nc", "main.rs");
+    assert_eq!(inserted, format!("{}@@ -2,0 +2,1 @@\n+b\n", HEADER));
+
+    let removed = line_diff("a\nb\nc", "a\nc", "main.rs");
+    assert_eq!(removed, format!("{}@@ -2,1 +2,0 @@\n-b\n", HEADER));
+}
+
+#[test]
+fn changed_lines_around_a_common_one() {
+    let diff = line_diff("a\nb\nc\nd", "a\nx\nc\ny", "main.rs");
+    assert_eq!(
+        diff,
+        format!("{}@@ -2,3 +2,3 @@\n-b\n+x\n c\n-d\n+y\n", HEADER)
+    );
+}
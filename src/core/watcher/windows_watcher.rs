@@ -0,0 +1,18 @@
+// Placeholder
+
+use eframe::egui;
+use std::path::PathBuf;
+
+pub struct FileWatcher {}
+
+impl FileWatcher {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn watch(&mut self, _ctx: &egui::Context, _files: &[PathBuf]) {}
+
+    pub fn changed_files(&self) -> Vec<PathBuf> {
+        vec![]
+    }
+}
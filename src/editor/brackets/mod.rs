@@ -0,0 +1,254 @@
+//! Brackets and quotes: finding the match of a bracket, closing pairs as they are
+//! typed and indenting the lines they open. Strings and comments are left alone,
+//! as told by the lexer.
+
+#[cfg(test)]
+mod tests;
+
+use egui::{text::CCursor, text_edit::CCursorRange};
+use std::{ops::Range, sync::Arc};
+
+use super::cursors::{apply_edits, range};
+use super::highlighting::Token;
+use super::{Indentation, Syntax, TokenType};
+use eframe::egui;
+
+pub const PAIRS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+const PAIRED_QUOTES: [char; 2] = ['"', '\''];
+/// Chars after which a typed quote opens a string rather than ending a word
+const BEFORE_QUOTE: &str = "([{,=:;!|";
+
+/// The bracket closing `c`, if it opens a pair
+pub fn closing_of(c: char) -> Option<char> {
+    PAIRS
+        .iter()
+        .find(|(open, _)| *open == c)
+        .map(|(_, close)| *close)
+}
+
+fn is_closing(c: char) -> bool {
+    PAIRS.iter().any(|(_, close)| *close == c)
+}
+
+/// Whether a pair typed before `next` gets closed right away
+fn closes_before(next: Option<char>) -> bool {
+    next.is_none_or(|c| c.is_whitespace() || is_closing(c) || c == ',' || c == ';')
+}
+
+/// For each char of `text`, whether it is code rather than part of a string or a comment
+pub fn code_chars(syntax: &Syntax, text: &str) -> Vec<bool> {
+    let mut code: Vec<bool> = Token::default()
+        .tokens(syntax, text)
+        .iter()
+        .flat_map(|token| {
+            let is_code = !matches!(token.ty(), TokenType::Str(_) | TokenType::Comment(_));
+            token.buffer().chars().map(move |_| is_code)
+        })
+        .collect();
+    code.resize(text.chars().count(), true);
+    code
+}
+
+#[derive(Default)]
+pub struct CodeComputer;
+
+impl egui::util::cache::ComputerMut<(&Syntax, &str), Arc<[bool]>> for CodeComputer {
+    fn compute(&mut self, (syntax, text): (&Syntax, &str)) -> Arc<[bool]> {
+        code_chars(syntax, text).into()
+    }
+}
+
+pub type CodeCache = egui::util::cache::FrameCache<Arc<[bool]>, CodeComputer>;
+
+/// Code map of `text`, lexed again only when it changes
+pub fn cached_code_chars(ctx: &egui::Context, syntax: &Syntax, text: &str) -> Arc<[bool]> {
+    ctx.memory_mut(|mem| mem.caches.cache::<CodeCache>().get((syntax, text)))
+}
+
+/// Whether the char index `index` is in code rather than inside a string or a comment
+pub fn in_code(syntax: &Syntax, text: &str, index: usize) -> bool {
+    let before: String = text.chars().take(index).collect();
+    !matches!(
+        Token::open_at_end(syntax, &before),
+        TokenType::Str(_) | TokenType::Comment(_)
+    )
+}
+
+/// `in_code` for each char index of `text` and its end, lexing it once
+pub fn code_positions(syntax: &Syntax, text: &str) -> Vec<bool> {
+    Token::open_along(syntax, text)
+        .into_iter()
+        .map(|ty| !matches!(ty, TokenType::Str(_) | TokenType::Comment(_)))
+        .collect()
+}
+
+/// The bracket at `index`, or else just before it, with the one matching it
+pub fn pair_at(
+    ctx: &egui::Context,
+    syntax: &Syntax,
+    text: &str,
+    index: usize,
+) -> Option<(usize, usize)> {
+    let is_bracket = |at: usize| {
+        text.chars()
+            .nth(at)
+            .is_some_and(|c| closing_of(c).is_some() || is_closing(c))
+    };
+    let candidates: Vec<usize> = [Some(index), index.checked_sub(1)]
+        .into_iter()
+        .flatten()
+        .filter(|&at| is_bracket(at))
+        .collect();
+    if candidates.is_empty() {
+        return None;
+    }
+    // only looked up when there is a bracket to match
+    let code = cached_code_chars(ctx, syntax, text);
+    candidates
+        .into_iter()
+        .find_map(|at| Some((at, find_match(text, &code, at)?)))
+}
+
+/// Char index of the bracket matching the one at `index`, skipping strings and comments
+pub fn find_match(text: &str, code: &[bool], index: usize) -> Option<usize> {
+    let chars: Vec<char> = text.chars().collect();
+    let bracket = *chars.get(index)?;
+    if !code.get(index).copied().unwrap_or(true) {
+        return None;
+    }
+    let (open, close) = *PAIRS
+        .iter()
+        .find(|(open, close)| *open == bracket || *close == bracket)?;
+    let forward = bracket == open;
+    let mut depth = 0;
+    let mut at = index;
+    loop {
+        let c = chars[at];
+        if code.get(at).copied().unwrap_or(true) {
+            if c == bracket {
+                depth += 1;
+            } else if c == open || c == close {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(at);
+                }
+            }
+        }
+        at = match forward {
+            true if at + 1 < chars.len() => at + 1,
+            false if at > 0 => at - 1,
+            _ => return None,
+        };
+    }
+}
+
+/// Whether a line ends by opening a block, so that the next one is indented once more
+pub fn opens_block(syntax: &Syntax, line: &str) -> bool {
+    let line = line.trim_end();
+    line.ends_with(|c| closing_of(c).is_some())
+        || (syntax.language() == "Python" && line.ends_with(':'))
+}
+
+/// Type `typed` at every cursor, closing brackets and quotes in code, typing over
+/// the closing char already there and dedenting a closing bracket alone on its line.
+/// Returns false, without editing, when no cursor needs more than the char itself.
+pub fn type_char(
+    text: &mut String,
+    cursors: &mut [CCursorRange],
+    typed: char,
+    syntax: &Syntax,
+    indentation: Indentation,
+) -> bool {
+    let quote = PAIRED_QUOTES.contains(&typed);
+    if closing_of(typed).is_none() && !is_closing(typed) && !quote {
+        return false;
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let code_at = code_positions(syntax, text);
+    let mut special = false;
+    let mut edits: Vec<(Range<usize>, String)> = vec![];
+    // where each cursor ends, from the end of its edit
+    let mut shifts: Vec<isize> = vec![];
+    for cursor in cursors.iter() {
+        let selected = range(cursor);
+        let index = selected.start;
+        let before = index.checked_sub(1).map(|at| chars[at]);
+        let after = chars.get(index).copied();
+        let code = code_at.get(index).copied().unwrap_or(true);
+        let line_start = chars[..index]
+            .iter()
+            .rposition(|&c| c == '\n')
+            .map_or(0, |at| at + 1);
+        let blank_before = chars[line_start..index].iter().all(|c| c.is_whitespace());
+
+        let (edit, shift) = if !selected.is_empty() {
+            ((selected, typed.to_string()), 0)
+        } else if after == Some(typed) && ((quote && !code) || (is_closing(typed) && code)) {
+            // type over the char closing the string or the pair
+            ((index..index, String::new()), 1)
+        } else if let Some(close) = closing_of(typed).filter(|_| code && closes_before(after)) {
+            ((index..index, format!("{}{}", typed, close)), -1)
+        } else if quote
+            && code
+            && before.is_none_or(|c| c.is_whitespace() || BEFORE_QUOTE.contains(c))
+            && closes_before(after)
+        {
+            ((index..index, format!("{}{}", typed, typed)), -1)
+        } else if is_closing(typed) && code && blank_before && index > line_start {
+            let indent: String = chars[line_start..index].iter().collect();
+            let outdented = match indent.strip_suffix('\t') {
+                Some(rest) => rest.to_string(),
+                None => {
+                    let width = indentation.unit().chars().count();
+                    let spaces = indent.chars().rev().take_while(|&c| c == ' ').count();
+                    indent[..indent.len() - spaces.min(width)].to_string()
+                }
+            };
+            ((line_start..index, format!("{}{}", outdented, typed)), 0)
+        } else {
+            edits.push((index..index, typed.to_string()));
+            shifts.push(0);
+            continue;
+        };
+        special = true;
+        edits.push(edit);
+        shifts.push(shift);
+    }
+    if !special {
+        return false;
+    }
+    apply_edits(text, edits, cursors);
+    for (cursor, shift) in cursors.iter_mut().zip(shifts) {
+        let index = (range(cursor).end as isize + shift).max(0) as usize;
+        *cursor = CCursorRange::one(CCursor::new(index));
+    }
+    true
+}
+
+/// Backspace between an empty pair, like `(|)`, removes both of its chars.
+/// Returns false, without editing, unless every cursor is in one.
+pub fn delete_pair(text: &mut String, cursors: &mut [CCursorRange]) -> bool {
+    let chars: Vec<char> = text.chars().collect();
+    let in_pair = |cursor: &CCursorRange| {
+        let index = cursor.primary.index;
+        range(cursor).is_empty()
+            && index > 0
+            && index < chars.len()
+            && (closing_of(chars[index - 1]) == Some(chars[index])
+                || (PAIRED_QUOTES.contains(&chars[index]) && chars[index - 1] == chars[index]))
+    };
+    if !cursors.iter().all(in_pair) {
+        return false;
+    }
+    let edits = cursors
+        .iter()
+        .map(|cursor| {
+            (
+                cursor.primary.index - 1..cursor.primary.index + 1,
+                String::new(),
+            )
+        })
+        .collect();
+    apply_edits(text, edits, cursors);
+    true
+}
@@ -0,0 +1,133 @@
+use super::*;
+
+fn at(index: usize) -> CCursorRange {
+    CCursorRange::one(CCursor::new(index))
+}
+
+#[test]
+fn matches_skip_strings_and_comments() {
+    let syntax = Syntax::rust();
+    let text = "f(\")\", a[0]) // (";
+    let code = code_chars(&syntax, text);
+    assert_eq!(find_match(text, &code, 1), Some(11));
+    assert_eq!(find_match(text, &code, 11), Some(1));
+    assert_eq!(find_match(text, &code, 8), Some(10));
+    // the ones in the string and in the comment have no match
+    assert_eq!(find_match(text, &code, 3), None);
+    assert_eq!(find_match(text, &code, 16), None);
+    // right after a bracket also counts
+    let ctx = egui::Context::default();
+    assert_eq!(pair_at(&ctx, &syntax, text, 12), Some((11, 1)));
+}
+
+#[test]
+fn code_positions_agree_with_in_code() {
+    let syntax = Syntax::rust();
+    let text = "f(\"a\\\"b\", 'c') /* x */ y // z\nw";
+    let positions = code_positions(&syntax, text);
+    assert_eq!(positions.len(), text.chars().count() + 1);
+    for (index, code) in positions.into_iter().enumerate() {
+        assert_eq!(code, in_code(&syntax, text, index), "at {}", index);
+    }
+}
+
+#[test]
+fn pairs_are_closed_and_typed_over() {
+    let syntax = Syntax::rust();
+    let mut text = String::from("let a = ;");
+    let mut list = vec![at(8)];
+    assert!(type_char(
+        &mut text,
+        &mut list,
+        '(',
+        &syntax,
+        Indentation::Tabs
+    ));
+    assert_eq!(text, "let a = ();");
+    assert_eq!(list, vec![at(9)]);
+    assert!(type_char(
+        &mut text,
+        &mut list,
+        '"',
+        &syntax,
+        Indentation::Tabs
+    ));
+    assert_eq!(text, "let a = (\"\");");
+    // inside the string, nothing is closed but the closing quote is typed over
+    assert!(!type_char(
+        &mut text,
+        &mut list,
+        '(',
+        &syntax,
+        Indentation::Tabs
+    ));
+    assert!(type_char(
+        &mut text,
+        &mut list,
+        '"',
+        &syntax,
+        Indentation::Tabs
+    ));
+    assert!(type_char(
+        &mut text,
+        &mut list,
+        ')',
+        &syntax,
+        Indentation::Tabs
+    ));
+    assert_eq!(text, "let a = (\"\");");
+    assert_eq!(list, vec![at(12)]);
+
+    // nothing is closed before a word, nor a quote after one
+    let mut text = String::from("word");
+    let mut list = vec![at(0)];
+    assert!(!type_char(
+        &mut text,
+        &mut list,
+        '(',
+        &syntax,
+        Indentation::Tabs
+    ));
+    let mut list = vec![at(4)];
+    assert!(!type_char(
+        &mut text,
+        &mut list,
+        '\'',
+        &syntax,
+        Indentation::Tabs
+    ));
+}
+
+#[test]
+fn closing_brace_is_dedented() {
+    let syntax = Syntax::rust();
+    let mut text = String::from("fn a() {\n    b();\n    ");
+    let mut list = vec![at(22)];
+    assert!(type_char(
+        &mut text,
+        &mut list,
+        '}',
+        &syntax,
+        Indentation::Spaces(4)
+    ));
+    assert_eq!(text, "fn a() {\n    b();\n}");
+    assert_eq!(list, vec![at(19)]);
+}
+
+#[test]
+fn empty_pairs_are_deleted_together() {
+    let mut text = String::from("a() + b[x]");
+    let mut list = vec![at(2)];
+    assert!(delete_pair(&mut text, &mut list));
+    assert_eq!(text, "a + b[x]");
+    assert_eq!(list, vec![at(1)]);
+    let mut list = vec![at(7)];
+    assert!(!delete_pair(&mut text, &mut list));
+}
+
+#[test]
+fn blocks_open_after_brackets_and_python_colons() {
+    assert!(opens_block(&Syntax::rust(), "fn a() { "));
+    assert!(!opens_block(&Syntax::rust(), "match a:"));
+    assert!(opens_block(&Syntax::python(), "if a:"));
+}
@@ -0,0 +1,423 @@
+//! Several cursors editing a text at once. The first cursor of a list is the one
+//! the text edit shows, the others are drawn and moved by the editor.
+
+#[cfg(test)]
+mod tests;
+
+use egui::{text::CCursor, text_edit::CCursorRange};
+use std::ops::Range;
+
+use super::position::{self, Column};
+use super::Indentation;
+use eframe::egui;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Motion {
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+}
+
+/// Chars a cursor covers
+pub fn range(cursor: &CCursorRange) -> Range<usize> {
+    let (a, b) = (cursor.primary.index, cursor.secondary.index);
+    a.min(b)..a.max(b)
+}
+
+fn at(index: usize) -> CCursorRange {
+    CCursorRange::one(CCursor::new(index))
+}
+
+/// Drop the cursors overlapping an earlier one, so the first one stays first
+pub fn merge(cursors: &mut Vec<CCursorRange>) {
+    let mut kept: Vec<CCursorRange> = vec![];
+    for cursor in cursors.drain(..) {
+        let new = range(&cursor);
+        let overlaps = kept
+            .iter()
+            .map(range)
+            .any(|old| old == new || (new.start < old.end && old.start < new.end));
+        if !overlaps {
+            kept.push(cursor);
+        }
+    }
+    *cursors = kept;
+}
+
+/// Selected texts, in the order of the text, one per line
+pub fn selected_text(text: &str, cursors: &[CCursorRange]) -> String {
+    let mut ranges: Vec<Range<usize>> = cursors.iter().map(range).collect();
+    ranges.sort_by_key(|range| range.start);
+    ranges
+        .into_iter()
+        .map(|range| {
+            text.chars()
+                .skip(range.start)
+                .take(range.len())
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Replace char ranges of `text` and move the cursors along,
+/// edits overlapping an earlier one are left out
+pub fn apply_edits(
+    text: &mut String,
+    mut edits: Vec<(Range<usize>, String)>,
+    cursors: &mut [CCursorRange],
+) {
+    edits.sort_by_key(|(range, _)| range.start);
+    let mut kept: Vec<(Range<usize>, String)> = vec![];
+    for edit in edits {
+        if kept
+            .last()
+            .is_none_or(|(last, _)| last.end <= edit.0.start && *last != edit.0)
+        {
+            kept.push(edit);
+        }
+    }
+    // from the end, so that the offsets of the earlier edits hold
+    for (range, replacement) in kept.iter().rev() {
+        let start = position::char_to_byte(text, range.start);
+        let end = position::char_to_byte(text, range.end);
+        text.replace_range(start..end, replacement);
+    }
+
+    let map = |index: usize| {
+        let mut shift: isize = 0;
+        for (range, replacement) in &kept {
+            let length = replacement.chars().count() as isize;
+            if range.end <= index {
+                shift += length - range.len() as isize;
+            } else if range.start < index {
+                return (range.start as isize + shift + length) as usize;
+            } else {
+                break;
+            }
+        }
+        (index as isize + shift).max(0) as usize
+    };
+    for cursor in cursors.iter_mut() {
+        cursor.primary = CCursor::new(map(cursor.primary.index));
+        cursor.secondary = CCursor::new(map(cursor.secondary.index));
+    }
+}
+
+/// Replace every selection with `typed`, or insert it at every cursor
+pub fn type_text(text: &mut String, cursors: &mut Vec<CCursorRange>, typed: &str) {
+    let edits = cursors
+        .iter()
+        .map(|cursor| (range(cursor), typed.to_string()))
+        .collect();
+    apply_edits(text, edits, cursors);
+    collapse(cursors);
+}
+
+/// Line break at every cursor, keeping the indentation of its line
+pub fn new_line(text: &mut String, cursors: &mut Vec<CCursorRange>, indentation: Indentation) {
+    let edits = cursors
+        .iter()
+        .map(|cursor| {
+            let range = range(cursor);
+            let line = &line_text(text, range.start);
+            let indent = indentation.unit().repeat(indentation.depth(line));
+            (range, format!("\n{}", indent))
+        })
+        .collect();
+    apply_edits(text, edits, cursors);
+    collapse(cursors);
+}
+
+/// Remove the selections, or the char before each cursor
+pub fn backspace(text: &mut String, cursors: &mut Vec<CCursorRange>) {
+    let edits = cursors
+        .iter()
+        .map(range)
+        .filter_map(|range| match range.start {
+            _ if !range.is_empty() => Some(range),
+            0 => None,
+            start => Some(start - 1..start),
+        })
+        .map(|range| (range, String::new()))
+        .collect();
+    apply_edits(text, edits, cursors);
+    collapse(cursors);
+}
+
+/// Remove the selections, or the char after each cursor
+pub fn delete(text: &mut String, cursors: &mut Vec<CCursorRange>) {
+    let length = text.chars().count();
+    let edits = cursors
+        .iter()
+        .map(range)
+        .filter_map(|range| match range.start {
+            _ if !range.is_empty() => Some(range),
+            start if start >= length => None,
+            start => Some(start..start + 1),
+        })
+        .map(|range| (range, String::new()))
+        .collect();
+    apply_edits(text, edits, cursors);
+    collapse(cursors);
+}
+
+/// One more level of indentation on every line holding a cursor
+pub fn indent(text: &mut String, cursors: &mut [CCursorRange], indentation: Indentation) {
+    let edits = cursor_lines(text, cursors)
+        .into_iter()
+        .map(|start| (start..start, indentation.unit()))
+        .collect();
+    apply_edits(text, edits, cursors);
+}
+
+/// One less level of indentation on every line holding a cursor
+pub fn outdent(text: &mut String, cursors: &mut [CCursorRange], indentation: Indentation) {
+    let width = indentation.unit().chars().count();
+    let edits = cursor_lines(text, cursors)
+        .into_iter()
+        .filter_map(|start| {
+            let line = line_text(text, start);
+            let count = if line.starts_with('\t') {
+                1
+            } else {
+                line.chars().take(width).take_while(|&c| c == ' ').count()
+            };
+            (count > 0).then(|| (start..start + count, String::new()))
+        })
+        .collect();
+    apply_edits(text, edits, cursors);
+}
+
+/// Comment the lines holding a cursor with `head` at their smallest indentation,
+/// or uncomment them if they all are. Blank lines are left alone unless all are blank.
+pub fn toggle_comment(text: &mut String, cursors: &mut [CCursorRange], head: &str) {
+    if head.is_empty() {
+        return;
+    }
+    let mut lines: Vec<(usize, String)> = cursor_lines(text, cursors)
+        .into_iter()
+        .map(|start| (start, line_text(text, start)))
+        .collect();
+    if lines.iter().any(|(_, line)| !line.trim().is_empty()) {
+        lines.retain(|(_, line)| !line.trim().is_empty());
+    }
+    let commented = lines
+        .iter()
+        .all(|(_, line)| line.trim_start().starts_with(head));
+    let indent = lines
+        .iter()
+        .map(|(_, line)| indent_width(line))
+        .min()
+        .unwrap_or(0);
+    let head_length = head.chars().count();
+    let edits = lines
+        .into_iter()
+        .map(|(start, line)| {
+            if !commented {
+                return (start + indent..start + indent, format!("{} ", head));
+            }
+            let head_start = start + indent_width(&line);
+            let space = line.trim_start()[head.len()..].starts_with(' ') as usize;
+            (head_start..head_start + head_length + space, String::new())
+        })
+        .collect();
+    apply_edits(text, edits, cursors);
+}
+
+/// Wrap every selection, or the line of an empty cursor, between the `start` and `end`
+/// of a block comment, or unwrap them if they all already are
+pub fn toggle_block_comment(
+    text: &mut String,
+    cursors: &mut [CCursorRange],
+    [start, end]: [&str; 2],
+) {
+    if start.is_empty() || end.is_empty() {
+        return;
+    }
+    let spans: Vec<Range<usize>> = cursors
+        .iter()
+        .map(|cursor| {
+            let selected = range(cursor);
+            if !selected.is_empty() {
+                return selected;
+            }
+            let (_, column) = position::char_to_line_col(text, selected.start, Column::Chars);
+            let line = line_text(text, selected.start);
+            let line_start = selected.start - column + indent_width(&line);
+            line_start..line_start + line.trim().chars().count()
+        })
+        .collect();
+    let inner = |span: &Range<usize>| -> String {
+        text.chars().skip(span.start).take(span.len()).collect()
+    };
+    let commented = spans.iter().all(|span| {
+        let inner = inner(span);
+        inner.len() >= start.len() + end.len() && inner.starts_with(start) && inner.ends_with(end)
+    });
+    let edits = spans
+        .iter()
+        .map(|span| {
+            let inner = inner(span);
+            let replacement = if commented {
+                inner[start.len()..inner.len() - end.len()].to_string()
+            } else {
+                format!("{}{}{}", start, inner, end)
+            };
+            (span.clone(), replacement)
+        })
+        .collect();
+    apply_edits(text, edits, cursors);
+}
+
+/// Move every cursor, a selection collapses to its side for Left and Right
+pub fn move_cursors(text: &str, cursors: &mut Vec<CCursorRange>, motion: Motion) {
+    let length = text.chars().count();
+    for cursor in cursors.iter_mut() {
+        let range = range(cursor);
+        let index = cursor.primary.index;
+        let (line, col) = position::char_to_line_col(text, index, Column::Chars);
+        let moved = match motion {
+            Motion::Left if !range.is_empty() => range.start,
+            Motion::Right if !range.is_empty() => range.end,
+            Motion::Left => index.saturating_sub(1),
+            Motion::Right => (index + 1).min(length),
+            Motion::Up if line == 0 => 0,
+            Motion::Up => position::line_col_to_char(text, (line - 1, col), Column::Chars),
+            Motion::Down => position::line_col_to_char(text, (line + 1, col), Column::Chars),
+            Motion::Home => index - col,
+            Motion::End => position::line_col_to_char(text, (line, usize::MAX), Column::Chars),
+        };
+        *cursor = at(moved);
+    }
+    merge(cursors);
+}
+
+/// Ctrl+D: select the word at the first cursor, or add its next match as the first cursor
+pub fn add_next_occurrence(text: &str, cursors: &mut Vec<CCursorRange>) {
+    let Some(first) = cursors.first() else {
+        return;
+    };
+    let selected = range(first);
+    if selected.is_empty() {
+        let word = word_at(text, selected.start);
+        cursors[0] = CCursorRange::two(CCursor::new(word.start), CCursor::new(word.end));
+        return;
+    }
+    let needle: String = text
+        .chars()
+        .skip(selected.start)
+        .take(selected.len())
+        .collect();
+    let matches: Vec<Range<usize>> = text
+        .match_indices(&needle)
+        .map(|(byte, _)| {
+            let start = position::byte_to_char(text, byte);
+            start..start + selected.len()
+        })
+        .filter(|found| cursors.iter().all(|cursor| range(cursor) != *found))
+        .collect();
+    // after the first cursor, wrapping around at the end
+    let next = matches
+        .iter()
+        .find(|found| found.start >= selected.end)
+        .or(matches.first());
+    if let Some(next) = next {
+        let cursor = CCursorRange::two(CCursor::new(next.start), CCursor::new(next.end));
+        cursors.insert(0, cursor);
+        merge(cursors);
+    }
+}
+
+/// Alt+drag: one cursor per line from `anchor` to `head`, as (line, column) in chars,
+/// the columns are clamped to each line and the head line comes first
+pub fn column_selection(
+    text: &str,
+    anchor: (usize, usize),
+    head: (usize, usize),
+) -> Vec<CCursorRange> {
+    let lines = anchor.0.min(head.0)..=anchor.0.max(head.0);
+    let mut cursors: Vec<CCursorRange> = lines
+        .map(|line| CCursorRange {
+            secondary: CCursor::new(position::line_col_to_char(
+                text,
+                (line, anchor.1),
+                Column::Chars,
+            )),
+            primary: CCursor::new(position::line_col_to_char(
+                text,
+                (line, head.1),
+                Column::Chars,
+            )),
+        })
+        .collect();
+    if head.0 > anchor.0 {
+        cursors.rotate_right(1);
+    }
+    cursors
+}
+
+/// Chars of the word around `index`, empty if there is none
+pub fn word_at(text: &str, index: usize) -> Range<usize> {
+    let chars: Vec<char> = text.chars().collect();
+    let is_word = |c: &char| c.is_alphanumeric() || *c == '_';
+    let start = index
+        - chars[..index.min(chars.len())]
+            .iter()
+            .rev()
+            .take_while(|c| is_word(c))
+            .count();
+    let end = index
+        + chars[index.min(chars.len())..]
+            .iter()
+            .take_while(|c| is_word(c))
+            .count();
+    start..end
+}
+
+fn collapse(cursors: &mut Vec<CCursorRange>) {
+    for cursor in cursors.iter_mut() {
+        *cursor = at(range(cursor).end);
+    }
+    merge(cursors);
+}
+
+/// Char index of the start of each line holding a cursor, a selection
+/// ending at the very start of a line leaves that line out
+fn cursor_lines(text: &str, cursors: &[CCursorRange]) -> Vec<usize> {
+    let mut starts = vec![0];
+    starts.extend(
+        text.chars()
+            .enumerate()
+            .filter(|(_, c)| *c == '\n')
+            .map(|(index, _)| index + 1),
+    );
+    let line_of = |index: usize| starts.partition_point(|&start| start <= index) - 1;
+    let mut lines: Vec<usize> = vec![];
+    for cursor in cursors {
+        let range = range(cursor);
+        let last = if range.end > range.start && starts.contains(&range.end) {
+            range.end - 1
+        } else {
+            range.end
+        };
+        lines.extend(line_of(range.start)..=line_of(last));
+    }
+    lines.sort();
+    lines.dedup();
+    lines.into_iter().map(|line| starts[line]).collect()
+}
+
+/// Chars of whitespace at the start of a line
+fn indent_width(line: &str) -> usize {
+    line.chars().take_while(|c| c.is_whitespace()).count()
+}
+
+/// The line a char index is on, without its line break
+fn line_text(text: &str, index: usize) -> String {
+    let (line, _) = position::char_to_line_col(text, index, Column::Chars);
+    text.lines().nth(line).unwrap_or_default().to_string()
+}
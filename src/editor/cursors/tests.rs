@@ -0,0 +1,147 @@
+use super::*;
+
+fn cursors(indices: &[usize]) -> Vec<CCursorRange> {
+    indices.iter().map(|&index| at(index)).collect()
+}
+
+fn selection(start: usize, end: usize) -> CCursorRange {
+    CCursorRange::two(CCursor::new(start), CCursor::new(end))
+}
+
+#[test]
+fn typing_and_deleting_at_every_cursor() {
+    let mut text = String::from("let a = 1;\nlet b = 2;");
+    let mut list = cursors(&[4, 15]);
+    type_text(&mut text, &mut list, "my_");
+    assert_eq!(text, "let my_a = 1;\nlet my_b = 2;");
+    assert_eq!(list, cursors(&[7, 21]));
+
+    backspace(&mut text, &mut list);
+    backspace(&mut text, &mut list);
+    assert_eq!(text, "let ma = 1;\nlet mb = 2;");
+    delete(&mut text, &mut list);
+    assert_eq!(text, "let m = 1;\nlet m = 2;");
+    assert_eq!(list, cursors(&[5, 16]));
+
+    // selections are replaced, a cursor at the end deletes nothing
+    let mut list = vec![selection(4, 5), at(21)];
+    type_text(&mut text, &mut list, "é");
+    assert_eq!(text, "let é = 1;\nlet m = 2;é");
+    delete(&mut text, &mut list);
+    assert_eq!(text, "let é= 1;\nlet m = 2;é");
+}
+
+#[test]
+fn new_line_keeps_the_indentation() {
+    let mut text = String::from("fn a() {\n\tone();\n\ttwo();\n}");
+    let mut list = cursors(&[16, 24]);
+    new_line(&mut text, &mut list, Indentation::Tabs);
+    assert_eq!(text, "fn a() {\n\tone();\n\t\n\ttwo();\n\t\n}");
+    assert_eq!(list, cursors(&[18, 28]));
+}
+
+#[test]
+fn line_commands_at_every_cursor() {
+    let mut text = String::from("a\nb\nc\nd");
+    // the selection ends at the start of the "c" line, which is left out
+    let mut list = vec![selection(0, 4), at(6)];
+    indent(&mut text, &mut list, Indentation::Spaces(2));
+    assert_eq!(text, "  a\n  b\nc\n  d");
+    assert_eq!(list, vec![selection(2, 8), at(12)]);
+
+    toggle_comment(&mut text, &mut list, "//");
+    assert_eq!(text, "  // a\n  // b\nc\n  // d");
+
+    toggle_comment(&mut text, &mut list, "//");
+    outdent(&mut text, &mut list, Indentation::Spaces(2));
+    assert_eq!(text, "a\nb\nc\nd");
+}
+
+#[test]
+fn comments_keep_the_smallest_indentation() {
+    let original = "    if a:\n\n        b";
+    let mut text = String::from(original);
+    let mut list = vec![selection(0, 20)];
+    toggle_comment(&mut text, &mut list, "#");
+    assert_eq!(text, "    # if a:\n\n    #     b");
+    toggle_comment(&mut text, &mut list, "#");
+    assert_eq!(text, original);
+}
+
+#[test]
+fn block_comments_wrap_the_selection() {
+    let mut text = String::from("let a = b + c;");
+    let mut list = vec![selection(8, 13)];
+    toggle_block_comment(&mut text, &mut list, ["/*", "*/"]);
+    assert_eq!(text, "let a = /*b + c*/;");
+    assert_eq!(list, vec![selection(8, 17)]);
+    toggle_block_comment(&mut text, &mut list, ["/*", "*/"]);
+    assert_eq!(text, "let a = b + c;");
+    assert_eq!(list, vec![selection(8, 13)]);
+
+    // without a selection, the line of the cursor without its indentation
+    let mut text = String::from("  x = 1\ny");
+    let mut list = cursors(&[3]);
+    toggle_block_comment(&mut text, &mut list, ["/*", "*/"]);
+    assert_eq!(text, "  /*x = 1*/\ny");
+    assert_eq!(list, cursors(&[11]));
+    toggle_block_comment(&mut text, &mut list, ["/*", "*/"]);
+    assert_eq!(text, "  x = 1\ny");
+}
+
+#[test]
+fn cursors_move_together() {
+    let text = "abc\nde\nfghi";
+    let mut list = cursors(&[2, 6]);
+    move_cursors(text, &mut list, Motion::Down);
+    assert_eq!(list, cursors(&[6, 9]));
+    move_cursors(text, &mut list, Motion::End);
+    assert_eq!(list, cursors(&[6, 11]));
+    move_cursors(text, &mut list, Motion::Home);
+    assert_eq!(list, cursors(&[4, 7]));
+    move_cursors(text, &mut list, Motion::Left);
+    move_cursors(text, &mut list, Motion::Up);
+    // both end up on the first line, at the same place
+    move_cursors(text, &mut list, Motion::Up);
+    assert_eq!(list, cursors(&[0]));
+}
+
+#[test]
+fn next_occurrence_wraps_around() {
+    let text = "value + other_value + value";
+    let mut list = cursors(&[22]);
+    add_next_occurrence(text, &mut list);
+    assert_eq!(list, vec![selection(22, 27)]);
+    add_next_occurrence(text, &mut list);
+    assert_eq!(list, vec![selection(0, 5), selection(22, 27)]);
+    // "value" inside "other_value" is a match too
+    add_next_occurrence(text, &mut list);
+    assert_eq!(list.len(), 3);
+    assert_eq!(range(&list[0]), 14..19);
+    add_next_occurrence(text, &mut list);
+    assert_eq!(list.len(), 3);
+}
+
+#[test]
+fn column_selection_is_clamped_to_lines() {
+    let text = "abcdef\nab\nabcdef";
+    let list = column_selection(text, (0, 1), (2, 4));
+    assert_eq!(
+        list,
+        vec![
+            CCursorRange {
+                secondary: CCursor::new(11),
+                primary: CCursor::new(14),
+            },
+            CCursorRange {
+                secondary: CCursor::new(1),
+                primary: CCursor::new(4),
+            },
+            CCursorRange {
+                secondary: CCursor::new(8),
+                primary: CCursor::new(9),
+            },
+        ]
+    );
+    assert_eq!(selected_text(text, &list), "bcd\nb\nbcd");
+}
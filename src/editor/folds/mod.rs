@@ -0,0 +1,177 @@
+//! Folding blocks of lines, found from the brackets or else from the indentation.
+//! A fold is kept as the zero based line it starts on, the line stays visible and
+//! the ones of its block are laid out with no height.
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::{Range, RangeInclusive};
+
+use super::brackets;
+use super::history::diff;
+use super::position::{self, Column};
+use super::Syntax;
+use eframe::egui;
+
+/// Lines that can be folded, mapped to the last line their fold hides
+pub type FoldRanges = BTreeMap<usize, usize>;
+
+#[derive(Default)]
+pub struct FoldComputer;
+
+impl egui::util::cache::ComputerMut<(&Syntax, &str), FoldRanges> for FoldComputer {
+    fn compute(&mut self, (syntax, text): (&Syntax, &str)) -> FoldRanges {
+        fold_ranges(syntax, text)
+    }
+}
+
+pub type FoldCache = egui::util::cache::FrameCache<FoldRanges, FoldComputer>;
+
+/// Fold ranges of `text`, computed again only when it changes
+pub fn cached_fold_ranges(ctx: &egui::Context, syntax: &Syntax, text: &str) -> FoldRanges {
+    ctx.memory_mut(|mem| mem.caches.cache::<FoldCache>().get((syntax, text)))
+}
+
+/// Brackets opened on a line and closed two lines or more further, the closing line
+/// staying visible, then blocks of deeper indented lines after the others
+pub fn fold_ranges(syntax: &Syntax, text: &str) -> FoldRanges {
+    let mut ranges = FoldRanges::new();
+    let code = brackets::code_chars(syntax, text);
+    let mut line = 0;
+    let mut opened: Vec<usize> = vec![];
+    for (c, is_code) in text.chars().zip(code) {
+        if c == '\n' {
+            line += 1;
+        } else if !is_code {
+            continue;
+        } else if brackets::closing_of(c).is_some() {
+            opened.push(line);
+        } else if brackets::PAIRS.iter().any(|(_, close)| *close == c) {
+            match opened.pop() {
+                Some(start) if line > start + 1 => {
+                    let last = ranges.entry(start).or_insert(line - 1);
+                    *last = (*last).max(line - 1);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let lines: Vec<&str> = text.split('\n').collect();
+    let indent = |line: &str| line.chars().take_while(|c| c.is_whitespace()).count();
+    for (start, header) in lines.iter().enumerate() {
+        if header.trim().is_empty() || ranges.contains_key(&start) {
+            continue;
+        }
+        let width = indent(header);
+        let last = lines[start + 1..]
+            .iter()
+            .take_while(|line| line.trim().is_empty() || indent(line) > width)
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(offset, _)| start + 1 + offset)
+            .last();
+        if let Some(last) = last {
+            ranges.insert(start, last);
+        }
+    }
+    ranges
+}
+
+/// The innermost block not folded yet starting on `line` or holding it
+pub fn fold_at(ranges: &FoldRanges, folded: &BTreeSet<usize>, line: usize) -> Option<usize> {
+    ranges
+        .range(..=line)
+        .rev()
+        .find(|(start, &last)| last >= line && !folded.contains(start))
+        .map(|(&start, _)| start)
+}
+
+/// Lines hidden by the folds, a fold inside a hidden one adding nothing
+pub fn hidden_lines(folds: &BTreeSet<usize>, ranges: &FoldRanges) -> Vec<RangeInclusive<usize>> {
+    let mut hidden: Vec<RangeInclusive<usize>> = vec![];
+    for (&start, &last) in folds.iter().filter_map(|start| ranges.get_key_value(start)) {
+        if hidden
+            .last()
+            .is_some_and(|previous| previous.contains(&start))
+        {
+            continue;
+        }
+        hidden.push(start + 1..=last);
+    }
+    hidden
+}
+
+/// Byte ranges laid out with no height for the hidden lines, each one from the line break
+/// before its first line to the one ending its last line, so that blank lines get none either
+pub fn hidden_bytes(text: &str, hidden: &[RangeInclusive<usize>]) -> Vec<Range<usize>> {
+    let breaks: Vec<usize> = text
+        .char_indices()
+        .filter(|(_, c)| *c == '\n')
+        .map(|(index, _)| index)
+        .collect();
+    let line_end = |line: usize| breaks.get(line).copied().unwrap_or(text.len());
+    hidden
+        .iter()
+        .filter(|lines| *lines.start() > 0)
+        .map(|lines| line_end(lines.start() - 1)..line_end(*lines.end()))
+        .collect()
+}
+
+/// Whether the char index `index` lies on a hidden line
+pub fn is_hidden(text: &str, hidden: &[RangeInclusive<usize>], index: usize) -> bool {
+    let (line, _) = position::char_to_line_col(text, index, Column::Chars);
+    hidden.iter().any(|lines| lines.contains(&line))
+}
+
+/// Move a cursor that went into hidden lines out of them: to the end of the line above
+/// when it went up from `previous`, to the start of the line below otherwise
+pub fn skip_hidden(
+    text: &str,
+    hidden: &[RangeInclusive<usize>],
+    index: usize,
+    previous: usize,
+) -> usize {
+    let (line, _) = position::char_to_line_col(text, index, Column::Chars);
+    let Some(lines) = hidden.iter().find(|lines| lines.contains(&line)) else {
+        return index;
+    };
+    let line_count = text.split('\n').count();
+    if index < previous || *lines.end() + 1 >= line_count {
+        position::line_col_to_char(text, (lines.start() - 1, usize::MAX), Column::Chars)
+    } else {
+        position::line_col_to_char(text, (lines.end() + 1, 0), Column::Chars)
+    }
+}
+
+/// Keep the folds on their lines as `before` became `after`, dropping the ones whose
+/// line break was edited
+pub fn shift_folds(folds: &mut BTreeSet<usize>, before: &str, after: &str) {
+    if folds.is_empty() || before == after {
+        return;
+    }
+    let (start, removed, inserted) = diff(before, after);
+    let end = start + removed.chars().count();
+    let delta = inserted.matches('\n').count() as isize - removed.matches('\n').count() as isize;
+    let breaks: Vec<usize> = before
+        .chars()
+        .enumerate()
+        .filter(|(_, c)| *c == '\n')
+        .map(|(index, _)| index)
+        .collect();
+    let length = before.chars().count();
+    *folds = folds
+        .iter()
+        .filter_map(|&line| {
+            let line_end = breaks.get(line).copied().unwrap_or(length);
+            if start <= line_end && line_end < end {
+                None
+            } else if start >= line_end {
+                Some(line)
+            } else {
+                usize::try_from(line as isize + delta).ok()
+            }
+        })
+        .collect();
+}
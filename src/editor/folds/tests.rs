@@ -0,0 +1,54 @@
+use super::*;
+
+const CODE: &str = "fn a() {\n    let b = [\n        1,\n    ];\n}\n\nfn c() {}\n";
+
+#[test]
+fn blocks_from_brackets() {
+    let ranges = fold_ranges(&Syntax::rust(), CODE);
+    assert_eq!(ranges.get(&0), Some(&3));
+    assert_eq!(ranges.get(&1), Some(&2));
+    assert_eq!(ranges.get(&6), None);
+    // brackets in strings are left out
+    let ranges = fold_ranges(&Syntax::rust(), "a(\"(\",\n  b,\n  c)");
+    assert_eq!(ranges.get(&0), Some(&1));
+}
+
+#[test]
+fn blocks_from_indentation() {
+    let text = "if a:\n    b\n\n    c\nd";
+    let ranges = fold_ranges(&Syntax::python(), text);
+    assert_eq!(ranges, FoldRanges::from([(0, 3)]));
+}
+
+#[test]
+fn hidden_lines_and_bytes() {
+    let ranges = fold_ranges(&Syntax::rust(), CODE);
+    let folds = BTreeSet::from([0, 1]);
+    let hidden = hidden_lines(&folds, &ranges);
+    assert_eq!(hidden, vec![1..=3]);
+    // from the line break of the first line to the one of the fourth
+    assert_eq!(hidden_bytes(CODE, &hidden), vec![8..40]);
+    assert!(is_hidden(CODE, &hidden, 12));
+    assert!(!is_hidden(CODE, &hidden, 8));
+
+    // down from the first line lands after the fold, up from the last before it
+    assert_eq!(skip_hidden(CODE, &hidden, 12, 4), 41);
+    assert_eq!(skip_hidden(CODE, &hidden, 38, 41), 8);
+}
+
+#[test]
+fn folds_follow_the_edits() {
+    let mut folds = BTreeSet::from([0, 4]);
+    let before = "a {\n\n}\n\nb {\n\n}";
+    // a line added above the second fold
+    let after = "a {\n\n}\n\n\nb {\n\n}";
+    shift_folds(&mut folds, before, after);
+    assert_eq!(folds, BTreeSet::from([0, 5]));
+    // typing at the end of a folded line keeps it, joining it with the next drops it
+    shift_folds(&mut folds, after, "a {x\n\n}\n\n\nb {\n\n}");
+    assert_eq!(folds, BTreeSet::from([0, 5]));
+    shift_folds(&mut folds, after, "a {\n}\n\n\nb {\n\n}");
+    assert_eq!(folds, BTreeSet::from([0, 4]));
+    shift_folds(&mut folds, "a {\n}\n\n\nb {\n\n}", "a {}\n\n\nb {\n\n}");
+    assert_eq!(folds, BTreeSet::from([3]));
+}
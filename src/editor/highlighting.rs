@@ -1,8 +1,16 @@
 use super::syntax::{Syntax, TokenType, QUOTES, SEPARATORS};
 use super::CodeEditor;
 use eframe::egui;
-use eframe::egui::text::LayoutJob;
+use eframe::egui::text::{LayoutJob, LayoutSection};
 use std::mem;
+use std::ops::Range;
+
+#[derive(Clone, Debug, PartialEq, Hash)]
+/// Byte range of the text drawn with an underline
+pub struct Underline {
+    pub range: Range<usize>,
+    pub color: egui::Color32,
+}
 
 #[derive(Default, Debug, PartialEq, PartialOrd, Eq, Ord)]
 /// Lexer and Token
@@ -78,6 +86,7 @@ impl Token {
             }
         }
         editor.append(&mut job, self);
+        editor.decorate(&mut job);
         job
     }
 
@@ -94,6 +103,28 @@ impl Token {
         tokens
     }
 
+    /// Type of the token the lexer is still in at the end of `text`,
+    /// a string or a comment there is one `text` ends inside of
+    pub fn open_at_end(syntax: &Syntax, text: &str) -> TokenType {
+        let mut token = Token::default();
+        for c in text.chars() {
+            token.automata(c, syntax);
+        }
+        token.ty
+    }
+
+    /// Type of the token the lexer is in before each char of `text` and at its end,
+    /// `open_at_end` for every prefix in a single pass
+    pub fn open_along(syntax: &Syntax, text: &str) -> Vec<TokenType> {
+        let mut token = Token::default();
+        let mut types = vec![token.ty];
+        for c in text.chars() {
+            token.automata(c, syntax);
+            types.push(token.ty);
+        }
+        types
+    }
+
     fn automata(&mut self, c: char, syntax: &Syntax) -> Vec<Self> {
         use TokenType as Ty;
         let mut tokens = vec![];
@@ -111,7 +142,7 @@ impl Token {
             }
             (Ty::Comment(true), _) => {
                 self.buffer.push(c);
-                if self.buffer.ends_with(syntax.comment_multiline[1]) {
+                if self.buffer.ends_with(syntax.comment_multiline[1].as_str()) {
                     tokens.extend(self.drain(Ty::Unknown));
                 }
             }
@@ -137,9 +168,9 @@ impl Token {
                 _ => {
                     self.buffer.push(c);
                     self.ty = {
-                        if self.buffer.starts_with(syntax.comment) {
+                        if syntax.starts_comment(&self.buffer) {
                             Ty::Comment(false)
-                        } else if self.buffer.starts_with(syntax.comment_multiline[0]) {
+                        } else if syntax.starts_comment_multiline(&self.buffer) {
                             Ty::Comment(true)
                         } else if syntax.is_keyword(&self.buffer) {
                             Ty::Keyword
@@ -179,9 +210,9 @@ impl Token {
                     tokens.extend(self.first(c, syntax));
                 } else {
                     self.buffer.push(c);
-                    if self.buffer.starts_with(syntax.comment) {
+                    if syntax.starts_comment(&self.buffer) {
                         self.ty = Ty::Comment(false);
-                    } else if self.buffer.starts_with(syntax.comment_multiline[0]) {
+                    } else if syntax.starts_comment_multiline(&self.buffer) {
                         self.ty = Ty::Comment(true);
                     } else if let Some(c) = self.buffer.pop() {
                         tokens.extend(self.drain(Ty::Punctuation(c)));
@@ -238,4 +269,66 @@ impl CodeEditor {
     fn append(&self, job: &mut LayoutJob, token: &Token) {
         job.append(token.buffer(), 0.0, self.format(token.ty()));
     }
+
+    /// Split the sections at the underline, highlight and folded boundaries,
+    /// then decorate the covered ones
+    fn decorate(&self, job: &mut LayoutJob) {
+        if self.underlines.is_empty() && self.highlights.is_empty() && self.hidden.is_empty() {
+            return;
+        }
+        let mut sections: Vec<LayoutSection> = vec![];
+        for section in mem::take(&mut job.sections) {
+            let mut cuts: Vec<usize> = self
+                .underlines
+                .iter()
+                .map(|underline| &underline.range)
+                .chain(self.highlights.iter())
+                .chain(self.hidden.iter())
+                .flat_map(|range| [range.start, range.end])
+                .filter(|&cut| cut > section.byte_range.start && cut < section.byte_range.end)
+                .collect();
+            cuts.push(section.byte_range.end);
+            cuts.sort();
+            cuts.dedup();
+
+            let mut start = section.byte_range.start;
+            let mut leading_space = section.leading_space;
+            for end in cuts {
+                let mut format = section.format.clone();
+                if let Some(underline) = self
+                    .underlines
+                    .iter()
+                    .find(|underline| underline.range.start <= start && underline.range.end >= end)
+                {
+                    format.underline = egui::Stroke::new(self.fontsize * 0.1, underline.color);
+                }
+                if self
+                    .highlights
+                    .iter()
+                    .any(|range| range.start <= start && range.end >= end)
+                {
+                    format.background = self.theme.selection().gamma_multiply(0.5);
+                }
+                if self
+                    .hidden
+                    .iter()
+                    .any(|range| range.start <= start && range.end >= end)
+                {
+                    // folded lines take no height
+                    format.line_height = Some(0.0);
+                    format.color = egui::Color32::TRANSPARENT;
+                    format.background = egui::Color32::TRANSPARENT;
+                    format.underline = egui::Stroke::NONE;
+                }
+                sections.push(LayoutSection {
+                    leading_space,
+                    byte_range: start..end,
+                    format,
+                });
+                leading_space = 0.0;
+                start = end;
+            }
+        }
+        job.sections = sections;
+    }
 }
@@ -0,0 +1,209 @@
+//! Undo and redo owned by the editor rather than by the egui text edit state,
+//! so that every change of a tab text forms a step, whoever made it.
+
+#[cfg(test)]
+mod tests;
+
+use egui::{text::CCursor, text_edit::CCursorRange};
+use std::time::{Duration, Instant};
+
+use super::position;
+use eframe::egui;
+
+/// Steps kept for each tab
+pub const UNDO_LIMIT: usize = 1000;
+/// Typing after such a pause starts a new step
+const GROUP_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditKind {
+    /// Typed in the editor, merged with the typing next to it
+    Typing,
+    /// Editor commands and changes made from outside, always a step of their own
+    Command,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// Chars `removed` at `start` were replaced by `inserted`
+struct Edit {
+    start: usize,
+    removed: String,
+    inserted: String,
+    kind: EditKind,
+    cursor_before: CCursorRange,
+    cursor_after: CCursorRange,
+    time: Instant,
+}
+
+impl Edit {
+    /// Typing right after `self`, in the same direction, joins its step
+    fn merge(&mut self, next: &Edit) -> bool {
+        let joinable = self.kind == EditKind::Typing
+            && next.kind == EditKind::Typing
+            && next.time.duration_since(self.time) < GROUP_DELAY
+            && !self.inserted.contains('\n')
+            && !next.inserted.contains('\n');
+        if !joinable {
+            return false;
+        }
+        let end = self.start + self.inserted.chars().count();
+        if next.removed.is_empty() && next.start == end {
+            self.inserted.push_str(&next.inserted);
+        } else if next.inserted.is_empty() && self.inserted.is_empty() {
+            if next.start + next.removed.chars().count() == self.start {
+                // backspace
+                self.start = next.start;
+                self.removed.insert_str(0, &next.removed);
+            } else if next.start == self.start {
+                // delete
+                self.removed.push_str(&next.removed);
+            } else {
+                return false;
+            }
+        } else {
+            return false;
+        }
+        self.cursor_after = next.cursor_after;
+        self.time = next.time;
+        true
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// Undo and redo stacks of a tab, fed by comparing its text with the one seen last
+pub struct UndoHistory {
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+    /// The text as of the last recorded step
+    text: String,
+    cursor: Option<CCursorRange>,
+    /// The cursor moved since the last step, typing starts a new one
+    sealed: bool,
+    /// Bumped whenever the text changes, telling others when to look at it again
+    version: u64,
+}
+
+impl UndoHistory {
+    pub fn new(text: &str) -> Self {
+        Self {
+            undo: vec![],
+            redo: vec![],
+            text: text.to_string(),
+            cursor: None,
+            sealed: false,
+            version: 0,
+        }
+    }
+
+    /// The text as of the last change recorded
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Record how `text` changed since the last call, `cursor` being where the change left it
+    pub fn record(&mut self, text: &str, cursor: Option<CCursorRange>, kind: EditKind) {
+        if text == self.text {
+            if cursor.is_some() && cursor != self.cursor {
+                self.sealed = self.cursor.is_some();
+                self.cursor = cursor;
+            }
+            return;
+        }
+        let (start, removed, inserted) = diff(&self.text, text);
+        let end = start + inserted.chars().count();
+        let at = |index| CCursorRange::one(CCursor::new(index));
+        let edit = Edit {
+            start,
+            removed,
+            inserted,
+            kind,
+            cursor_before: self.cursor.unwrap_or(at(start)),
+            cursor_after: cursor.unwrap_or(at(end)),
+            time: Instant::now(),
+        };
+        self.text = text.to_string();
+        self.version += 1;
+        self.cursor = Some(edit.cursor_after);
+        self.redo.clear();
+
+        if let Some(last) = self.undo.last_mut().filter(|_| !self.sealed) {
+            if last.merge(&edit) {
+                return;
+            }
+        }
+        self.sealed = false;
+        self.undo.push(edit);
+        if self.undo.len() > UNDO_LIMIT {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Revert the last step in `text`, returns the cursor to restore
+    pub fn undo(&mut self, text: &mut String) -> Option<CCursorRange> {
+        self.record(&text.clone(), None, EditKind::Command);
+        let edit = self.undo.pop()?;
+        replace(text, edit.start, &edit.inserted, &edit.removed);
+        self.restore(text, edit.cursor_before);
+        self.redo.push(edit);
+        self.cursor
+    }
+
+    /// Apply again the last undone step, returns the cursor to restore
+    pub fn redo(&mut self, text: &mut String) -> Option<CCursorRange> {
+        self.record(&text.clone(), None, EditKind::Command);
+        let edit = self.redo.pop()?;
+        replace(text, edit.start, &edit.removed, &edit.inserted);
+        self.restore(text, edit.cursor_after);
+        self.undo.push(edit);
+        self.cursor
+    }
+
+    fn restore(&mut self, text: &str, cursor: CCursorRange) {
+        self.text = text.to_string();
+        self.version += 1;
+        self.cursor = Some(cursor);
+        self.sealed = true;
+    }
+}
+
+/// Start, removed and inserted chars of the one range where `new` differs from `old`
+pub fn diff(old: &str, new: &str) -> (usize, String, String) {
+    let prefix = old
+        .chars()
+        .zip(new.chars())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old_rest, new_rest) = (
+        &old[position::char_to_byte(old, prefix)..],
+        &new[position::char_to_byte(new, prefix)..],
+    );
+    let suffix = old_rest
+        .chars()
+        .rev()
+        .zip(new_rest.chars().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let cut = |rest: &str| {
+        let end = rest.chars().count() - suffix;
+        rest.chars().take(end).collect::<String>()
+    };
+    (prefix, cut(old_rest), cut(new_rest))
+}
+
+/// Replace `current` found at char `start` of `text` with `wanted`
+fn replace(text: &mut String, start: usize, current: &str, wanted: &str) {
+    let start_byte = position::char_to_byte(text, start);
+    text.replace_range(start_byte..start_byte + current.len(), wanted);
+}
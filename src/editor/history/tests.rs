@@ -0,0 +1,109 @@
+use super::*;
+
+fn cursor(index: usize) -> Option<CCursorRange> {
+    Some(CCursorRange::one(CCursor::new(index)))
+}
+
+/// Type `text` one char at a time at the end
+fn type_in(history: &mut UndoHistory, code: &mut String, text: &str) {
+    for c in text.chars() {
+        code.push(c);
+        history.record(code, cursor(code.chars().count()), EditKind::Typing);
+    }
+}
+
+#[test]
+fn typing_forms_one_step() {
+    let mut code = String::from("fn");
+    let mut history = UndoHistory::new(&code);
+    history.record(&code, cursor(2), EditKind::Typing);
+    type_in(&mut history, &mut code, " main");
+    code.push('\n');
+    history.record(&code, cursor(8), EditKind::Typing);
+    type_in(&mut history, &mut code, "é");
+
+    assert_eq!(history.undo(&mut code), cursor(8));
+    assert_eq!(code, "fn main\n");
+    assert_eq!(history.undo(&mut code), cursor(7));
+    assert_eq!(code, "fn main");
+    assert_eq!(history.undo(&mut code), cursor(2));
+    assert_eq!(code, "fn");
+    assert_eq!(history.undo(&mut code), None);
+
+    assert_eq!(history.redo(&mut code), cursor(7));
+    assert_eq!(code, "fn main");
+    assert_eq!(history.redo(&mut code), cursor(8));
+    assert_eq!(history.redo(&mut code), cursor(9));
+    assert_eq!(code, "fn main\né");
+    assert!(!history.can_redo());
+}
+
+#[test]
+fn moving_the_cursor_starts_a_step() {
+    let mut code = String::from("ab");
+    let mut history = UndoHistory::new(&code);
+    history.record(&code, cursor(2), EditKind::Typing);
+    type_in(&mut history, &mut code, "cd");
+    history.record(&code, cursor(0), EditKind::Typing);
+    code.insert(0, '>');
+    history.record(&code, cursor(1), EditKind::Typing);
+
+    history.undo(&mut code);
+    assert_eq!(code, "abcd");
+    history.undo(&mut code);
+    assert_eq!(code, "ab");
+}
+
+#[test]
+fn backspaces_and_commands() {
+    let mut code = String::from("let value = 1;");
+    let mut history = UndoHistory::new(&code);
+    history.record(&code, cursor(9), EditKind::Typing);
+    for length in (4..9).rev() {
+        code.replace_range(length..length + 1, "");
+        history.record(&code, cursor(length), EditKind::Typing);
+    }
+    assert_eq!(code, "let  = 1;");
+
+    // an editor command right after is a step of its own
+    let selection = Some(CCursorRange::two(CCursor::new(0), CCursor::new(9)));
+    code.insert_str(0, "//");
+    history.record(&code, selection, EditKind::Command);
+
+    assert_eq!(history.undo(&mut code), cursor(4));
+    assert_eq!(code, "let  = 1;");
+    assert_eq!(history.undo(&mut code), cursor(9));
+    assert_eq!(code, "let value = 1;");
+}
+
+#[test]
+fn changes_made_elsewhere_are_steps() {
+    let mut code = String::from("one two one");
+    let mut history = UndoHistory::new(&code);
+    history.record(&code, cursor(3), EditKind::Typing);
+
+    // replace all, done on the text directly and only seen at undo time
+    code = code.replace("one", "three");
+    assert_eq!(history.undo(&mut code), cursor(3));
+    assert_eq!(code, "one two one");
+    // the changed range goes from the first "o" to the last "n"
+    assert_eq!(history.redo(&mut code), cursor(14));
+    assert_eq!(code, "three two three");
+}
+
+#[test]
+fn version_follows_the_text() {
+    let mut code = String::from("a");
+    let mut history = UndoHistory::new(&code);
+    history.record(&code, cursor(1), EditKind::Typing);
+    assert_eq!(history.version(), 0);
+    type_in(&mut history, &mut code, "b");
+    assert_eq!(history.version(), 1);
+    history.record(&code, cursor(0), EditKind::Typing);
+    assert_eq!(history.version(), 1);
+    history.undo(&mut code);
+    assert_eq!(history.version(), 2);
+    // nothing left to undo
+    history.undo(&mut code);
+    assert_eq!(history.version(), 2);
+}
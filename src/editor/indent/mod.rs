@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeMap;
+
+const DEFAULT_SPACES: usize = 4;
+const MAX_SPACES: usize = 8;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+/// Indentation style of a file, detected when it is opened.
+pub enum Indentation {
+    #[default]
+    Tabs,
+    Spaces(usize),
+}
+
+impl Indentation {
+    /// Guess the indentation style from the leading whitespace of each line.
+    /// Falls back to tabs when the text has no indented line.
+    pub fn detect(text: &str) -> Self {
+        let mut tab_lines: usize = 0;
+        let mut space_lines: usize = 0;
+        let mut steps: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut previous_width: usize = 0;
+
+        for line in text.lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if line.starts_with('\t') {
+                tab_lines += 1;
+                continue;
+            }
+            let width = line.chars().take_while(|&c| c == ' ').count();
+            if width > 0 {
+                space_lines += 1;
+            }
+            if width > previous_width && width - previous_width <= MAX_SPACES {
+                *steps.entry(width - previous_width).or_insert(0) += 1;
+            }
+            previous_width = width;
+        }
+
+        if space_lines == 0 || tab_lines >= space_lines {
+            return Indentation::Tabs;
+        }
+
+        let step = steps
+            .iter()
+            .filter(|(&step, _)| step > 1)
+            .max_by_key(|(&step, &count)| (count, step))
+            .map_or(DEFAULT_SPACES, |(&step, _)| step);
+
+        Indentation::Spaces(step)
+    }
+
+    /// Text inserted for one level of indentation.
+    pub fn unit(&self) -> String {
+        match self {
+            Indentation::Tabs => "\t".into(),
+            Indentation::Spaces(width) => " ".repeat(*width),
+        }
+    }
+
+    /// Number of indentation levels at the start of a line.
+    pub fn depth(&self, line: &str) -> usize {
+        match self {
+            Indentation::Tabs => line.chars().take_while(|&c| c == '\t').count(),
+            Indentation::Spaces(width) => {
+                line.chars().take_while(|&c| c == ' ').count() / (*width).max(1)
+            }
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use super::*;
+
+#[test]
+fn tabs() {
+    let text = "fn a() {\n\tif b {\n\t\tc();\n\t}\n}\n";
+    assert_eq!(Indentation::detect(text), Indentation::Tabs);
+}
+
+#[test]
+fn two_and_four_spaces() {
+    let two = "a:\n  b:\n    c: 1\n  d: 2\n";
+    assert_eq!(Indentation::detect(two), Indentation::Spaces(2));
+    let four = "def a():\n    if b:\n        c()\n    return\n";
+    assert_eq!(Indentation::detect(four), Indentation::Spaces(4));
+    // a continuation line off by one space does not count
+    let aligned = "fn a() {\n    b(1,\n     2);\n    c();\n}\n";
+    assert_eq!(Indentation::detect(aligned), Indentation::Spaces(4));
+}
+
+#[test]
+fn mixed_files_follow_the_most_lines() {
+    let mostly_tabs = "a\n\tb\n\tc\n  d\n";
+    assert_eq!(Indentation::detect(mostly_tabs), Indentation::Tabs);
+    let mostly_spaces = "a\n  b\n  c\n\td\n";
+    assert_eq!(Indentation::detect(mostly_spaces), Indentation::Spaces(2));
+    // as many of each
+    assert_eq!(Indentation::detect("a\n\tb\n  c\n"), Indentation::Tabs);
+}
+
+#[test]
+fn empty_or_flat_files() {
+    assert_eq!(Indentation::detect(""), Indentation::Tabs);
+    assert_eq!(Indentation::detect("a\nb\n\n   \n"), Indentation::Tabs);
+    // only one space deep
+    assert_eq!(Indentation::detect("a\n b\n"), Indentation::Spaces(4));
+}
+
+#[test]
+fn unit_and_depth() {
+    assert_eq!(Indentation::Tabs.unit(), "\t");
+    assert_eq!(Indentation::Spaces(2).unit(), "  ");
+    assert_eq!(Indentation::Tabs.depth("\t\tx"), 2);
+    assert_eq!(Indentation::Tabs.depth("    x"), 0);
+    assert_eq!(Indentation::Spaces(4).depth("     x"), 1);
+    assert_eq!(Indentation::Spaces(2).depth("    x"), 2);
+    assert_eq!(Indentation::Spaces(0).depth("  x"), 2);
+}
@@ -0,0 +1,315 @@
+#[cfg(test)]
+mod tests;
+
+use serde::Deserialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use super::Syntax;
+
+/// Path of a tab not saved yet
+const UNTITLED: &str = "untitled";
+/// Language of the untitled tab
+const UNTITLED_LANGUAGE: &str = "Rust";
+
+#[derive(Clone, Debug, PartialEq)]
+/// A syntax and the files it applies to
+pub struct Language {
+    /// Shared with the editor, which takes it every frame
+    pub syntax: Arc<Syntax>,
+    pub extensions: Vec<String>,
+    pub file_names: Vec<String>,
+    pub interpreters: Vec<String>,
+}
+
+impl Language {
+    pub fn new(syntax: Syntax) -> Self {
+        Self {
+            syntax: Arc::new(syntax),
+            extensions: vec![],
+            file_names: vec![],
+            interpreters: vec![],
+        }
+    }
+
+    /// File extensions, without the dot
+    pub fn with_extensions(self, extensions: &[&str]) -> Self {
+        Self {
+            extensions: to_strings(extensions),
+            ..self
+        }
+    }
+
+    /// Exact file names, for files without a meaningful extension
+    pub fn with_file_names(self, file_names: &[&str]) -> Self {
+        Self {
+            file_names: to_strings(file_names),
+            ..self
+        }
+    }
+
+    /// Programs named in a shebang line, without version numbers
+    pub fn with_interpreters(self, interpreters: &[&str]) -> Self {
+        Self {
+            interpreters: to_strings(interpreters),
+            ..self
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.syntax.language()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// Every known language, the first match wins
+pub struct LanguageRegistry {
+    languages: Vec<Language>,
+}
+
+impl Default for LanguageRegistry {
+    fn default() -> Self {
+        Self {
+            languages: vec![
+                Language::new(Syntax::rust()).with_extensions(&["rs"]),
+                Language::new(Syntax::python())
+                    .with_extensions(&["py", "pyw", "pyi"])
+                    .with_interpreters(&["python"]),
+                Language::new(Syntax::javascript())
+                    .with_extensions(&["js", "mjs", "cjs", "jsx", "ts", "tsx"])
+                    .with_interpreters(&["node", "deno"]),
+                Language::new(Syntax::lua())
+                    .with_extensions(&["lua"])
+                    .with_interpreters(&["lua", "luajit"]),
+                Language::new(Syntax::sql()).with_extensions(&["sql"]),
+                Language::new(Syntax::asm()).with_extensions(&["asm", "s", "S", "nasm"]),
+                Language::new(Syntax::pendragon()).with_extensions(&["dr"]),
+                Language::new(Syntax::shell())
+                    .with_extensions(&["sh", "bash", "zsh", "ksh", "mk"])
+                    .with_file_names(&[
+                        "Makefile",
+                        "makefile",
+                        "GNUmakefile",
+                        "Dockerfile",
+                        "Containerfile",
+                        "PKGBUILD",
+                        ".bashrc",
+                        ".bash_profile",
+                        ".zshrc",
+                        ".profile",
+                    ])
+                    .with_interpreters(&["sh", "bash", "zsh", "ksh", "dash"]),
+                Language::new(Syntax::text()).with_extensions(&["txt"]),
+            ],
+        }
+    }
+}
+
+impl LanguageRegistry {
+    /// Add a language, it takes precedence over the ones already registered
+    pub fn register(&mut self, language: Language) {
+        self.languages.insert(0, language);
+    }
+
+    /// Register every definition file (.toml or .json) of a directory, in name order
+    pub fn with_definitions(mut self, dir: &Path) -> Self {
+        for error in self.load_definitions(dir) {
+            eprintln!("{}", error);
+        }
+        self
+    }
+
+    /// Register the definition files of a directory, returns why the others were skipped
+    fn load_definitions(&mut self, dir: &Path) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return vec![];
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .collect();
+        paths.sort();
+        let mut errors = vec![];
+        for path in paths {
+            match read_definition(&path) {
+                Ok(Some(definition)) => self.register(definition.into_language()),
+                Ok(None) => (),
+                Err(err) => {
+                    errors.push(format!("Error loading syntax {}: {}", path.display(), err))
+                }
+            }
+        }
+        errors
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = vec![];
+        for language in &self.languages {
+            if !names.iter().any(|name| name == language.name()) {
+                names.push(language.name().to_string());
+            }
+        }
+        names.sort();
+        names
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Language> {
+        self.languages
+            .iter()
+            .find(|language| language.name() == name)
+    }
+
+    /// Language of a file from its name, then its extension, then its shebang line.
+    /// The untitled tab is Rust until it is saved under a name.
+    pub fn detect(&self, path: &Path, text: &str) -> Option<&Language> {
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if let Some(language) = self
+            .languages
+            .iter()
+            .find(|language| language.file_names.contains(&file_name))
+        {
+            return Some(language);
+        }
+
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if let Some(language) = self
+            .languages
+            .iter()
+            .find(|language| language.extensions.contains(&extension))
+        {
+            return Some(language);
+        }
+
+        if let Some(language) = shebang_interpreter(text).and_then(|interpreter| {
+            self.languages
+                .iter()
+                .find(|language| language.interpreters.contains(&interpreter))
+        }) {
+            return Some(language);
+        }
+
+        if path == Path::new(UNTITLED) {
+            return self.get(UNTITLED_LANGUAGE);
+        }
+        None
+    }
+
+    /// Syntax of the language chosen by the user, or detected, or plain text
+    pub fn syntax(&self, path: &Path, text: &str, language_override: Option<&str>) -> Arc<Syntax> {
+        language_override
+            .and_then(|name| self.get(name))
+            .or_else(|| self.detect(path, text))
+            .map_or_else(
+                || Arc::new(Syntax::text()),
+                |language| language.syntax.clone(),
+            )
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+/// A language as written by the user in a definition file
+struct Definition {
+    name: String,
+    case_sensitive: bool,
+    comment: String,
+    comment_multiline: [String; 2],
+    keywords: Vec<String>,
+    types: Vec<String>,
+    special: Vec<String>,
+    extensions: Vec<String>,
+    file_names: Vec<String>,
+    interpreters: Vec<String>,
+}
+
+impl Default for Definition {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            case_sensitive: true,
+            comment: String::new(),
+            comment_multiline: [String::new(), String::new()],
+            keywords: vec![],
+            types: vec![],
+            special: vec![],
+            extensions: vec![],
+            file_names: vec![],
+            interpreters: vec![],
+        }
+    }
+}
+
+impl Definition {
+    fn into_language(self) -> Language {
+        // case insensitive syntaxes match the uppercased word
+        let case = |words: Vec<String>| -> Vec<String> {
+            if self.case_sensitive {
+                words
+            } else {
+                words.iter().map(|word| word.to_ascii_uppercase()).collect()
+            }
+        };
+        let [start, end] = &self.comment_multiline;
+        let syntax = Syntax::new(&self.name)
+            .with_case_sensitive(self.case_sensitive)
+            .with_comment(&self.comment)
+            .with_comment_multiline([start, end])
+            .with_keywords(case(self.keywords.clone()))
+            .with_types(case(self.types.clone()))
+            .with_special(case(self.special.clone()));
+        Language {
+            syntax: Arc::new(syntax),
+            extensions: self.extensions,
+            file_names: self.file_names,
+            interpreters: self.interpreters,
+        }
+    }
+}
+
+/// None when the file is not a definition file
+fn read_definition(path: &Path) -> Result<Option<Definition>, std::io::Error> {
+    let extension = path.extension().and_then(|extension| extension.to_str());
+    if !matches!(extension, Some("toml") | Some("json")) {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path)?;
+    let mut definition: Definition = if extension == Some("toml") {
+        toml::from_str(&content).map_err(std::io::Error::other)?
+    } else {
+        serde_json::from_str(&content).map_err(std::io::Error::other)?
+    };
+    if definition.name.is_empty() {
+        definition.name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+    }
+    Ok(Some(definition))
+}
+
+/// "#!/usr/bin/env python3.11" gives "python"
+fn shebang_interpreter(text: &str) -> Option<String> {
+    let line = text.lines().next()?.strip_prefix("#!")?;
+    let mut words = line.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|word| !word.starts_with('-') && !word.contains('='))?;
+    }
+    Some(
+        program
+            .trim_end_matches(|c: char| c.is_ascii_digit() || c == '.')
+            .to_string(),
+    )
+}
+
+fn to_strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
@@ -0,0 +1,135 @@
+use super::*;
+
+fn detected(path: &str, text: &str) -> Option<String> {
+    LanguageRegistry::default()
+        .detect(Path::new(path), text)
+        .map(|language| language.name().to_string())
+}
+
+#[test]
+fn detect_from_extension() {
+    assert_eq!(detected("src/main.rs", "").as_deref(), Some("Rust"));
+    assert_eq!(detected("app.tsx", "").as_deref(), Some("Javascript"));
+    assert_eq!(detected("boot.S", "").as_deref(), Some("Assembly"));
+    assert_eq!(detected("notes.txt", "").as_deref(), Some("Text"));
+    // the extension wins over the shebang
+    assert_eq!(detected("run.py", "#!/bin/sh").as_deref(), Some("Python"));
+    assert_eq!(detected("data.xyz", ""), None);
+}
+
+#[test]
+fn detect_from_file_name() {
+    assert_eq!(detected("/project/Makefile", "").as_deref(), Some("Shell"));
+    assert_eq!(detected("Dockerfile", "").as_deref(), Some("Shell"));
+    assert_eq!(detected("/home/me/.bashrc", "").as_deref(), Some("Shell"));
+    assert_eq!(detected("makefile.old", ""), None);
+}
+
+#[test]
+fn detect_from_shebang() {
+    let script = |shebang: &str| detected("bin/tool", &format!("{}\nbody", shebang));
+    assert_eq!(
+        script("#!/usr/bin/env python3.11").as_deref(),
+        Some("Python")
+    );
+    assert_eq!(
+        script("#!/usr/bin/env -S node --harmony").as_deref(),
+        Some("Javascript")
+    );
+    assert_eq!(script("#! /bin/bash -e").as_deref(), Some("Shell"));
+    assert_eq!(script("#!/usr/bin/luajit").as_deref(), Some("Lua"));
+    assert_eq!(script("#!/usr/bin/perl"), None);
+    assert_eq!(detected("bin/tool", "body\n#!/bin/sh"), None);
+}
+
+#[test]
+fn syntax_of_a_tab() {
+    let registry = LanguageRegistry::default();
+    let syntax = |path: &str, language_override: Option<&str>| {
+        registry
+            .syntax(Path::new(path), "", language_override)
+            .language()
+            .to_string()
+    };
+    assert_eq!(syntax("untitled", None), "Rust");
+    assert_eq!(syntax("data.xyz", None), "Text");
+    assert_eq!(syntax("main.rs", Some("Python")), "Python");
+    assert_eq!(syntax("main.rs", Some("Unknown")), "Rust");
+    // the syntax is shared, not copied
+    let first = registry.syntax(Path::new("a.rs"), "", None);
+    assert!(Arc::ptr_eq(
+        &first,
+        &registry.syntax(Path::new("b.rs"), "", None)
+    ));
+}
+
+#[test]
+fn shebang_interpreters() {
+    assert_eq!(shebang_interpreter("#!/bin/sh").as_deref(), Some("sh"));
+    assert_eq!(
+        shebang_interpreter("#!/usr/bin/env python3.11\n").as_deref(),
+        Some("python")
+    );
+    assert_eq!(
+        shebang_interpreter("#!/usr/bin/env -S LANG=C node").as_deref(),
+        Some("node")
+    );
+    assert_eq!(shebang_interpreter("#!/usr/bin/env"), None);
+    assert_eq!(shebang_interpreter("# comment"), None);
+    assert_eq!(shebang_interpreter(""), None);
+}
+
+#[test]
+fn user_definitions_override_built_in_ones() {
+    let dir = std::env::temp_dir().join(format!("calcifer_syntax_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("a_python.toml"),
+        "name = \"Python\"\nkeywords = [\"spam\"]\nextensions = [\"py\"]\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("b_cobol.json"),
+        r#"{"case_sensitive": false, "keywords": ["move"], "extensions": ["cob"], "comment": "*>"}"#,
+    )
+    .unwrap();
+    fs::write(dir.join("c_broken.toml"), "keywords = [\"unclosed\"").unwrap();
+    fs::write(dir.join("d_wrong_type.json"), r#"{"keywords": "move"}"#).unwrap();
+    fs::write(dir.join("readme.md"), "not a definition").unwrap();
+
+    let mut registry = LanguageRegistry::default();
+    let errors = registry.load_definitions(&dir);
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].contains("c_broken.toml"));
+    assert!(errors[1].contains("d_wrong_type.json"));
+
+    let python = registry.detect(Path::new("main.py"), "").unwrap();
+    assert!(python.syntax.keywords.contains("spam"));
+    assert_eq!(
+        registry
+            .names()
+            .iter()
+            .filter(|name| *name == "Python")
+            .count(),
+        1
+    );
+    // found by its name before the built-in one
+    assert_eq!(registry.get("Python"), Some(python));
+
+    // named after its file, with uppercased keywords as it ignores case
+    let cobol = registry.detect(Path::new("pay.cob"), "").unwrap();
+    assert_eq!(cobol.name(), "b_cobol");
+    assert!(cobol.syntax.keywords.contains("MOVE"));
+    assert_eq!(cobol.syntax.comment(), "*>");
+
+    // the other built-in languages are still there
+    assert_eq!(
+        registry.detect(Path::new("lib.rs"), "").unwrap().name(),
+        "Rust"
+    );
+    assert_eq!(
+        LanguageRegistry::default().with_definitions(&dir).names(),
+        registry.names()
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
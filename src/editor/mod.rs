@@ -1,15 +1,33 @@
 #![allow(dead_code)]
 
+pub mod brackets;
+pub mod cursors;
+pub mod folds;
 pub mod highlighting;
+pub mod history;
+pub mod indent;
+pub mod languages;
+pub mod position;
+pub mod symbols;
 mod syntax;
 pub mod themes;
 
+#[cfg(test)]
+mod tests;
+
 use eframe::egui;
 use egui::{text::CCursor, text_edit::CCursorRange};
 use highlighting::highlight;
+pub use highlighting::Underline;
+pub use history::{EditKind, UndoHistory};
+pub use indent::Indentation;
+pub use languages::LanguageRegistry;
+use position::Column;
 use std::cmp::{max, min};
+use std::collections::BTreeSet;
 use std::hash::{Hash, Hasher};
-use std::ops::{Bound, RangeBounds};
+use std::ops::{Bound, Range, RangeBounds};
+use std::sync::Arc;
 pub use syntax::{Syntax, TokenType};
 pub use themes::ColorTheme;
 
@@ -74,7 +92,13 @@ impl StringUtils for str {
 pub struct CodeEditor {
     id: String,
     theme: ColorTheme,
-    syntax: Syntax,
+    syntax: Arc<Syntax>,
+    indentation: Indentation,
+    underlines: Vec<Underline>,
+    highlights: Vec<Range<usize>>,
+    markers: Vec<(usize, egui::Color32)>,
+    /// Byte ranges of the folded lines, laid out with no height
+    hidden: Vec<Range<usize>>,
     numlines: bool,
     fontsize: f32,
     rows: usize,
@@ -89,6 +113,10 @@ impl Hash for CodeEditor {
         #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
         (self.fontsize as u32).hash(state);
         self.syntax.hash(state);
+        self.underlines.hash(state);
+        self.highlights.hash(state);
+        self.markers.hash(state);
+        self.hidden.hash(state);
     }
 }
 
@@ -96,8 +124,13 @@ impl Default for CodeEditor {
     fn default() -> CodeEditor {
         CodeEditor {
             id: String::from("Code Editor"),
-            theme: ColorTheme::GRUVBOX,
-            syntax: Syntax::rust(),
+            theme: ColorTheme::gruvbox(),
+            syntax: Arc::new(Syntax::rust()),
+            indentation: Indentation::Tabs,
+            underlines: vec![],
+            highlights: vec![],
+            markers: vec![],
+            hidden: vec![],
             numlines: true,
             fontsize: 10.0,
             rows: 10,
@@ -156,8 +189,42 @@ impl CodeEditor {
     /// Use custom syntax for highlighting
     ///
     /// **Default: Rust**
-    pub fn with_syntax(self, syntax: Syntax) -> Self {
-        CodeEditor { syntax, ..self }
+    pub fn with_syntax(self, syntax: impl Into<Arc<Syntax>>) -> Self {
+        CodeEditor {
+            syntax: syntax.into(),
+            ..self
+        }
+    }
+
+    /// Indentation inserted by Tab, Enter and block indent/outdent
+    ///
+    /// **Default: Tabs**
+    pub fn with_indentation(self, indentation: Indentation) -> Self {
+        CodeEditor {
+            indentation,
+            ..self
+        }
+    }
+
+    /// Underline byte ranges of the text, used for diagnostics
+    ///
+    /// **Default: none**
+    pub fn with_underlines(self, underlines: Vec<Underline>) -> Self {
+        CodeEditor { underlines, ..self }
+    }
+
+    /// Give a background to byte ranges of the text, used for search results
+    ///
+    /// **Default: none**
+    pub fn with_highlights(self, highlights: Vec<Range<usize>>) -> Self {
+        CodeEditor { highlights, ..self }
+    }
+
+    /// Mark zero based lines in the gutter, the first marker of a line gives its color
+    ///
+    /// **Default: none**
+    pub fn with_markers(self, markers: Vec<(usize, egui::Color32)>) -> Self {
+        CodeEditor { markers, ..self }
     }
 
     /// Turn on/off scrolling on the vertical axis.
@@ -195,7 +262,16 @@ impl CodeEditor {
         egui::text::TextFormat::simple(font_id, color)
     }
 
-    fn numlines_show(&self, ui: &mut egui::Ui, text: &str) {
+    /// Line numbers, with the markers and the fold signs of the lines
+    ///
+    /// Returns the foldable line clicked
+    fn numlines_show(
+        &self,
+        ui: &mut egui::Ui,
+        text: &str,
+        ranges: &folds::FoldRanges,
+        folded: &BTreeSet<usize>,
+    ) -> Option<usize> {
         let total = if text.ends_with('\n') || text.is_empty() {
             text.lines().count() + 1
         } else {
@@ -203,11 +279,33 @@ impl CodeEditor {
         }
         .max(self.rows);
         let max_indent = total.to_string().len();
+        let marker = |line: usize| {
+            self.markers
+                .iter()
+                .find(|(marked, _)| *marked == line)
+                .map(|(_, color)| *color)
+        };
+        // a column for the markers only when there are some, same for the folds
+        let marker_width = usize::from(!self.markers.is_empty());
+        let fold_width = usize::from(!ranges.is_empty());
+        let hidden = folds::hidden_lines(folded, ranges);
+        let is_hidden = |line: usize| hidden.iter().any(|lines| lines.contains(&line));
         let mut counter = (1..=total)
             .map(|i| {
                 let label = i.to_string();
+                let sign = match marker(i - 1) {
+                    Some(_) => "●",
+                    None if marker_width > 0 => " ",
+                    None => "",
+                };
+                let fold = match ranges.contains_key(&(i - 1)) {
+                    true if folded.contains(&(i - 1)) => "▶",
+                    true => "▼",
+                    false if fold_width > 0 => " ",
+                    false => "",
+                };
                 format!(
-                    "{}{label}",
+                    "{sign}{}{label}{fold}",
                     " ".repeat(max_indent.saturating_sub(label.len()))
                 )
             })
@@ -215,32 +313,53 @@ impl CodeEditor {
             .join("\n");
 
         #[allow(clippy::cast_precision_loss)]
-        let width = max_indent as f32 * self.fontsize * 0.5;
+        let width = (max_indent + marker_width + fold_width) as f32 * self.fontsize * 0.5;
 
         let mut layouter = |ui: &egui::Ui, string: &str, _wrap_width: f32| {
-            let layout_job = egui::text::LayoutJob::single_section(
-                string.to_string(),
-                egui::TextFormat::simple(
-                    egui::FontId::monospace(self.fontsize),
-                    self.theme.type_color(TokenType::Comment(true)),
-                ),
-            );
+            let font_id = egui::FontId::monospace(self.fontsize);
+            let default_color = self.theme.type_color(TokenType::Comment(true));
+            let mut layout_job = egui::text::LayoutJob::default();
+            for (line, label) in string.split_inclusive('\n').enumerate() {
+                let mut format = egui::TextFormat::simple(
+                    font_id.clone(),
+                    marker(line).unwrap_or(default_color),
+                );
+                if is_hidden(line) {
+                    format.line_height = Some(0.0);
+                    format.color = egui::Color32::TRANSPARENT;
+                }
+                layout_job.append(label, 0.0, format);
+            }
             ui.fonts(|f| f.layout_job(layout_job))
         };
 
-        ui.add(
-            egui::TextEdit::multiline(&mut counter)
-                .id_source(format!("{}_numlines", self.id))
-                .font(egui::TextStyle::Monospace)
-                .interactive(false)
-                .frame(false)
-                .desired_rows(self.rows)
-                .desired_width(width)
-                .layouter(&mut layouter),
-        );
+        let output = egui::TextEdit::multiline(&mut counter)
+            .id_source(format!("{}_numlines", self.id))
+            .font(egui::TextStyle::Monospace)
+            .interactive(false)
+            .frame(false)
+            .desired_rows(self.rows)
+            .desired_width(width)
+            .layouter(&mut layouter)
+            .show(ui);
+
+        let id = ui.make_persistent_id(format!("{}_folds", self.id));
+        let response = ui.interact(output.response.rect, id, egui::Sense::click());
+        let pointer = response
+            .interact_pointer_pos()
+            .filter(|_| response.clicked())?;
+        let line = output
+            .galley
+            .cursor_from_pos(pointer - output.text_draw_pos)
+            .rcursor
+            .row;
+        ranges.contains_key(&line).then_some(line)
     }
 
     /// Show Code Editor
+    ///
+    /// Returns the char index under the mouse pointer
+    #[allow(clippy::too_many_arguments)]
     pub fn show(
         &mut self,
         ui: &mut egui::Ui,
@@ -249,13 +368,37 @@ impl CodeEditor {
         last_cursor: &mut Option<CCursorRange>,
         vertical_offset: &mut f32,
         override_cursor: Option<CCursorRange>,
-    ) {
+        history: &mut UndoHistory,
+        extra_cursors: &mut Vec<CCursorRange>,
+        folded: &mut BTreeSet<usize>,
+    ) -> Option<usize> {
         //let mut text_edit_output: Option<TextEditOutput> = None;
+        let mut hovered: Option<usize> = None;
+        // changes made from outside since the last frame, replace or reload
+        folds::shift_folds(folded, history.text(), text);
+        history.record(text, *last_cursor, EditKind::Command);
+
+        let ranges = folds::cached_fold_ranges(ui.ctx(), &self.syntax, text);
+        folded.retain(|line| ranges.contains_key(line));
+        // a cursor placed inside a fold, from outside or by undo, opens it
+        if let Some(cursor) = override_cursor.or(*last_cursor) {
+            let (line, _) = position::char_to_line_col(text, cursor.primary.index, Column::Chars);
+            folded.retain(|start| !(start + 1..=ranges[start]).contains(&line));
+        }
+        let hidden = folds::hidden_lines(folded, &ranges);
+        self.hidden = folds::hidden_bytes(text, &hidden);
+        let frame_text = text.clone();
+
         let mut code_editor = |ui: &mut egui::Ui| {
             ui.horizontal_top(|h| {
                 self.theme.modify_style(h, self.fontsize);
                 if self.numlines {
-                    self.numlines_show(h, text);
+                    if let Some(line) = self.numlines_show(h, text, &ranges, folded) {
+                        if !folded.remove(&line) {
+                            folded.insert(line);
+                        }
+                        h.ctx().request_repaint();
+                    }
                 }
                 egui::ScrollArea::horizontal()
                     .id_source(format!("{}_inner_scroll", self.id))
@@ -265,6 +408,88 @@ impl CodeEditor {
                             ui.fonts(|f| f.layout_job(layout_job))
                         };
 
+                        // before the text edit, which would otherwise use its own undo
+                        // and apply the keys at its one cursor only
+                        let mut restored = None;
+                        let previous_cursor = *last_cursor;
+                        if ui.memory(|m| m.has_focus(ui.make_persistent_id(&self.id))) {
+                            let pressed =
+                                |modifiers, key| ui.input_mut(|i| i.consume_key(modifiers, key));
+                            // Ctrl+Z also matches with Shift held, so redo is checked first
+                            let shift = egui::Modifiers::COMMAND | egui::Modifiers::SHIFT;
+                            let alt = egui::Modifiers::COMMAND | egui::Modifiers::ALT;
+                            if pressed(egui::Modifiers::COMMAND, egui::Key::Y)
+                                || pressed(shift, egui::Key::Z)
+                            {
+                                restored = history.redo(text);
+                            } else if pressed(egui::Modifiers::COMMAND, egui::Key::Z) {
+                                restored = history.undo(text);
+                            }
+                            if restored.is_some() {
+                                *saved = false;
+                                extra_cursors.clear();
+                            } else if let Some(primary) = *last_cursor {
+                                let mut list = vec![primary];
+                                list.extend(extra_cursors.iter().copied());
+                                let before = text.clone();
+                                let handled = if pressed(egui::Modifiers::COMMAND, egui::Key::D) {
+                                    cursors::add_next_occurrence(text, &mut list);
+                                    true
+                                } else if pressed(egui::Modifiers::COMMAND, egui::Key::M) {
+                                    let index = list[0].primary.index;
+                                    let pair =
+                                        brackets::pair_at(ui.ctx(), &self.syntax, text, index);
+                                    if let Some((_, other)) = pair {
+                                        list = vec![CCursorRange::one(CCursor::new(other))];
+                                    }
+                                    true
+                                } else if pressed(alt, egui::Key::OpenBracket) {
+                                    folded.extend(ranges.keys());
+                                    true
+                                } else if pressed(alt, egui::Key::CloseBracket) {
+                                    folded.clear();
+                                    true
+                                } else if pressed(shift, egui::Key::OpenBracket) {
+                                    let index = list[0].primary.index;
+                                    let (line, _) =
+                                        position::char_to_line_col(text, index, Column::Chars);
+                                    if let Some(start) = folds::fold_at(&ranges, folded, line) {
+                                        folded.insert(start);
+                                        let end = (start, usize::MAX);
+                                        let index =
+                                            position::line_col_to_char(text, end, Column::Chars);
+                                        list = vec![CCursorRange::one(CCursor::new(index))];
+                                    }
+                                    true
+                                } else if pressed(shift, egui::Key::CloseBracket) {
+                                    let index = list[0].primary.index;
+                                    let (line, _) =
+                                        position::char_to_line_col(text, index, Column::Chars);
+                                    folded
+                                        .retain(|start| !(*start..=ranges[start]).contains(&line));
+                                    true
+                                } else if pressed(shift, egui::Key::E) {
+                                    self.toggle_comment(text, &mut list, true);
+                                    history.record(text, list.first().copied(), EditKind::Command);
+                                    true
+                                } else if pressed(egui::Modifiers::COMMAND, egui::Key::E) {
+                                    self.toggle_comment(text, &mut list, false);
+                                    history.record(text, list.first().copied(), EditKind::Command);
+                                    true
+                                } else {
+                                    self.cursor_input(ui, text, &mut list)
+                                };
+                                if handled {
+                                    restored = Some(list.remove(0));
+                                    *extra_cursors = list;
+                                }
+                                if *text != before {
+                                    *saved = false;
+                                }
+                            }
+                        }
+                        let override_cursor = restored.or(override_cursor);
+
                         let previous_text = text.clone();
 
                         let mut output = egui::TextEdit::multiline(text)
@@ -289,22 +514,19 @@ impl CodeEditor {
                         }
 
                         if output.response.has_focus()
-                            && ui.input(|i| i.key_pressed(egui::Key::E) && i.modifiers.ctrl)
-                        {
-                            if let Some(range) = last_cursor {
-                                (*text, extend) =
-                                    self.toggle_start_of_line(*range, text.clone(), "//");
-                                get_new_cursor = false;
-                            }
-                        }
-
-                        if output.response.has_focus()
-                            && ui.input(|i| i.key_pressed(egui::Key::Tab))
+                            && ui.input(|i| i.key_pressed(egui::Key::Tab) && !i.modifiers.shift)
                         {
                             if let Some(range) = last_cursor {
                                 if range.primary.index != range.secondary.index {
+                                    (*text, extend) = self.add_start_of_line(
+                                        *range,
+                                        previous_text.clone(),
+                                        &self.indentation.unit(),
+                                    );
+                                    get_new_cursor = false;
+                                } else if self.indentation != Indentation::Tabs {
                                     (*text, extend) =
-                                        self.add_start_of_line(*range, previous_text.clone(), "\t");
+                                        self.insert_indent(*range, previous_text.clone());
                                     get_new_cursor = false;
                                 }
                             }
@@ -318,7 +540,7 @@ impl CodeEditor {
                                     (*text, extend) = self.remove_start_of_line(
                                         *range,
                                         previous_text.clone(),
-                                        "\t",
+                                        &self.indentation.unit(),
                                     );
                                     get_new_cursor = false;
                                 }
@@ -329,6 +551,7 @@ impl CodeEditor {
                             output.response.request_focus();
                             output.state.set_ccursor_range(override_cursor);
                             output.state.store(ui.ctx(), output.response.id);
+                            *last_cursor = override_cursor;
                         } else if get_new_cursor {
                             *last_cursor = output.state.clone().ccursor_range();
                         } else if let Some(cursor_range) = *last_cursor {
@@ -352,9 +575,124 @@ impl CodeEditor {
                             *last_cursor = cursor;
                         }
 
+                        let alt = ui.input(|i| i.modifiers.alt);
+                        let pressed_here =
+                            output.response.hovered() && ui.input(|i| i.pointer.primary_pressed());
+                        if output.response.dragged() && alt {
+                            // Alt+drag, a column selection replaces the cursors
+                            let char_width = ui.fonts(|f| {
+                                f.glyph_width(&egui::FontId::monospace(self.fontsize), ' ')
+                            });
+                            let cell = |pos: egui::Pos2| {
+                                let relative = pos - output.text_draw_pos;
+                                let cursor = output.galley.cursor_from_pos(relative);
+                                let row_end = output
+                                    .galley
+                                    .rows
+                                    .get(cursor.rcursor.row)
+                                    .map_or(relative.x, |row| row.rect.right());
+                                let past_end =
+                                    ((relative.x - row_end) / char_width).round().max(0.0) as usize;
+                                (cursor.rcursor.row, cursor.rcursor.column + past_end)
+                            };
+                            let (origin, head) =
+                                ui.input(|i| (i.pointer.press_origin(), i.pointer.interact_pos()));
+                            if let (Some(origin), Some(head)) = (origin, head) {
+                                let mut list =
+                                    cursors::column_selection(text, cell(origin), cell(head));
+                                let primary = list.remove(0);
+                                let mut state =
+                                    egui::TextEdit::load_state(ui.ctx(), output.response.id)
+                                        .unwrap_or_default();
+                                state.set_ccursor_range(Some(primary));
+                                state.store(ui.ctx(), output.response.id);
+                                *last_cursor = Some(primary);
+                                *extra_cursors = list;
+                            }
+                        } else if pressed_here && alt {
+                            // Alt+click, the cursor that was there stays
+                            if let (Some(previous), Some(primary)) = (previous_cursor, *last_cursor)
+                            {
+                                let mut list = vec![primary, previous];
+                                list.extend(extra_cursors.iter().copied());
+                                cursors::merge(&mut list);
+                                *extra_cursors = list.split_off(1);
+                            }
+                        } else if pressed_here {
+                            extra_cursors.clear();
+                        }
+                        // the text edit moves through the folded lines as if they were shown
+                        let moved = (*last_cursor).zip(previous_cursor);
+                        if let Some((cursor, previous)) = moved.filter(|_| {
+                            override_cursor.is_none()
+                                && previous_text == *text
+                                && !hidden.is_empty()
+                        }) {
+                            let index = folds::skip_hidden(
+                                text,
+                                &hidden,
+                                cursor.primary.index,
+                                previous.primary.index,
+                            );
+                            if index != cursor.primary.index {
+                                let mut skipped = CCursorRange::one(CCursor::new(index));
+                                if cursor.primary != cursor.secondary {
+                                    skipped.secondary = cursor.secondary;
+                                }
+                                let mut state =
+                                    egui::TextEdit::load_state(ui.ctx(), output.response.id)
+                                        .unwrap_or_default();
+                                state.set_ccursor_range(Some(skipped));
+                                state.store(ui.ctx(), output.response.id);
+                                *last_cursor = Some(skipped);
+                            }
+                        }
+                        for lines in &hidden {
+                            let Some(row) = output.galley.rows.get(lines.start() - 1) else {
+                                continue;
+                            };
+                            let at = row.rect.right_center() + output.text_draw_pos.to_vec2();
+                            ui.painter().text(
+                                at + egui::vec2(self.fontsize * 0.5, 0.0),
+                                egui::Align2::LEFT_CENTER,
+                                "⋯",
+                                egui::FontId::monospace(self.fontsize),
+                                self.theme.type_color(TokenType::Comment(true)),
+                            );
+                        }
+
+                        if output.response.has_focus() {
+                            let cursor = last_cursor.filter(|c| c.primary == c.secondary);
+                            if let Some((at, other)) = cursor.and_then(|cursor| {
+                                let index = cursor.primary.index;
+                                brackets::pair_at(ui.ctx(), &self.syntax, text, index)
+                            }) {
+                                let origin = output.text_draw_pos;
+                                paint_brackets(ui, &output.galley, origin, [at, other]);
+                            }
+                        }
+                        if !extra_cursors.is_empty() {
+                            paint_cursors(ui, &output.galley, output.text_draw_pos, extra_cursors);
+                        }
+
                         if previous_text != text.clone() {
                             *saved = false;
                         }
+                        let kind = if get_new_cursor {
+                            EditKind::Typing
+                        } else {
+                            EditKind::Command
+                        };
+                        history.record(text, *last_cursor, kind);
+                        folds::shift_folds(folded, &frame_text, text);
+
+                        if let Some(pointer) = output.response.hover_pos() {
+                            let relative = pointer - output.text_draw_pos;
+                            if output.galley.rect.contains(relative.to_pos2()) {
+                                hovered =
+                                    Some(output.galley.cursor_from_pos(relative).ccursor.index);
+                            }
+                        }
                         //text_edit_output = Some(output);
                     });
             });
@@ -371,32 +709,135 @@ impl CodeEditor {
         }
 
         //text_edit_output.expect("TextEditOutput should exist at this point")
+        hovered
     }
 
-    fn toggle_start_of_line(
+    /// Keys taken out of the input before the text edit sees them: brackets and quotes
+    /// typed, and while there are several cursors, every key applied at all of them.
+    /// Returns whether any key was handled.
+    fn cursor_input(&self, ui: &egui::Ui, text: &mut String, list: &mut Vec<CCursorRange>) -> bool {
+        let events = ui.input(|i| i.events.clone());
+        // once a key is handled here, the text edit would apply the next ones at its old cursor
+        let mut taken = list.len() > 1;
+        let handled: Vec<bool> = events
+            .iter()
+            .map(|event| {
+                let handled = match event {
+                    egui::Event::Text(typed) if self.type_bracket(text, list, typed) => true,
+                    egui::Event::Key {
+                        key: egui::Key::Backspace,
+                        pressed: true,
+                        modifiers,
+                        ..
+                    } if modifiers.is_none() && brackets::delete_pair(text, list) => true,
+                    _ if !taken => false,
+                    egui::Event::Text(typed) | egui::Event::Paste(typed) => {
+                        cursors::type_text(text, list, typed);
+                        true
+                    }
+                    egui::Event::Copy | egui::Event::Cut => {
+                        let copied = cursors::selected_text(text, list);
+                        ui.ctx().output_mut(|o| o.copied_text = copied);
+                        if *event == egui::Event::Cut {
+                            cursors::type_text(text, list, "");
+                        }
+                        true
+                    }
+                    egui::Event::Key {
+                        key,
+                        pressed: true,
+                        modifiers,
+                        ..
+                    } => self.multi_cursor_key(text, list, *key, *modifiers),
+                    _ => false,
+                };
+                taken |= handled;
+                handled
+            })
+            .collect();
+        ui.input_mut(|i| {
+            let mut index = 0;
+            i.events.retain(|_| {
+                index += 1;
+                !handled[index - 1]
+            });
+        });
+        handled.contains(&true)
+    }
+
+    fn type_bracket(&self, text: &mut String, list: &mut [CCursorRange], typed: &str) -> bool {
+        let mut chars = typed.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => brackets::type_char(text, list, c, &self.syntax, self.indentation),
+            _ => false,
+        }
+    }
+
+    fn multi_cursor_key(
         &self,
-        cursor_range: CCursorRange,
-        text: String,
-        head: &str,
-    ) -> (String, isize) {
-        let mut substring = self
-            .get_selection_substring(text.clone(), cursor_range)
-            .clone();
-        let mut new_text: String = "".into();
-        let extend: isize;
+        text: &mut String,
+        list: &mut Vec<CCursorRange>,
+        key: egui::Key,
+        modifiers: egui::Modifiers,
+    ) -> bool {
+        if modifiers.command || modifiers.alt {
+            return false;
+        }
+        let selection = list.iter().any(|cursor| !cursors::range(cursor).is_empty());
+        let motion = match key {
+            egui::Key::Backspace => {
+                cursors::backspace(text, list);
+                return true;
+            }
+            egui::Key::Delete => {
+                cursors::delete(text, list);
+                return true;
+            }
+            egui::Key::Enter => {
+                cursors::new_line(text, list, self.indentation);
+                return true;
+            }
+            egui::Key::Tab if modifiers.shift => {
+                cursors::outdent(text, list, self.indentation);
+                return true;
+            }
+            egui::Key::Tab if selection => {
+                cursors::indent(text, list, self.indentation);
+                return true;
+            }
+            egui::Key::Tab => {
+                cursors::type_text(text, list, &self.indentation.unit());
+                return true;
+            }
+            egui::Key::Escape => {
+                list.truncate(1);
+                return true;
+            }
+            _ if modifiers.shift => return false,
+            egui::Key::ArrowLeft => cursors::Motion::Left,
+            egui::Key::ArrowRight => cursors::Motion::Right,
+            egui::Key::ArrowUp => cursors::Motion::Up,
+            egui::Key::ArrowDown => cursors::Motion::Down,
+            egui::Key::Home => cursors::Motion::Home,
+            egui::Key::End => cursors::Motion::End,
+            _ => return false,
+        };
+        cursors::move_cursors(text, list, motion);
+        true
+    }
 
-        if substring[1].slice(..head.len() + 1).contains(head) {
-            extend = -self.delta_char(substring[1].clone(), head);
-            substring[1] = substring[1].replace(&format!("\n{}", head), "\n");
+    /// Ctrl+E comments lines and Ctrl+Shift+E wraps the selections, each falling
+    /// back to the other when the language only has one kind of comment
+    fn toggle_comment(&self, text: &mut String, list: &mut [CCursorRange], block: bool) {
+        let line = self.syntax.comment();
+        let [start, end] = self.syntax.comment_multiline();
+        // languages with one marker repeat it as their block comment
+        let has_block = !start.is_empty() && !end.is_empty() && start != line;
+        if has_block && (block || line.is_empty()) {
+            cursors::toggle_block_comment(text, list, [start, end]);
         } else {
-            extend = self.delta_char(substring[1].clone(), head);
-            substring[1] = substring[1].replace(&"\n".to_string(), &format!("\n{}", head));
+            cursors::toggle_comment(text, list, line);
         }
-        new_text.push_str(&substring[0].clone());
-        new_text.push_str(&substring[1].clone());
-        new_text.push_str(&substring[2].clone());
-
-        (new_text, extend)
     }
 
     fn add_start_of_line(
@@ -461,13 +902,24 @@ impl CodeEditor {
     }
 
     fn delta_char(&self, text: String, modifier: &str) -> isize {
-        (modifier.len()
+        (modifier.chars().count()
             * text
                 .match_indices(&"\n".to_string())
                 .collect::<Vec<_>>()
                 .len()) as isize
     }
 
+    fn insert_indent(&self, cursor_range: CCursorRange, text: String) -> (String, isize) {
+        let cursor = min(cursor_range.primary.index, cursor_range.secondary.index);
+        let unit = self.indentation.unit();
+
+        let mut new_text: String = text.slice(..cursor).to_string();
+        new_text.push_str(&unit);
+        new_text.push_str(text.slice(cursor..));
+
+        (new_text, unit.chars().count() as isize)
+    }
+
     fn new_line(&self, cursor_range: CCursorRange, text: String) -> (String, isize) {
         let cursor = min(cursor_range.primary.index, cursor_range.secondary.index);
 
@@ -480,21 +932,87 @@ impl CodeEditor {
         while last_line_break > 0 && text.char_at(last_line_break) != '\n' {
             last_line_break -= 1;
         }
-        let indent_depth = text
-            .slice(last_line_break..cursor)
-            .match_indices(&"\t".to_string())
-            .collect::<Vec<_>>()
-            .len();
-
-        let new_indent_depth = indent_depth;
+        let previous_line = text.slice(last_line_break..cursor);
+        let previous_line = previous_line.strip_prefix('\n').unwrap_or(previous_line);
+        let indent_depth = self.indentation.depth(previous_line);
+
+        let mut new_indent = self.indentation.unit().repeat(indent_depth);
+        let mut closing_line = String::new();
+        if brackets::opens_block(&self.syntax, previous_line)
+            && brackets::in_code(&self.syntax, &text, cursor)
+        {
+            // between a pair just opened, its closing bracket goes on the line after
+            let opening = previous_line.trim_end().chars().last().unwrap_or_default();
+            let closing = brackets::closing_of(opening);
+            if closing.is_some() && text.chars().nth(cursor + 1) == closing {
+                closing_line = format!("\n{}", new_indent);
+            }
+            new_indent.push_str(&self.indentation.unit());
+        }
 
         let mut new_text: String = text.clone().slice(..(cursor + 1)).to_string();
-        new_text.push_str(&"\t".repeat(new_indent_depth));
+        new_text.push_str(&new_indent);
+        new_text.push_str(&closing_line);
         new_text.push_str(text.clone().slice((cursor + 1)..));
 
         (
             new_text.clone().to_string(),
-            (new_indent_depth + 1) as isize,
+            (new_indent.chars().count() + 1) as isize,
         )
     }
 }
+
+/// Carets and selections of the cursors the text edit does not know about
+fn paint_cursors(ui: &egui::Ui, galley: &egui::Galley, origin: egui::Pos2, list: &[CCursorRange]) {
+    let painter = ui.painter();
+    let caret = ui.visuals().text_cursor;
+    let fill = ui.visuals().selection.bg_fill.gamma_multiply(0.5);
+    let cursor_at = |index: usize| galley.from_ccursor(CCursor::new(index));
+    let rect_at = |index: usize| {
+        galley
+            .pos_from_cursor(&cursor_at(index))
+            .translate(origin.to_vec2())
+    };
+    for cursor in list {
+        let range = cursors::range(cursor);
+        let (start, end) = (
+            cursor_at(range.start).rcursor.row,
+            cursor_at(range.end).rcursor.row,
+        );
+        for row in start..=end {
+            if range.is_empty() {
+                break;
+            }
+            let Some(placed) = galley.rows.get(row) else {
+                continue;
+            };
+            let row_rect = placed.rect.translate(origin.to_vec2());
+            let left = if row == start {
+                rect_at(range.start).left()
+            } else {
+                row_rect.left()
+            };
+            let right = if row == end {
+                rect_at(range.end).left()
+            } else {
+                row_rect.right()
+            };
+            let rect = egui::Rect::from_x_y_ranges(left..=right, row_rect.y_range());
+            painter.rect_filled(rect, 0.0, fill);
+        }
+        let at = rect_at(cursor.primary.index);
+        painter.line_segment([at.center_top(), at.center_bottom()], caret);
+    }
+}
+
+/// Outline of the bracket at the cursor and of the one matching it
+fn paint_brackets(ui: &egui::Ui, galley: &egui::Galley, origin: egui::Pos2, brackets: [usize; 2]) {
+    let stroke = egui::Stroke::new(1.0, ui.visuals().text_cursor.color);
+    for index in brackets {
+        let left = galley.pos_from_cursor(&galley.from_ccursor(CCursor::new(index)));
+        let right = galley.pos_from_cursor(&galley.from_ccursor(CCursor::new(index + 1)));
+        let rect = egui::Rect::from_x_y_ranges(left.left()..=right.left(), left.y_range());
+        ui.painter()
+            .rect_stroke(rect.translate(origin.to_vec2()), 1.0, stroke);
+    }
+}
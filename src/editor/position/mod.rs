@@ -0,0 +1,93 @@
+//! Conversions between the ways a place in a text is counted:
+//! byte offsets (str slicing, regex matches), char indices (egui cursors)
+//! and line/column pairs (display, language servers).
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// What a column counts
+pub enum Column {
+    Chars,
+    /// Used by the language server protocol
+    Utf16,
+}
+
+impl Column {
+    fn width(&self, c: char) -> usize {
+        match self {
+            Column::Chars => 1,
+            Column::Utf16 => c.len_utf16(),
+        }
+    }
+}
+
+/// Char index of a byte offset, an offset inside a char counts as that char
+pub fn byte_to_char(text: &str, byte: usize) -> usize {
+    text.char_indices()
+        .take_while(|&(offset, c)| offset + c.len_utf8() <= byte)
+        .count()
+}
+
+/// Byte offset of a char index, clamped to the end of the text
+pub fn char_to_byte(text: &str, char_index: usize) -> usize {
+    text.char_indices()
+        .nth(char_index)
+        .map_or(text.len(), |(offset, _)| offset)
+}
+
+/// Zero based line and column of a char index
+pub fn char_to_line_col(text: &str, char_index: usize, column: Column) -> (usize, usize) {
+    let mut line = 0;
+    let mut col = 0;
+    for c in text.chars().take(char_index) {
+        if c == '\n' {
+            line += 1;
+            col = 0;
+        } else {
+            col += column.width(c);
+        }
+    }
+    (line, col)
+}
+
+/// Zero based line and column of a byte offset
+pub fn byte_to_line_col(text: &str, byte: usize, column: Column) -> (usize, usize) {
+    char_to_line_col(text, byte_to_char(text, byte), column)
+}
+
+/// Byte offset of a line and column, clamped to the end of the line,
+/// or to the end of the text past the last line
+pub fn line_col_to_byte(text: &str, (line, col): (usize, usize), column: Column) -> usize {
+    let mut line_start = 0;
+    for _ in 0..line {
+        match text[line_start..].find('\n') {
+            Some(offset) => line_start += offset + 1,
+            None => return text.len(),
+        }
+    }
+    let mut width = 0;
+    for (offset, c) in text[line_start..].char_indices() {
+        if width >= col || c == '\n' {
+            return line_start + offset;
+        }
+        width += column.width(c);
+    }
+    text.len()
+}
+
+/// Char index of a line and column, clamped like `line_col_to_byte`
+pub fn line_col_to_char(text: &str, position: (usize, usize), column: Column) -> usize {
+    byte_to_char(text, line_col_to_byte(text, position, column))
+}
+
+/// Zero based line and column of a one based `line` or `line:col` typed by the user
+pub fn parse_line_col(input: &str) -> Option<(usize, usize)> {
+    let mut parts = input.trim().splitn(2, ':');
+    let line: usize = parts.next()?.trim().parse().ok()?;
+    let col: usize = match parts.next() {
+        Some(col) => col.trim().parse().ok()?,
+        None => 1,
+    };
+    Some((line.checked_sub(1)?, col.saturating_sub(1)))
+}
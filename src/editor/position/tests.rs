@@ -0,0 +1,82 @@
+use super::*;
+
+// "é" is 2 bytes, "😀" is 4 bytes and 2 utf16 units, "─" is 3 bytes
+const TEXT: &str = "// café 😀\nlet x = \"──\";\n";
+
+#[test]
+fn byte_and_char_round_trip() {
+    let byte = TEXT.find('😀').unwrap();
+    assert_eq!(byte, 9);
+    assert_eq!(byte_to_char(TEXT, byte), 8);
+    assert_eq!(char_to_byte(TEXT, 8), byte);
+
+    for (char_index, (offset, _)) in TEXT.char_indices().enumerate() {
+        assert_eq!(byte_to_char(TEXT, offset), char_index);
+        assert_eq!(char_to_byte(TEXT, char_index), offset);
+    }
+}
+
+#[test]
+fn byte_inside_char() {
+    // the second byte of "é" belongs to it
+    assert_eq!(byte_to_char(TEXT, 6), 6);
+    assert_eq!(byte_to_char(TEXT, 7), 6);
+}
+
+#[test]
+fn clamped_to_text() {
+    let chars = TEXT.chars().count();
+    assert_eq!(byte_to_char(TEXT, TEXT.len() + 10), chars);
+    assert_eq!(char_to_byte(TEXT, chars + 10), TEXT.len());
+}
+
+#[test]
+fn line_and_column() {
+    let quote = TEXT.find('─').unwrap();
+    assert_eq!(byte_to_line_col(TEXT, quote, Column::Chars), (1, 9));
+    assert_eq!(line_col_to_byte(TEXT, (1, 9), Column::Chars), quote);
+
+    let emoji = TEXT.find('😀').unwrap();
+    assert_eq!(byte_to_line_col(TEXT, emoji, Column::Chars), (0, 8));
+    assert_eq!(byte_to_line_col(TEXT, TEXT.len(), Column::Chars), (2, 0));
+
+    let end_of_first_line = TEXT.find('\n').unwrap();
+    assert_eq!(
+        byte_to_line_col(TEXT, end_of_first_line, Column::Chars),
+        (0, 9)
+    );
+    assert_eq!(
+        byte_to_line_col(TEXT, end_of_first_line, Column::Utf16),
+        (0, 10)
+    );
+}
+
+#[test]
+fn line_and_column_clamped() {
+    let end_of_first_line = TEXT.find('\n').unwrap();
+    assert_eq!(
+        line_col_to_byte(TEXT, (0, 99), Column::Chars),
+        end_of_first_line
+    );
+    assert_eq!(line_col_to_char(TEXT, (0, 99), Column::Chars), 9);
+    assert_eq!(line_col_to_byte(TEXT, (9, 0), Column::Chars), TEXT.len());
+}
+
+#[test]
+fn every_char_round_trips_through_line_and_column() {
+    for column in [Column::Chars, Column::Utf16] {
+        for char_index in 0..=TEXT.chars().count() {
+            let position = char_to_line_col(TEXT, char_index, column);
+            assert_eq!(line_col_to_char(TEXT, position, column), char_index);
+        }
+    }
+}
+
+#[test]
+fn typed_line_and_column() {
+    assert_eq!(parse_line_col("12"), Some((11, 0)));
+    assert_eq!(parse_line_col(" 3:7 "), Some((2, 6)));
+    assert_eq!(parse_line_col("3:"), None);
+    assert_eq!(parse_line_col("0"), None);
+    assert_eq!(parse_line_col("a:1"), None);
+}
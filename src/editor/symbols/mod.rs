@@ -0,0 +1,148 @@
+//! Outline of a file: the functions, types and other items it defines, found from
+//! the tokens of the lexer, and the fuzzy filter used to pick one of them.
+
+#[cfg(test)]
+mod tests;
+
+use super::highlighting::Token;
+use super::{Indentation, Syntax, TokenType};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Symbol {
+    /// The keyword defining it, like `fn` or `class`
+    pub kind: String,
+    pub name: String,
+    /// Char index of the name
+    pub index: usize,
+    /// Zero based line of the name
+    pub line: usize,
+    /// Indentation levels of its line, for methods and nested items
+    pub depth: usize,
+}
+
+/// Keywords starting a definition, the name being the next word
+fn definers(language: &str) -> &'static [&'static str] {
+    match language {
+        "Rust" => &[
+            "fn",
+            "struct",
+            "enum",
+            "union",
+            "trait",
+            "impl",
+            "mod",
+            "type",
+            "const",
+            "static",
+            "macro_rules",
+        ],
+        "Python" => &["def", "class"],
+        "Javascript" => &["function", "class"],
+        "Lua" | "Shell" => &["function"],
+        "SQL" => &["TABLE", "VIEW", "FUNCTION", "PROCEDURE", "TRIGGER"],
+        _ => &["fn", "function", "def", "class", "struct"],
+    }
+}
+
+/// Words between a definer and the name, like `mut` in `static mut NAME`
+const SKIPPED: [&str; 3] = ["mut", "async", "!"];
+
+/// Definitions of `text`, in order. `impl` blocks are named after the rest of their line.
+pub fn symbols(syntax: &Syntax, text: &str) -> Vec<Symbol> {
+    let definers = definers(syntax.language());
+    let is_definer = |word: &str| {
+        definers.iter().any(|definer| match syntax.case_sensitive {
+            true => *definer == word,
+            false => definer.eq_ignore_ascii_case(word),
+        })
+    };
+    let indentation = Indentation::detect(text);
+    let lines: Vec<&str> = text.split('\n').collect();
+
+    let mut symbols = vec![];
+    let (mut index, mut line, mut column) = (0, 0, 0);
+    let mut kind: Option<String> = None;
+    for token in Token::default().tokens(syntax, text) {
+        let buffer = token.buffer();
+        let ty = token.ty();
+        let is_word = !matches!(
+            ty,
+            TokenType::Whitespace(_)
+                | TokenType::Comment(_)
+                | TokenType::Str(_)
+                | TokenType::Punctuation(_)
+        );
+        if let Some(defined) = kind.take() {
+            if matches!(ty, TokenType::Whitespace(' ' | '\t')) || SKIPPED.contains(&buffer) {
+                kind = Some(defined);
+            } else if is_word && !is_definer(buffer) {
+                let line_text = lines.get(line).copied().unwrap_or_default();
+                symbols.push(Symbol {
+                    name: buffer.to_string(),
+                    index,
+                    line,
+                    depth: indentation.depth(line_text),
+                    kind: defined,
+                });
+            }
+        }
+        if is_word && is_definer(buffer) {
+            let line_text = lines.get(line).copied().unwrap_or_default();
+            let before: String = line_text.chars().take(column).collect();
+            if buffer == "impl" {
+                // only at the start of a line, not `-> impl Trait`
+                let before = before.trim();
+                if before.is_empty() || before == "unsafe" {
+                    let rest: String = line_text.chars().skip(column + buffer.len()).collect();
+                    let name = rest.split(['{', ';']).next().unwrap_or_default().trim();
+                    symbols.push(Symbol {
+                        kind: buffer.to_string(),
+                        name: name.to_string(),
+                        index,
+                        line,
+                        depth: indentation.depth(line_text),
+                    });
+                }
+            } else {
+                kind = Some(buffer.to_string());
+            }
+        }
+        for c in buffer.chars() {
+            index += 1;
+            column += 1;
+            if c == '\n' {
+                line += 1;
+                column = 0;
+            }
+        }
+    }
+    symbols
+}
+
+/// Score of `candidate` holding the chars of `query` in order, case insensitive,
+/// higher for consecutive chars and chars starting a word; `None` if it does not
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<usize> {
+    let candidate: Vec<char> = candidate.chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+    for wanted in query.chars().filter(|c| !c.is_whitespace()) {
+        let found = candidate[position..]
+            .iter()
+            .position(|c| c.to_lowercase().eq(wanted.to_lowercase()))?
+            + position;
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == found) {
+            score += 3;
+        }
+        let word_start = found == 0
+            || !candidate[found - 1].is_alphanumeric()
+            || (candidate[found].is_uppercase() && candidate[found - 1].is_lowercase());
+        if word_start {
+            score += 2;
+        }
+        previous = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
@@ -0,0 +1,53 @@
+use super::*;
+
+fn names(symbols: &[Symbol]) -> Vec<(&str, &str, usize)> {
+    symbols
+        .iter()
+        .map(|symbol| (symbol.kind.as_str(), symbol.name.as_str(), symbol.line))
+        .collect()
+}
+
+#[test]
+fn rust_items() {
+    let text = "pub struct Point {\n    x: f32,\n}\n\nimpl<T> Display for Point {\n    pub const fn new() -> impl Iterator {\n        // fn hidden\n        let f: fn(u32) = g;\n    }\n}\nstatic mut COUNT: u32 = 0;\nmacro_rules! square {}";
+    let symbols = symbols(&Syntax::rust(), text);
+    assert_eq!(
+        names(&symbols),
+        [
+            ("struct", "Point", 0),
+            ("impl", "<T> Display for Point", 4),
+            ("fn", "new", 5),
+            ("static", "COUNT", 10),
+            ("macro_rules", "square", 11),
+        ]
+    );
+    assert_eq!(symbols[0].index, 11);
+    assert_eq!(symbols[2].depth, 1);
+}
+
+#[test]
+fn python_items() {
+    let text = "class Shape:\n    def area(self):\n        pass\n\ndef main():\n    pass";
+    let symbols = symbols(&Syntax::python(), text);
+    assert_eq!(
+        names(&symbols),
+        [
+            ("class", "Shape", 0),
+            ("def", "area", 1),
+            ("def", "main", 4)
+        ]
+    );
+}
+
+#[test]
+fn fuzzy_scores() {
+    assert_eq!(fuzzy_score("xyz", "new"), None);
+    assert!(fuzzy_score("", "new").is_some());
+    // consecutive chars and word starts rank higher
+    let exact = fuzzy_score("new", "new").unwrap();
+    let spread = fuzzy_score("new", "nodes_with").unwrap();
+    assert!(exact > spread);
+    let camel = fuzzy_score("gs", "getSymbols").unwrap();
+    let inner = fuzzy_score("gs", "flags").unwrap();
+    assert!(camel > inner);
+}
@@ -1,14 +1,12 @@
 use super::Syntax;
-use std::collections::BTreeSet;
 
 impl Syntax {
     pub fn asm() -> Self {
-        Syntax {
-            language: "Assembly",
-            case_sensitive: false,
-            comment: ";",
-            comment_multiline: ["/*", "*/"],
-            keywords: BTreeSet::from([
+        Syntax::new("Assembly")
+            .with_case_sensitive(false)
+            .with_comment(";")
+            .with_comment_multiline(["/*", "*/"])
+            .with_keywords([
                 "vaddpd",
                 "divsd",
                 "vrcp14ps",
@@ -1156,9 +1154,9 @@ impl Syntax {
                 "fcmovne",
                 "setno",
                 "vpermb",
-            ]),
-            types: BTreeSet::from(["ptr", "byte", "word", "dword", "qword"]),
-            special: BTreeSet::from([
+            ])
+            .with_types(["ptr", "byte", "word", "dword", "qword"])
+            .with_special([
                 "RAX", "RBX", "RCX", "RDX", "RSI", "RDI", "RBP", "RSP", "R8", "R9", "R10", "R11",
                 "R12", "R13", "R14", "R15", // 64-bit registers
                 "EAX", "EBX", "ECX", "EDX", "ESI", "EDI", "EBP", "ESP", "R8D", "R9D", "R10D",
@@ -1175,7 +1173,6 @@ impl Syntax {
                 "ZMM0", "ZMM1", "ZMM2", "ZMM3", "ZMM4", "ZMM5", "ZMM6", "ZMM7", "ZMM8", "ZMM9",
                 "ZMM10", "ZMM11", "ZMM12", "ZMM13", "ZMM14", "ZMM15",
                 // ZMM
-            ]),
-        }
+            ])
     }
 }
@@ -1,14 +1,12 @@
 use super::Syntax;
-use std::collections::BTreeSet;
 
 impl Syntax {
     pub fn javascript() -> Syntax {
-        Syntax {
-            language: "Javascript",
-            case_sensitive: true,
-            comment: "//",
-            comment_multiline: ["/*", "*/"],
-            keywords: BTreeSet::from([
+        Syntax::new("Javascript")
+            .with_case_sensitive(true)
+            .with_comment("//")
+            .with_comment_multiline(["/*", "*/"])
+            .with_keywords([
                 "&&",
                 "||",
                 "!",
@@ -66,8 +64,8 @@ impl Syntax {
                 "while",
                 "with",
                 "yield",
-            ]),
-            types: BTreeSet::from([
+            ])
+            .with_types([
                 "Boolean",
                 "Number",
                 "BigInt",
@@ -82,8 +80,7 @@ impl Syntax {
                 "long",
                 "short",
                 "void",
-            ]),
-            special: BTreeSet::from(["false", "null", "true"]),
-        }
+            ])
+            .with_special(["false", "null", "true"])
     }
 }
@@ -1,21 +1,18 @@
 use super::Syntax;
-use std::collections::BTreeSet;
 
 impl Syntax {
     pub fn lua() -> Syntax {
-        Syntax {
-            language: "Lua",
-            case_sensitive: true,
-            comment: "--",
-            comment_multiline: ["--[[", "]]"],
-            keywords: BTreeSet::from([
+        Syntax::new("Lua")
+            .with_case_sensitive(true)
+            .with_comment("--")
+            .with_comment_multiline(["--[[", "]]"])
+            .with_keywords([
                 "and", "break", "do", "else", "elseif", "end", "for", "function", "if", "in",
                 "local", "not", "or", "repeat", "return", "then", "until", "while",
-            ]),
-            types: BTreeSet::from([
+            ])
+            .with_types([
                 "boolean", "number", "string", "function", "userdata", "thread", "table",
-            ]),
-            special: BTreeSet::from(["false", "nil", "true"]),
-        }
+            ])
+            .with_special(["false", "nil", "true"])
     }
 }
@@ -7,6 +7,7 @@ pub mod rust;
 pub mod shell;
 pub mod sql;
 pub mod pendragon;
+pub mod text;
 
 use std::collections::BTreeSet;
 use std::hash::{Hash, Hasher};
@@ -95,13 +96,13 @@ impl From<char> for TokenType {
 #[derive(Clone, Debug, PartialEq)]
 /// Rules for highlighting.
 pub struct Syntax {
-    pub language: &'static str,
+    pub language: String,
     pub case_sensitive: bool,
-    pub comment: &'static str,
-    pub comment_multiline: [&'static str; 2],
-    pub keywords: BTreeSet<&'static str>,
-    pub types: BTreeSet<&'static str>,
-    pub special: BTreeSet<&'static str>,
+    pub comment: String,
+    pub comment_multiline: [String; 2],
+    pub keywords: BTreeSet<String>,
+    pub types: BTreeSet<String>,
+    pub special: BTreeSet<String>,
 }
 impl Default for Syntax {
     fn default() -> Self {
@@ -114,10 +115,16 @@ impl Hash for Syntax {
     }
 }
 impl Syntax {
-    pub fn new(language: &'static str) -> Self {
+    /// A language without comments or words to highlight
+    pub fn new(language: &str) -> Self {
         Syntax {
-            language,
-            ..Default::default()
+            language: language.into(),
+            case_sensitive: false,
+            comment: String::new(),
+            comment_multiline: [String::new(), String::new()],
+            keywords: BTreeSet::new(),
+            types: BTreeSet::new(),
+            special: BTreeSet::new(),
         }
     }
     pub fn with_case_sensitive(self, case_sensitive: bool) -> Self {
@@ -126,57 +133,72 @@ impl Syntax {
             ..self
         }
     }
-    pub fn with_comment(self, comment: &'static str) -> Self {
-        Syntax { comment, ..self }
+    pub fn with_comment(self, comment: &str) -> Self {
+        Syntax {
+            comment: comment.into(),
+            ..self
+        }
     }
-    pub fn with_comment_multiline(self, comment_multiline: [&'static str; 2]) -> Self {
+    pub fn with_comment_multiline(self, [start, end]: [&str; 2]) -> Self {
         Syntax {
-            comment_multiline,
+            comment_multiline: [start.into(), end.into()],
             ..self
         }
     }
-    pub fn with_keywords<T: Into<BTreeSet<&'static str>>>(self, keywords: T) -> Self {
+    pub fn with_keywords<T: IntoIterator<Item = S>, S: Into<String>>(self, keywords: T) -> Self {
         Syntax {
-            keywords: keywords.into(),
+            keywords: keywords.into_iter().map(Into::into).collect(),
             ..self
         }
     }
-    pub fn with_types<T: Into<BTreeSet<&'static str>>>(self, types: T) -> Self {
+    pub fn with_types<T: IntoIterator<Item = S>, S: Into<String>>(self, types: T) -> Self {
         Syntax {
-            types: types.into(),
+            types: types.into_iter().map(Into::into).collect(),
             ..self
         }
     }
-    pub fn with_special<T: Into<BTreeSet<&'static str>>>(self, special: T) -> Self {
+    pub fn with_special<T: IntoIterator<Item = S>, S: Into<String>>(self, special: T) -> Self {
         Syntax {
-            special: special.into(),
+            special: special.into_iter().map(Into::into).collect(),
             ..self
         }
     }
 
     pub fn language(&self) -> &str {
-        self.language
+        &self.language
     }
     pub fn comment(&self) -> &str {
-        self.comment
+        &self.comment
+    }
+    /// Start and end of a block comment
+    pub fn comment_multiline(&self) -> [&str; 2] {
+        [&self.comment_multiline[0], &self.comment_multiline[1]]
+    }
+    /// An empty comment marker means the language has no comments
+    pub fn starts_comment(&self, buffer: &str) -> bool {
+        !self.comment.is_empty() && buffer.starts_with(self.comment.as_str())
+    }
+    pub fn starts_comment_multiline(&self, buffer: &str) -> bool {
+        !self.comment_multiline[0].is_empty()
+            && buffer.starts_with(self.comment_multiline[0].as_str())
     }
     pub fn is_keyword(&self, word: &str) -> bool {
         if self.case_sensitive {
-            self.keywords.contains(&word)
+            self.keywords.contains(word)
         } else {
             self.keywords.contains(word.to_ascii_uppercase().as_str())
         }
     }
     pub fn is_type(&self, word: &str) -> bool {
         if self.case_sensitive {
-            self.types.contains(&word)
+            self.types.contains(word)
         } else {
             self.types.contains(word.to_ascii_uppercase().as_str())
         }
     }
     pub fn is_special(&self, word: &str) -> bool {
         if self.case_sensitive {
-            self.special.contains(&word)
+            self.special.contains(word)
         } else {
             self.special.contains(word.to_ascii_uppercase().as_str())
         }
@@ -184,15 +206,9 @@ impl Syntax {
 }
 
 impl Syntax {
-    pub fn simple(comment: &'static str) -> Self {
-        Syntax {
-            language: "",
-            case_sensitive: false,
-            comment,
-            comment_multiline: [comment; 2],
-            keywords: BTreeSet::new(),
-            types: BTreeSet::new(),
-            special: BTreeSet::new(),
-        }
+    pub fn simple(comment: &str) -> Self {
+        Syntax::new("")
+            .with_comment(comment)
+            .with_comment_multiline([comment; 2])
     }
 }
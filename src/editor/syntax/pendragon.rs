@@ -1,27 +1,24 @@
 use super::Syntax;
-use std::collections::BTreeSet;
 
 impl Syntax {
 	pub fn pendragon() -> Self {
-		Syntax {
-			language: "Pendragon",
-			case_sensitive: true,
-			comment: "Nota",
-			comment_multiline: ["/*", "*/"],
-			keywords: BTreeSet::from([ // rouge
+		Syntax::new("Pendragon")
+			.with_case_sensitive(true)
+			.with_comment("Nota")
+			.with_comment_multiline(["/*", "*/"])
+			.with_keywords([ // rouge
 				"Définis", "Modifie", "Tant", 
 				"que", "Affiche", "Si", "Demande"
-			]),
-			types: BTreeSet::from([ //bleu
+			])
+			.with_types([ //bleu
 				"entier", "booleen", "texte"
-			]),
-			special: BTreeSet::from([ //orange
+			])
+			.with_special([ //orange
 				"et", "ou", "puis", 
 				"plus", "moins", "fois", 
 				"divisé", "par", "ouvre", 
 				"la", "parenthèse", "ferme", 
 				"non", "est", "égal", "supérieur", "inférieur", "à"
-			]),
-		}
+			])
 	}
 }
@@ -1,20 +1,18 @@
 use super::Syntax;
-use std::collections::BTreeSet;
 
 impl Syntax {
     pub fn python() -> Syntax {
-        Syntax {
-            language: "Python",
-            case_sensitive: true,
-            comment: "#",
-            comment_multiline: [r#"'''"#, r#"'''"#],
-            keywords: BTreeSet::from([
+        Syntax::new("Python")
+            .with_case_sensitive(true)
+            .with_comment("#")
+            .with_comment_multiline([r#"'''"#, r#"'''"#])
+            .with_keywords([
                 "and", "as", "assert", "break", "class", "continue", "def", "del", "elif", "else",
                 "except", "finally", "for", "from", "global", "if", "import", "in", "is", "lambda",
                 "nonlocal", "not", "or", "pass", "raise", "return", "try", "while", "with",
                 "yield",
-            ]),
-            types: BTreeSet::from([
+            ])
+            .with_types([
                 "bool",
                 "int",
                 "float",
@@ -29,8 +27,7 @@ impl Syntax {
                 "dict",
                 "set",
                 "frozenset",
-            ]),
-            special: BTreeSet::from(["False", "None", "True"]),
-        }
+            ])
+            .with_special(["False", "None", "True"])
     }
 }
@@ -1,21 +1,19 @@
 use super::Syntax;
-use std::collections::BTreeSet;
 
 impl Syntax {
     pub fn rust() -> Self {
-        Syntax {
-            language: "Rust",
-            case_sensitive: true,
-            comment: "//",
-            comment_multiline: ["/*", "*/"],
-            keywords: BTreeSet::from([
+        Syntax::new("Rust")
+            .with_case_sensitive(true)
+            .with_comment("//")
+            .with_comment_multiline(["/*", "*/"])
+            .with_keywords([
                 "as", "break", "const", "continue", "crate", "else", "enum", "extern", "fn", "for",
                 "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
                 "return", "self", "struct", "super", "trait", "type", "use", "where", "while",
                 "async", "await", "abstract", "become", "box", "do", "final", "macro", "override",
                 "priv", "typeof", "unsized", "virtual", "yield", "try", "unsafe", "dyn",
-            ]),
-            types: BTreeSet::from([
+            ])
+            .with_types([
                 "Option",
                 "Result",
                 "Error",
@@ -80,8 +78,7 @@ impl Syntax {
                 "RwLockWriteGuard",
                 "WaitTimeoutResult",
                 "Weak",
-            ]),
-            special: BTreeSet::from(["Self", "static", "true", "false"]),
-        }
+            ])
+            .with_special(["Self", "static", "true", "false"])
     }
 }
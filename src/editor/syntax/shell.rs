@@ -1,19 +1,17 @@
 use super::Syntax;
-use std::collections::BTreeSet;
 
 impl Syntax {
     pub fn shell() -> Self {
-        Syntax {
-            language: "Shell",
-            case_sensitive: true,
-            comment: "#",
-            keywords: BTreeSet::from([
+        Syntax::new("Shell")
+            .with_case_sensitive(true)
+            .with_comment("#")
+            .with_keywords([
                 "echo", "read", "set", "unset", "readonly", "shift", "export", "if", "fi", "else",
                 "while", "do", "done", "for", "until", "case", "esac", "break", "continue", "exit",
                 "return", "trap", "wait", "eval", "exec", "ulimit", "umask",
-            ]),
-            comment_multiline: [": '", "'"],
-            types: BTreeSet::from([
+            ])
+            .with_comment_multiline([": '", "'"])
+            .with_types([
                 "ENV",
                 "HOME",
                 "IFS",
@@ -30,11 +28,10 @@ impl Syntax {
                 "PS2",
                 "PS4",
                 "PWD",
-            ]),
-            special: BTreeSet::from([
+            ])
+            .with_special([
                 "alias", "bg", "cd", "command", "false", "fc", "fg", "getopts", "jobs", "kill",
                 "newgrp", "pwd", "read", "true", "umask", "unalias", "wait",
-            ]),
-        }
+            ])
     }
 }
@@ -1,14 +1,12 @@
 use super::Syntax;
-use std::collections::BTreeSet;
 
 impl Syntax {
     pub fn sql() -> Self {
-        Syntax {
-            language: "SQL",
-            case_sensitive: false,
-            comment: "--",
-            comment_multiline: ["/*", "*/"],
-            keywords: BTreeSet::from([
+        Syntax::new("SQL")
+            .with_case_sensitive(false)
+            .with_comment("--")
+            .with_comment_multiline(["/*", "*/"])
+            .with_keywords([
                 "ADD",
                 "ALL",
                 "ALTER",
@@ -69,8 +67,8 @@ impl Syntax {
                 "VALUES",
                 "WHERE",
                 "WITH",
-            ]),
-            types: BTreeSet::from([
+            ])
+            .with_types([
                 "BOOL",
                 "INTEGER",
                 "SMALLINT",
@@ -130,8 +128,7 @@ impl Syntax {
                 "TABLE",
                 "SET",
                 "DATABASE",
-            ]),
-            special: BTreeSet::from(["PUBLIC"]),
-        }
+            ])
+            .with_special(["PUBLIC"])
     }
 }
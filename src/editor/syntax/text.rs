@@ -0,0 +1,7 @@
+use super::Syntax;
+
+impl Syntax {
+    pub fn text() -> Self {
+        Syntax::new("Text").with_case_sensitive(true)
+    }
+}
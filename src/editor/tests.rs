@@ -1,3 +1,4 @@
+use super::highlighting::Token;
 use super::*;
 
 #[test]
@@ -63,3 +64,31 @@ fn simple_rust() {
     println!("{str}");
     assert_eq!(input, output);
 }
+
+#[test]
+fn new_line_indents_an_opened_block() {
+    let editor = CodeEditor::default().with_indentation(Indentation::Spaces(4));
+    let cursor = CCursorRange::one(CCursor::new(12));
+    // the text edit already broke the line between the braces
+    let (text, extend) = editor.new_line(cursor, "    fn a() {\n}".into());
+    assert_eq!(text, "    fn a() {\n        \n    }");
+    assert_eq!(extend, 9);
+
+    let (text, _) = editor.new_line(cursor, "    fn a() {\nb".into());
+    assert_eq!(text, "    fn a() {\n        b");
+}
+
+#[test]
+fn insert_indent_moves_the_cursor_by_chars() {
+    let cursor = CCursorRange::one(CCursor::new(2));
+    let editor = CodeEditor::default().with_indentation(Indentation::Spaces(2));
+    assert_eq!(
+        editor.insert_indent(cursor, "éé".into()),
+        ("éé  ".into(), 2)
+    );
+    let editor = editor.with_indentation(Indentation::Tabs);
+    assert_eq!(
+        editor.insert_indent(cursor, "éé".into()),
+        ("éé\t".into(), 1)
+    );
+}
@@ -3,37 +3,41 @@ use super::ColorTheme;
 impl ColorTheme {
     ///  Original Author: sainnhe <https://github.com/sainnhe/sonokai>
     ///  Modified by p4ymak <https://github.com/p4ymak>
-    pub const FIRE: ColorTheme = ColorTheme {
-        name: "Fire",
-        dark: true,
-        bg: "#242424",
-        cursor: "#dadada",      // foreground
-        selection: "#444852",   // dunno
-        comments: "#656565",    // dark_gray
-        functions: "#ffad69",   // light orange
-        keywords: "#48b1a7",    // mid green
-        literals: "#d2d2d3",    //
-        numerics: "#ff7b4f",    // orange
-        punctuation: "#989898", // gray
-        strs: "#cbd5a1",        // light_green
-        types: "#038e83",       // dark_green
-        special: "#48b1a7",     // mid green
-    };
+    pub fn fire() -> ColorTheme {
+        ColorTheme {
+            name: "Fire".into(),
+            dark: true,
+            bg: "#242424".into(),
+            cursor: "#dadada".into(),      // foreground
+            selection: "#444852".into(),   // dunno
+            comments: "#656565".into(),    // dark_gray
+            functions: "#ffad69".into(),   // light orange
+            keywords: "#48b1a7".into(),    // mid green
+            literals: "#d2d2d3".into(),    //
+            numerics: "#ff7b4f".into(),    // orange
+            punctuation: "#989898".into(), // gray
+            strs: "#cbd5a1".into(),        // light_green
+            types: "#038e83".into(),       // dark_green
+            special: "#48b1a7".into(),     // mid green
+        }
+    }
 
-    pub const ASH: ColorTheme = ColorTheme {
-        name: "Ash",
-        dark: true,
-        bg: "#101010",
-        cursor: "#eaeaea",
-        selection: "#505050",
-        comments: "#656565",
-        functions: "#a0a0a0",
-        keywords: "#848484",
-        literals: "#d2d2d2",
-        numerics: "#d2d2d2",
-        punctuation: "#848484",
-        strs: "#a0a0a0",
-        types: "#c6c6c6",
-        special: "#848484",
-    };
+    pub fn ash() -> ColorTheme {
+        ColorTheme {
+            name: "Ash".into(),
+            dark: true,
+            bg: "#101010".into(),
+            cursor: "#eaeaea".into(),
+            selection: "#505050".into(),
+            comments: "#656565".into(),
+            functions: "#a0a0a0".into(),
+            keywords: "#848484".into(),
+            literals: "#d2d2d2".into(),
+            numerics: "#d2d2d2".into(),
+            punctuation: "#848484".into(),
+            strs: "#a0a0a0".into(),
+            types: "#c6c6c6".into(),
+            special: "#848484".into(),
+        }
+    }
 }
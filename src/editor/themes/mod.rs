@@ -4,63 +4,144 @@ pub mod fantasy;
 pub mod github;
 pub mod gruvbox;
 pub mod sonokai;
+pub mod vscode;
+
+#[cfg(test)]
+mod tests;
 
 use super::syntax::TokenType;
 
 use eframe::egui;
 use egui::Color32;
+use serde::Deserialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Built-in themes, in the order of the settings menu.
+pub fn default_themes() -> Vec<ColorTheme> {
+    vec![
+        ColorTheme::ayu_mirage(),
+        ColorTheme::ayu_dark(),
+        ColorTheme::github_dark(),
+        ColorTheme::gruvbox(),
+        ColorTheme::sonokai(),
+        ColorTheme::fire(),
+        ColorTheme::ash(),
+    ]
+}
 
-pub const ERROR_COLOR: Color32 = Color32::from_rgb(255, 0, 255);
+/// Built-in themes followed by the .toml and .json files of a directory,
+/// json files can also be VS Code themes.
+/// A file using the name of a built-in theme replaces it.
+pub fn load_themes(dir: &Path) -> Vec<ColorTheme> {
+    let mut themes = default_themes();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return themes;
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .collect();
+    paths.sort();
+    for path in paths {
+        match read_theme(&path) {
+            Ok(Some(theme)) => {
+                themes.retain(|known| known.name != theme.name);
+                themes.push(theme);
+            }
+            Ok(None) => (),
+            Err(err) => eprintln!("Error loading theme {}: {}", path.display(), err),
+        }
+    }
+    themes
+}
 
-/// Array of default themes.
-pub const DEFAULT_THEMES: [ColorTheme; 7] = [
-    ColorTheme::AYU_MIRAGE,
-    ColorTheme::AYU_DARK,
-    ColorTheme::GITHUB_DARK,
-    ColorTheme::GRUVBOX,
-    ColorTheme::SONOKAI,
-    ColorTheme::FIRE,
-    ColorTheme::ASH,
-];
+/// None when the file is not a theme file.
+fn read_theme(path: &Path) -> Result<Option<ColorTheme>, std::io::Error> {
+    let extension = path.extension().and_then(|extension| extension.to_str());
+    if !matches!(extension, Some("toml") | Some("json")) {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path)?;
+    let mut theme: ColorTheme = if extension == Some("toml") {
+        toml::from_str(&content).map_err(std::io::Error::other)?
+    } else {
+        let value: serde_json::Value =
+            serde_json::from_str(&vscode::strip_jsonc(&content)).map_err(std::io::Error::other)?;
+        if ColorTheme::is_vscode(&value) {
+            ColorTheme::from_vscode(&value)
+        } else {
+            serde_json::from_value(value).map_err(std::io::Error::other)?
+        }
+    };
+    if theme.name.is_empty() {
+        theme.name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+    }
+    for (slot, hex) in theme.colors() {
+        if color_from_hex(hex).is_none() {
+            eprintln!("Invalid color {} for {} in theme {}", hex, slot, theme.name);
+        }
+    }
+    Ok(Some(theme))
+}
 
-fn color_from_hex(hex: &str) -> Option<Color32> {
+/// Accepts "#rgb", "#rrggbb" and "#rrggbbaa", the # being optional.
+pub fn color_from_hex(hex: &str) -> Option<Color32> {
     if hex == "none" {
         return Some(Color32::from_rgba_premultiplied(255, 0, 255, 0));
     }
-    let rgb = (1..hex.len())
-        .step_by(2)
-        .filter_map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
-        .collect::<Vec<u8>>();
-    let color = Color32::from_rgb(*rgb.first()?, *rgb.get(1)?, *rgb.get(2)?);
-    Some(color)
+    let hex = hex.trim().trim_start_matches('#');
+    let channels: Vec<u8> = match hex.len() {
+        3 => hex
+            .chars()
+            .map(|c| Some(c.to_digit(16)? as u8 * 17))
+            .collect::<Option<_>>()?,
+        6 | 8 => (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+            .collect::<Option<_>>()?,
+        _ => return None,
+    };
+    match channels[..] {
+        [r, g, b] => Some(Color32::from_rgb(r, g, b)),
+        [r, g, b, a] => Some(Color32::from_rgba_unmultiplied(r, g, b, a)),
+        _ => None,
+    }
 }
 
-#[derive(Hash, Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Hash, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(default)]
 /// Colors in hexadecimal notation as used in HTML and CSS.
+/// Fields missing from a theme file are taken from the default theme.
 pub struct ColorTheme {
-    pub name: &'static str,
+    #[serde(default)]
+    pub name: String,
     pub dark: bool,
-    pub bg: &'static str,
-    pub cursor: &'static str,
-    pub selection: &'static str,
-    pub comments: &'static str,
-    pub functions: &'static str,
-    pub keywords: &'static str,
-    pub literals: &'static str,
-    pub numerics: &'static str,
-    pub punctuation: &'static str,
-    pub strs: &'static str,
-    pub types: &'static str,
-    pub special: &'static str,
+    pub bg: String,
+    pub cursor: String,
+    pub selection: String,
+    pub comments: String,
+    pub functions: String,
+    pub keywords: String,
+    pub literals: String,
+    pub numerics: String,
+    pub punctuation: String,
+    pub strs: String,
+    pub types: String,
+    pub special: String,
 }
 impl Default for ColorTheme {
     fn default() -> Self {
-        ColorTheme::GRUVBOX
+        ColorTheme::gruvbox()
     }
 }
 impl ColorTheme {
     pub fn name(&self) -> &str {
-        self.name
+        &self.name
     }
 
     pub fn is_dark(&self) -> bool {
@@ -68,15 +149,20 @@ impl ColorTheme {
     }
 
     pub fn bg(&self) -> Color32 {
-        color_from_hex(self.bg).unwrap_or(ERROR_COLOR)
+        color_from_hex(&self.bg).unwrap_or_else(|| self.fallback_color(true))
     }
 
     pub fn cursor(&self) -> Color32 {
-        color_from_hex(self.cursor).unwrap_or(ERROR_COLOR)
+        self.color(&self.cursor)
     }
 
     pub fn selection(&self) -> Color32 {
-        color_from_hex(self.selection).unwrap_or(ERROR_COLOR)
+        self.color(&self.selection)
+    }
+
+    /// One of the foreground colors of the theme, a malformed one is replaced by a neutral color
+    pub fn color(&self, hex: &str) -> Color32 {
+        color_from_hex(hex).unwrap_or_else(|| self.fallback_color(false))
     }
 
     pub fn modify_style(&self, ui: &mut egui::Ui, fontsize: f32) {
@@ -90,59 +176,68 @@ impl ColorTheme {
         style.visuals.text_cursor.width = fontsize * 0.1;
     }
 
-    pub const fn type_color_str(&self, ty: TokenType) -> &'static str {
+    pub fn type_color_str(&self, ty: TokenType) -> &str {
         match ty {
-            TokenType::Comment(_) => self.comments,
-            TokenType::Function => self.functions,
-            TokenType::Keyword => self.keywords,
-            TokenType::Literal => self.literals,
-            TokenType::Numeric(_) => self.numerics,
-            TokenType::Punctuation(_) => self.punctuation,
-            TokenType::Special => self.special,
-            TokenType::Str(_) => self.strs,
-            TokenType::Type => self.types,
-            TokenType::Whitespace(_) | TokenType::Unknown => self.comments,
+            TokenType::Comment(_) => &self.comments,
+            TokenType::Function => &self.functions,
+            TokenType::Keyword => &self.keywords,
+            TokenType::Literal => &self.literals,
+            TokenType::Numeric(_) => &self.numerics,
+            TokenType::Punctuation(_) => &self.punctuation,
+            TokenType::Special => &self.special,
+            TokenType::Str(_) => &self.strs,
+            TokenType::Type => &self.types,
+            TokenType::Whitespace(_) | TokenType::Unknown => &self.comments,
         }
     }
 
     pub fn type_color(&self, ty: TokenType) -> Color32 {
-        match ty {
-            TokenType::Comment(_) => color_from_hex(self.comments),
-            TokenType::Function => color_from_hex(self.functions),
-            TokenType::Keyword => color_from_hex(self.keywords),
-            TokenType::Literal => color_from_hex(self.literals),
-            TokenType::Numeric(_) => color_from_hex(self.numerics),
-            TokenType::Punctuation(_) => color_from_hex(self.punctuation),
-            TokenType::Special => color_from_hex(self.special),
-            TokenType::Str(_) => color_from_hex(self.strs),
-            TokenType::Type => color_from_hex(self.types),
-            TokenType::Whitespace(_) | TokenType::Unknown => color_from_hex(self.comments),
+        self.color(self.type_color_str(ty))
+    }
+
+    /// Neutral color used in place of a malformed one
+    fn fallback_color(&self, background: bool) -> Color32 {
+        match (self.dark, background) {
+            (true, true) => Color32::from_gray(32),
+            (true, false) => Color32::from_gray(200),
+            (false, true) => Color32::from_gray(240),
+            (false, false) => Color32::from_gray(60),
         }
-        .unwrap_or(ERROR_COLOR)
     }
 
-    pub fn monocolor(
-        dark: bool,
-        bg: &'static str,
-        fg: &'static str,
-        cursor: &'static str,
-        selection: &'static str,
-    ) -> Self {
+    fn colors(&self) -> [(&str, &str); 12] {
+        [
+            ("bg", &self.bg),
+            ("cursor", &self.cursor),
+            ("selection", &self.selection),
+            ("comments", &self.comments),
+            ("functions", &self.functions),
+            ("keywords", &self.keywords),
+            ("literals", &self.literals),
+            ("numerics", &self.numerics),
+            ("punctuation", &self.punctuation),
+            ("strs", &self.strs),
+            ("types", &self.types),
+            ("special", &self.special),
+        ]
+    }
+
+    pub fn monocolor(dark: bool, bg: &str, fg: &str, cursor: &str, selection: &str) -> Self {
         ColorTheme {
-            name: "monocolor",
+            name: "monocolor".into(),
             dark,
-            bg,
-            cursor,
-            selection,
-            literals: fg,
-            numerics: fg,
-            keywords: fg,
-            functions: fg,
-            punctuation: fg,
-            types: fg,
-            strs: fg,
-            comments: fg,
-            special: fg,
+            bg: bg.into(),
+            cursor: cursor.into(),
+            selection: selection.into(),
+            literals: fg.into(),
+            numerics: fg.into(),
+            keywords: fg.into(),
+            functions: fg.into(),
+            punctuation: fg.into(),
+            types: fg.into(),
+            strs: fg.into(),
+            comments: fg.into(),
+            special: fg.into(),
         }
     }
 }
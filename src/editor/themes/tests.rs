@@ -0,0 +1,82 @@
+use super::*;
+use std::env;
+
+#[test]
+fn hex_colors() {
+    assert_eq!(color_from_hex("#fa0"), Some(Color32::from_rgb(255, 170, 0)));
+    assert_eq!(
+        color_from_hex("FFAA00"),
+        Some(Color32::from_rgb(255, 170, 0))
+    );
+    assert_eq!(
+        color_from_hex(" #ffaa0080 "),
+        Some(Color32::from_rgba_unmultiplied(255, 170, 0, 128))
+    );
+    assert_eq!(color_from_hex("none").map(|color| color.a()), Some(0));
+}
+
+#[test]
+fn malformed_hex_colors() {
+    for hex in [
+        "", "#", "#12", "#1234", "#12345", "#ggg", "#12345z", "éé", "ééé", "#fa0fa0f",
+    ] {
+        assert_eq!(color_from_hex(hex), None, "{}", hex);
+    }
+}
+
+#[test]
+fn malformed_colors_fall_back_to_neutral_ones() {
+    let mut theme = ColorTheme::gruvbox();
+    theme.bg = "#zzzzzz".into();
+    theme.keywords = "red".into();
+    assert!(theme.dark);
+    assert_eq!(theme.bg(), Color32::from_gray(32));
+    assert_eq!(
+        theme.type_color(TokenType::Keyword),
+        Color32::from_gray(200)
+    );
+    assert_eq!(
+        theme.color(&theme.functions),
+        color_from_hex(&theme.functions).unwrap()
+    );
+
+    theme.dark = false;
+    assert_eq!(theme.bg(), Color32::from_gray(240));
+    assert_eq!(theme.color(&theme.keywords), Color32::from_gray(60));
+
+    // a foreground slot stays one even with the value of the background
+    theme.comments = theme.bg.clone();
+    assert_eq!(theme.color(&theme.comments), Color32::from_gray(60));
+}
+
+#[test]
+fn theme_files_of_a_directory() {
+    let dir = env::temp_dir().join(format!("calcifer_themes_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    // replaces the built-in theme of the same name, missing colors come from the default
+    fs::write(dir.join("a.toml"), "name = \"Gruvbox\"\nbg = \"#000000\"\n").unwrap();
+    fs::write(
+        dir.join("b.json"),
+        r##"{"dark": false, "keywords": "#123"}"##,
+    )
+    .unwrap();
+    fs::write(dir.join("c.toml"), "bg = [").unwrap();
+    fs::write(dir.join("notes.txt"), "not a theme").unwrap();
+
+    let themes = load_themes(&dir);
+    let names: Vec<&str> = themes.iter().map(|theme| theme.name()).collect();
+    assert_eq!(names.len(), default_themes().len() + 1);
+    assert_eq!(names.iter().filter(|name| **name == "Gruvbox").count(), 1);
+    assert_eq!(names[names.len() - 2..], ["Gruvbox", "b"]);
+
+    let replaced = &themes[themes.len() - 2];
+    assert_eq!(replaced.bg, "#000000");
+    assert_eq!(replaced.keywords, ColorTheme::gruvbox().keywords);
+    let named_after_file = &themes[themes.len() - 1];
+    assert!(!named_after_file.dark);
+    assert_eq!(named_after_file.keywords, "#123");
+
+    assert!(read_theme(&dir.join("c.toml")).is_err());
+    assert!(read_theme(&dir.join("notes.txt")).unwrap().is_none());
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,173 @@
+#[cfg(test)]
+mod tests;
+
+use serde_json::Value;
+
+use super::{color_from_hex, ColorTheme};
+
+impl ColorTheme {
+    /// Whether a json theme file was written for VS Code
+    pub fn is_vscode(theme: &Value) -> bool {
+        theme.get("tokenColors").is_some() || theme.get("colors").is_some()
+    }
+
+    /// Map the `colors` and `tokenColors` of a VS Code theme onto our slots,
+    /// anything the theme does not define comes from its foreground or the default theme
+    pub fn from_vscode(theme: &Value) -> ColorTheme {
+        let colors = &theme["colors"];
+        let color = |key: &str| -> Option<String> {
+            let hex = colors.get(key)?.as_str()?;
+            color_from_hex(hex).map(|_| hex.to_string())
+        };
+        let rules = token_rules(&theme["tokenColors"]);
+        let default = ColorTheme::default();
+
+        let bg = color("editor.background").unwrap_or(default.bg.clone());
+        let foreground = color("editor.foreground").or_else(|| rule_color(&rules, "source"));
+        let dark = match theme["type"].as_str() {
+            Some(kind) => kind != "light" && kind != "hcLight",
+            None => color_from_hex(&bg).map_or(default.dark, |bg| {
+                (bg.r() as u32 + bg.g() as u32 + bg.b() as u32) < 3 * 128
+            }),
+        };
+
+        // the first scope with a matching rule wins
+        let slot = |scopes: &[&str], default: &String| -> String {
+            scopes
+                .iter()
+                .find_map(|scope| rule_color(&rules, scope))
+                .or(foreground.clone())
+                .unwrap_or(default.clone())
+        };
+
+        ColorTheme {
+            name: theme["name"].as_str().unwrap_or_default().to_string(),
+            dark,
+            bg,
+            cursor: color("editorCursor.foreground")
+                .or(foreground.clone())
+                .unwrap_or(default.cursor.clone()),
+            selection: color("editor.selectionBackground").unwrap_or(default.selection.clone()),
+            comments: slot(&["comment"], &default.comments),
+            functions: slot(
+                &[
+                    "entity.name.function",
+                    "support.function",
+                    "meta.function-call",
+                ],
+                &default.functions,
+            ),
+            keywords: slot(
+                &["keyword.control", "keyword", "storage.modifier"],
+                &default.keywords,
+            ),
+            literals: slot(&["variable.other", "variable"], &default.literals),
+            numerics: slot(&["constant.numeric", "constant"], &default.numerics),
+            punctuation: slot(&["punctuation", "keyword.operator"], &default.punctuation),
+            strs: slot(&["string"], &default.strs),
+            types: slot(
+                &[
+                    "entity.name.type",
+                    "support.type",
+                    "storage.type",
+                    "entity.name.class",
+                ],
+                &default.types,
+            ),
+            special: slot(
+                &["constant.language", "support.constant", "variable.language"],
+                &default.special,
+            ),
+        }
+    }
+}
+
+/// Every (scope, foreground) pair of the `tokenColors` list, in order
+fn token_rules(token_colors: &Value) -> Vec<(String, String)> {
+    let mut rules = vec![];
+    for rule in token_colors.as_array().into_iter().flatten() {
+        let Some(foreground) = rule["settings"]["foreground"].as_str() else {
+            continue;
+        };
+        if color_from_hex(foreground).is_none() {
+            continue;
+        }
+        let scopes: Vec<&str> = match &rule["scope"] {
+            Value::String(scopes) => scopes.split(',').collect(),
+            Value::Array(scopes) => scopes.iter().filter_map(|scope| scope.as_str()).collect(),
+            _ => vec![],
+        };
+        for scope in scopes {
+            rules.push((scope.trim().to_string(), foreground.to_string()));
+        }
+    }
+    rules
+}
+
+/// Color of the most specific rule applying to a scope, "keyword" applies to "keyword.control"
+/// but "keyword.control" does not apply to "keyword". Selectors with several parts are skipped.
+fn rule_color(rules: &[(String, String)], scope: &str) -> Option<String> {
+    rules
+        .iter()
+        .filter(|(selector, _)| !selector.contains(' '))
+        .filter(|(selector, _)| {
+            scope == selector
+                || scope
+                    .strip_prefix(selector.as_str())
+                    .is_some_and(|rest| rest.starts_with('.'))
+        })
+        .max_by_key(|(selector, _)| selector.len())
+        .map(|(_, foreground)| foreground.clone())
+}
+
+/// VS Code theme files are jsonc, remove comments and trailing commas so serde_json reads them
+pub fn strip_jsonc(content: &str) -> String {
+    let without_comments = strip_outside_strings(content, |c, rest, stripped| match c {
+        '/' if rest.starts_with('/') => rest.find('\n').unwrap_or(rest.len()),
+        '/' if rest.starts_with('*') => rest[1..].find("*/").map_or(rest.len(), |end| end + 3),
+        _ => {
+            stripped.push(c);
+            0
+        }
+    });
+    strip_outside_strings(&without_comments, |c, rest, stripped| {
+        let closing = rest.trim_start().starts_with([']', '}']);
+        if c != ',' || !closing {
+            stripped.push(c);
+        }
+        0
+    })
+}
+
+/// Calls `handle` on every char outside of string literals with the text following it,
+/// `handle` returns how many bytes of that text to skip
+fn strip_outside_strings(
+    content: &str,
+    mut handle: impl FnMut(char, &str, &mut String) -> usize,
+) -> String {
+    let mut stripped = String::with_capacity(content.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut skip_until = 0;
+    for (index, c) in content.char_indices() {
+        if index < skip_until {
+            continue;
+        }
+        if in_string {
+            stripped.push(c);
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => (),
+            }
+        } else if c == '"' {
+            in_string = true;
+            stripped.push(c);
+        } else {
+            let rest = &content[index + c.len_utf8()..];
+            skip_until = index + c.len_utf8() + handle(c, rest, &mut stripped);
+        }
+    }
+    stripped
+}
@@ -0,0 +1,105 @@
+use super::*;
+use serde_json::json;
+
+const THEME: &str = r##"// exported from VS Code
+{
+    "name": "Night Owl Lite",
+    "type": "dark",
+    "colors": {
+        "editor.background": "#011627", /* the panel */
+        "editor.foreground": "#d6deeb",
+        "editor.selectionBackground": "#1d3b53",
+        "editorCursor.foreground": "not a color",
+    },
+    "tokenColors": [
+        {
+            "name": "Comment",
+            "scope": ["comment", "punctuation.definition.comment"],
+            "settings": { "fontStyle": "italic", "foreground": "#637777" },
+        },
+        { "scope": "keyword, storage.modifier", "settings": { "foreground": "#c792ea" } },
+        { "scope": "keyword.control", "settings": { "foreground": "#ff5874" } },
+        { "scope": "string", "settings": { "foreground": "#ecc48d" } },
+        { "scope": "meta.tag string", "settings": { "foreground": "#ffffff" } },
+        { "scope": "constant.numeric", "settings": {} },
+    ],
+}
+"##;
+
+#[test]
+fn line_comments_outside_strings() {
+    let stripped = strip_jsonc("{\"url\": \"http://a//b\", // the site\n\"n\": 1}");
+    assert_eq!(stripped, "{\"url\": \"http://a//b\", \n\"n\": 1}");
+}
+
+#[test]
+fn block_comments() {
+    let stripped = strip_jsonc("{/* a\n * b */\"a\": \"/* kept */\" /**/}");
+    assert_eq!(stripped, "{\"a\": \"/* kept */\" }");
+    // an unclosed comment runs to the end
+    assert_eq!(strip_jsonc("[1] /* open"), "[1] ");
+}
+
+#[test]
+fn trailing_commas() {
+    let stripped = strip_jsonc("{\"a\": [1, 2, ],\n\"b\": \"x,}\",\n}");
+    assert_eq!(stripped, "{\"a\": [1, 2 ],\n\"b\": \"x,}\"\n}");
+    let value: Value = serde_json::from_str(&stripped).unwrap();
+    assert_eq!(value["b"], "x,}");
+    // an escaped quote does not end the string
+    assert_eq!(strip_jsonc(r#"["\",]", ]"#), r#"["\",]" ]"#);
+}
+
+#[test]
+fn scopes_as_array_or_list() {
+    let rules = token_rules(&json!([
+        { "scope": ["comment", "string.quoted"], "settings": { "foreground": "#111" } },
+        { "scope": "keyword , storage", "settings": { "foreground": "#222" } },
+        { "scope": "constant", "settings": { "foreground": "blue" } },
+        { "settings": { "foreground": "#333" } },
+    ]));
+    let scopes: Vec<(&str, &str)> = rules
+        .iter()
+        .map(|(scope, color)| (scope.as_str(), color.as_str()))
+        .collect();
+    assert_eq!(
+        scopes,
+        [
+            ("comment", "#111"),
+            ("string.quoted", "#111"),
+            ("keyword", "#222"),
+            ("storage", "#222"),
+        ]
+    );
+
+    assert_eq!(
+        rule_color(&rules, "keyword.control").as_deref(),
+        Some("#222")
+    );
+    assert_eq!(rule_color(&rules, "string").as_deref(), None);
+    assert_eq!(rule_color(&rules, "keywords"), None);
+}
+
+#[test]
+fn vscode_theme_file() {
+    let value: Value = serde_json::from_str(&strip_jsonc(THEME)).unwrap();
+    assert!(ColorTheme::is_vscode(&value));
+    let theme = ColorTheme::from_vscode(&value);
+    let default = ColorTheme::default();
+
+    assert_eq!(theme.name, "Night Owl Lite");
+    assert!(theme.dark);
+    assert_eq!(theme.bg, "#011627");
+    assert_eq!(theme.selection, "#1d3b53");
+    // the malformed cursor color gives way to the foreground
+    assert_eq!(theme.cursor, "#d6deeb");
+    assert_eq!(theme.comments, "#637777");
+    // the most specific rule wins, selectors with several parts are skipped
+    assert_eq!(theme.keywords, "#ff5874");
+    assert_eq!(theme.strs, "#ecc48d");
+    // no color of its own
+    assert_eq!(theme.numerics, "#d6deeb");
+    assert_ne!(theme.numerics, default.numerics);
+
+    assert!(!ColorTheme::is_vscode(&json!({ "bg": "#000" })));
+}
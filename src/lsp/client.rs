@@ -0,0 +1,478 @@
+use eframe::egui;
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    mem,
+    path::Path,
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::{path_to_uri, uri_to_path, Diagnostic, Location, ServerConfig};
+
+/// How long a server has to answer shutdown and exit before it is killed
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+enum Outgoing {
+    Message(Vec<u8>),
+    /// The id of the shutdown request, with the server messages to find its answer in,
+    /// or nothing when the server was never initialized
+    Shutdown(Option<(u64, mpsc::Receiver<Value>)>),
+}
+
+enum Request {
+    Initialize,
+    Hover(String, (usize, usize)),
+    Definition,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hover {
+    pub uri: String,
+    pub position: (usize, usize),
+    pub text: Option<String>,
+}
+
+struct Document {
+    version: i64,
+    text: String,
+}
+
+/// Connection to one language server process, talking JSON-RPC over its stdio
+pub struct LspClient {
+    /// To the thread owning the process, so that the ui never waits on its pipe or its exit
+    writer: mpsc::Sender<Outgoing>,
+    receiver: mpsc::Receiver<Value>,
+    next_id: u64,
+    pending: HashMap<u64, Request>,
+    initialized: bool,
+    queue: Vec<Value>,
+    documents: HashMap<String, Document>,
+    pub diagnostics: HashMap<String, Vec<Diagnostic>>,
+    pub hover: Option<Hover>,
+    pub definition: Option<Location>,
+}
+
+impl LspClient {
+    pub fn start(
+        config: &ServerConfig,
+        root: &Path,
+        ctx: &egui::Context,
+    ) -> Result<Self, std::io::Error> {
+        let mut child = Command::new(&config.command)
+            .args(&config.args)
+            .current_dir(root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| std::io::Error::other("Failed to open stdin"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| std::io::Error::other("Failed to open stdout"))?;
+
+        let (writer, outgoing) = mpsc::channel();
+        thread::spawn(move || run_server(child, stdin, outgoing));
+
+        let (sender, receiver) = mpsc::channel();
+        let ctx = ctx.clone();
+        thread::spawn(move || read_messages(stdout, sender, ctx));
+
+        let mut client = Self {
+            writer,
+            receiver,
+            next_id: 1,
+            pending: HashMap::new(),
+            initialized: false,
+            queue: vec![],
+            documents: HashMap::new(),
+            diagnostics: HashMap::new(),
+            hover: None,
+            definition: None,
+        };
+
+        client.request(
+            Request::Initialize,
+            "initialize",
+            json!({
+                "processId": std::process::id(),
+                "rootUri": path_to_uri(root),
+                "clientInfo": { "name": "calcifer" },
+                "capabilities": {
+                    "textDocument": {
+                        "synchronization": { "didSave": true },
+                        "hover": { "contentFormat": ["plaintext", "markdown"] },
+                        "definition": { "linkSupport": true },
+                        "publishDiagnostics": {},
+                    },
+                },
+            }),
+        );
+
+        Ok(client)
+    }
+
+    /// Send didOpen the first time a document is seen, then didChange with the full text
+    pub fn update_document(&mut self, uri: &str, language_id: &str, text: &str) {
+        match self.documents.get_mut(uri) {
+            None => {
+                self.documents.insert(
+                    uri.into(),
+                    Document {
+                        version: 1,
+                        text: text.into(),
+                    },
+                );
+                self.notify(
+                    "textDocument/didOpen",
+                    json!({
+                        "textDocument": {
+                            "uri": uri,
+                            "languageId": language_id,
+                            "version": 1,
+                            "text": text,
+                        },
+                    }),
+                );
+            }
+            Some(document) if document.text != text => {
+                document.version += 1;
+                document.text = text.into();
+                let version = document.version;
+                self.notify(
+                    "textDocument/didChange",
+                    json!({
+                        "textDocument": { "uri": uri, "version": version },
+                        "contentChanges": [{ "text": text }],
+                    }),
+                );
+            }
+            Some(_) => (),
+        }
+    }
+
+    pub fn save_document(&mut self, uri: &str) {
+        if self.documents.contains_key(uri) {
+            self.notify(
+                "textDocument/didSave",
+                json!({ "textDocument": { "uri": uri } }),
+            );
+        }
+    }
+
+    pub fn close_document(&mut self, uri: &str) {
+        if self.documents.remove(uri).is_some() {
+            self.diagnostics.remove(uri);
+            self.notify(
+                "textDocument/didClose",
+                json!({ "textDocument": { "uri": uri } }),
+            );
+        }
+    }
+
+    pub fn request_hover(&mut self, uri: &str, position: (usize, usize)) {
+        let already_asked = self
+            .pending
+            .values()
+            .any(|request| matches!(request, Request::Hover(u, p) if u == uri && *p == position));
+        if already_asked {
+            return;
+        }
+        self.request(
+            Request::Hover(uri.into(), position),
+            "textDocument/hover",
+            text_document_position(uri, position),
+        );
+    }
+
+    pub fn request_definition(&mut self, uri: &str, position: (usize, usize)) {
+        self.request(
+            Request::Definition,
+            "textDocument/definition",
+            text_document_position(uri, position),
+        );
+    }
+
+    /// Handle every message received since the last call
+    pub fn poll(&mut self) {
+        while let Ok(message) = self.receiver.try_recv() {
+            match (message.get("id"), message.get("method")) {
+                (Some(id), Some(method)) => {
+                    self.answer_server_request(id.clone(), method, &message)
+                }
+                (Some(id), None) => self.handle_response(id, &message),
+                (None, Some(method)) => self.handle_notification(method, &message),
+                (None, None) => (),
+            }
+        }
+    }
+
+    fn handle_response(&mut self, id: &Value, message: &Value) {
+        let Some(request) = id.as_u64().and_then(|id| self.pending.remove(&id)) else {
+            return;
+        };
+        let result = message.get("result").unwrap_or(&Value::Null);
+
+        match request {
+            Request::Initialize => {
+                self.initialized = true;
+                self.notify("initialized", json!({}));
+                for queued in std::mem::take(&mut self.queue) {
+                    self.write(&queued);
+                }
+            }
+            Request::Hover(uri, position) => {
+                let text = hover_text(result.get("contents").unwrap_or(&Value::Null));
+                self.hover = Some(Hover {
+                    uri,
+                    position,
+                    text: if text.trim().is_empty() {
+                        None
+                    } else {
+                        Some(text.trim().to_string())
+                    },
+                });
+            }
+            Request::Definition => {
+                let location = match result {
+                    Value::Array(locations) => locations.first().unwrap_or(&Value::Null),
+                    _ => result,
+                };
+                self.definition = parse_location(location);
+            }
+        }
+    }
+
+    fn handle_notification(&mut self, method: &Value, message: &Value) {
+        if method != "textDocument/publishDiagnostics" {
+            return;
+        }
+        let params = message.get("params").unwrap_or(&Value::Null);
+        let Some(uri) = params.get("uri").and_then(|uri| uri.as_str()) else {
+            return;
+        };
+        let diagnostics = params
+            .get("diagnostics")
+            .and_then(|diagnostics| diagnostics.as_array())
+            .map(|diagnostics| diagnostics.iter().filter_map(parse_diagnostic).collect())
+            .unwrap_or_default();
+        self.diagnostics.insert(uri.into(), diagnostics);
+    }
+
+    /// Servers can ask things to the client, an empty answer keeps them going
+    fn answer_server_request(&mut self, id: Value, method: &Value, message: &Value) {
+        let result = if method == "workspace/configuration" {
+            let items = message["params"]["items"]
+                .as_array()
+                .map_or(0, |items| items.len());
+            Value::Array(vec![Value::Null; items])
+        } else {
+            Value::Null
+        };
+        self.write(&json!({ "jsonrpc": "2.0", "id": id, "result": result }));
+    }
+
+    fn request(&mut self, request: Request, method: &str, params: Value) {
+        let id = self.next_id;
+        self.next_id += 1;
+        let is_initialize = matches!(request, Request::Initialize);
+        self.pending.insert(id, request);
+
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        if is_initialize {
+            self.write(&message);
+        } else {
+            self.send(message);
+        }
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        let message = json!({ "jsonrpc": "2.0", "method": method, "params": params });
+        if method == "initialized" {
+            self.write(&message);
+        } else {
+            self.send(message);
+        }
+    }
+
+    /// Nothing but the initialize request may be sent before the server answered it
+    fn send(&mut self, message: Value) {
+        if self.initialized {
+            self.write(&message);
+        } else {
+            self.queue.push(message);
+        }
+    }
+
+    fn write(&mut self, message: &Value) {
+        // the thread only stops once the pipe is broken, which it reports itself
+        let _ = self.writer.send(Outgoing::Message(frame(message)));
+    }
+}
+
+impl Drop for LspClient {
+    /// The server is shut down by its thread, in the background
+    fn drop(&mut self) {
+        let request = self.initialized.then(|| {
+            (
+                self.next_id,
+                mem::replace(&mut self.receiver, mpsc::channel().1),
+            )
+        });
+        let _ = self.writer.send(Outgoing::Shutdown(request));
+    }
+}
+
+fn frame(message: &Value) -> Vec<u8> {
+    let body = message.to_string();
+    format!("Content-Length: {}\r\n\r\n{}", body.len(), body).into_bytes()
+}
+
+/// Write the messages to the server until the client is dropped, then ask it for shutdown
+/// and exit, killing it if it is still running after `SHUTDOWN_TIMEOUT`
+fn run_server(mut child: Child, mut stdin: ChildStdin, outgoing: mpsc::Receiver<Outgoing>) {
+    let mut write = |message: &[u8]| stdin.write_all(message).and_then(|_| stdin.flush());
+    let mut shutdown = None;
+    for message in outgoing {
+        match message {
+            Outgoing::Message(message) => {
+                if let Err(err) = write(&message) {
+                    eprintln!("Error writing to language server: {}", err);
+                    break;
+                }
+            }
+            Outgoing::Shutdown(request) => {
+                shutdown = request;
+                break;
+            }
+        }
+    }
+
+    let deadline = Instant::now() + SHUTDOWN_TIMEOUT;
+    if let Some((id, messages)) = shutdown {
+        let _ = write(&frame(
+            &json!({ "jsonrpc": "2.0", "id": id, "method": "shutdown" }),
+        ));
+        // exit may only be sent once shutdown is answered
+        while let Ok(message) =
+            messages.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        {
+            if message.get("id").and_then(Value::as_u64) == Some(id)
+                && message.get("method").is_none()
+            {
+                break;
+            }
+        }
+        let _ = write(&frame(&json!({ "jsonrpc": "2.0", "method": "exit" })));
+    }
+    drop(stdin);
+    while Instant::now() < deadline {
+        if !matches!(child.try_wait(), Ok(None)) {
+            return;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+fn read_messages(stdout: ChildStdout, sender: mpsc::Sender<Value>, ctx: egui::Context) {
+    let mut reader = BufReader::new(stdout);
+    loop {
+        let mut length: Option<usize> = None;
+        loop {
+            let mut header = String::new();
+            match reader.read_line(&mut header) {
+                Ok(0) | Err(_) => return,
+                Ok(_) => (),
+            }
+            let header = header.trim();
+            if header.is_empty() && length.is_some() {
+                break;
+            }
+            if let Some(value) = header.strip_prefix("Content-Length:") {
+                length = value.trim().parse().ok();
+            }
+        }
+
+        let mut body = vec![0; length.unwrap_or_default()];
+        if reader.read_exact(&mut body).is_err() {
+            return;
+        }
+        if let Ok(message) = serde_json::from_slice::<Value>(&body) {
+            if sender.send(message).is_err() {
+                return;
+            }
+            ctx.request_repaint();
+        }
+    }
+}
+
+fn text_document_position(uri: &str, (line, character): (usize, usize)) -> Value {
+    json!({
+        "textDocument": { "uri": uri },
+        "position": { "line": line, "character": character },
+    })
+}
+
+fn parse_position(position: &Value) -> Option<(usize, usize)> {
+    Some((
+        position.get("line")?.as_u64()? as usize,
+        position.get("character")?.as_u64()? as usize,
+    ))
+}
+
+fn parse_diagnostic(diagnostic: &Value) -> Option<Diagnostic> {
+    Some(Diagnostic {
+        start: parse_position(&diagnostic["range"]["start"])?,
+        end: parse_position(&diagnostic["range"]["end"])?,
+        severity: diagnostic["severity"].as_u64().unwrap_or(1) as u8,
+        message: diagnostic["message"].as_str().unwrap_or_default().into(),
+    })
+}
+
+/// Accepts both Location and LocationLink
+fn parse_location(location: &Value) -> Option<Location> {
+    let uri = location
+        .get("uri")
+        .or_else(|| location.get("targetUri"))?
+        .as_str()?;
+    let range = location
+        .get("range")
+        .or_else(|| location.get("targetSelectionRange"))?;
+    let (line, character) = parse_position(&range["start"])?;
+    Some(Location {
+        path: uri_to_path(uri)?,
+        line,
+        character,
+    })
+}
+
+/// Hover contents can be a string, a MarkupContent, a MarkedString or a list of those
+fn hover_text(contents: &Value) -> String {
+    match contents {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .map(hover_text)
+            .collect::<Vec<String>>()
+            .join("\n\n"),
+        Value::Object(content) => content
+            .get("value")
+            .and_then(|value| value.as_str())
+            .unwrap_or_default()
+            .lines()
+            .filter(|line| !line.starts_with("```"))
+            .collect::<Vec<&str>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
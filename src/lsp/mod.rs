@@ -0,0 +1,301 @@
+mod client;
+pub use client::*;
+
+#[cfg(test)]
+mod tests;
+
+use eframe::egui;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+
+use crate::editor::position::{self, Column};
+use crate::panels::Tab;
+
+const ROOT_MARKERS: [&str; 5] = [
+    ".git",
+    "Cargo.toml",
+    "pyproject.toml",
+    "package.json",
+    "go.mod",
+];
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+/// How to launch the language server of a file extension
+pub struct ServerConfig {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub language_id: String,
+}
+
+impl ServerConfig {
+    fn new(command: &str, args: &[&str], language_id: &str) -> Self {
+        Self {
+            command: command.into(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            language_id: language_id.into(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// Diagnostic published by a server, positions are in UTF-16 code units as in the protocol
+pub struct Diagnostic {
+    pub start: (usize, usize),
+    pub end: (usize, usize),
+    pub severity: u8,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Location {
+    pub path: PathBuf,
+    pub line: usize,
+    pub character: usize,
+}
+
+/// A server process is shared by every file with the same command and workspace root
+type ServerKey = (String, PathBuf);
+
+/// Server of an open document, and the version of its tab history it was last sent
+struct OpenDocument {
+    key: ServerKey,
+    version: u64,
+}
+
+pub struct LspManager {
+    servers: HashMap<String, ServerConfig>,
+    clients: HashMap<ServerKey, LspClient>,
+    documents: HashMap<PathBuf, OpenDocument>,
+    failed: Vec<String>,
+}
+
+impl LspManager {
+    /// Built-in servers, overridden by the extensions listed in the config file
+    pub fn new(config_path: &Path) -> Self {
+        let mut servers: HashMap<String, ServerConfig> = HashMap::from([
+            ("rs".into(), ServerConfig::new("rust-analyzer", &[], "rust")),
+            (
+                "py".into(),
+                ServerConfig::new("pyright-langserver", &["--stdio"], "python"),
+            ),
+            (
+                "js".into(),
+                ServerConfig::new("typescript-language-server", &["--stdio"], "javascript"),
+            ),
+            (
+                "ts".into(),
+                ServerConfig::new("typescript-language-server", &["--stdio"], "typescript"),
+            ),
+            (
+                "tsx".into(),
+                ServerConfig::new(
+                    "typescript-language-server",
+                    &["--stdio"],
+                    "typescriptreact",
+                ),
+            ),
+            ("c".into(), ServerConfig::new("clangd", &[], "c")),
+            ("h".into(), ServerConfig::new("clangd", &[], "c")),
+            ("cpp".into(), ServerConfig::new("clangd", &[], "cpp")),
+            (
+                "lua".into(),
+                ServerConfig::new("lua-language-server", &[], "lua"),
+            ),
+        ]);
+
+        if let Ok(config) = read_to_string(config_path) {
+            match serde_json::from_str::<HashMap<String, ServerConfig>>(&config) {
+                Ok(user_servers) => servers.extend(user_servers),
+                Err(err) => eprintln!("Error reading {} : {}", config_path.display(), err),
+            }
+        }
+
+        Self {
+            servers,
+            clients: HashMap::new(),
+            documents: HashMap::new(),
+            failed: vec![],
+        }
+    }
+
+    /// Start the servers needed by the open tabs, send them the buffers that changed
+    /// and read their messages
+    pub fn sync(&mut self, ctx: &egui::Context, tabs: &[Tab]) {
+        for tab in tabs {
+            if !tab.path.is_absolute() {
+                continue;
+            }
+            let Some(config) = self.servers.get(&tab.language) else {
+                continue;
+            };
+            if self.failed.contains(&config.command) {
+                continue;
+            }
+            let version = tab.history.version();
+            let key = match self.documents.get(&tab.path) {
+                Some(document) if document.key.0 == config.command => {
+                    if document.version == version {
+                        continue;
+                    }
+                    document.key.clone()
+                }
+                _ => (config.command.clone(), workspace_root(&tab.path)),
+            };
+            let config = config.clone();
+            // the language of the tab changed, and with it its server
+            if self
+                .documents
+                .get(&tab.path)
+                .is_some_and(|document| document.key != key)
+            {
+                self.forget(&tab.path);
+            }
+            if !self.clients.contains_key(&key) {
+                match LspClient::start(&config, &key.1, ctx) {
+                    Ok(client) => {
+                        self.clients.insert(key.clone(), client);
+                    }
+                    Err(err) => {
+                        eprintln!("Could not start {} : {}", config.command, err);
+                        self.failed.push(config.command.clone());
+                        continue;
+                    }
+                }
+            }
+            if let Some(client) = self.clients.get_mut(&key) {
+                client.update_document(&path_to_uri(&tab.path), &config.language_id, &tab.code);
+                self.documents
+                    .insert(tab.path.clone(), OpenDocument { key, version });
+            }
+        }
+
+        for client in self.clients.values_mut() {
+            client.poll();
+        }
+    }
+
+    fn client(&self, tab: &Tab) -> Option<&LspClient> {
+        self.clients.get(&self.documents.get(&tab.path)?.key)
+    }
+
+    fn client_mut(&mut self, tab: &Tab) -> Option<&mut LspClient> {
+        self.clients.get_mut(&self.documents.get(&tab.path)?.key)
+    }
+
+    /// Close a document in its server, which is stopped once it has none left open
+    fn forget(&mut self, path: &Path) {
+        let Some(document) = self.documents.remove(path) else {
+            return;
+        };
+        if let Some(client) = self.clients.get_mut(&document.key) {
+            client.close_document(&path_to_uri(path));
+        }
+        if !self.documents.values().any(|open| open.key == document.key) {
+            self.clients.remove(&document.key);
+        }
+    }
+
+    pub fn saved(&mut self, tab: &Tab) {
+        if let Some(client) = self.client_mut(tab) {
+            client.save_document(&path_to_uri(&tab.path));
+        }
+    }
+
+    pub fn closed(&mut self, tab: &Tab) {
+        self.forget(&tab.path);
+    }
+
+    pub fn diagnostics(&self, tab: &Tab) -> Vec<Diagnostic> {
+        self.client(tab)
+            .and_then(|client| client.diagnostics.get(&path_to_uri(&tab.path)))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Hover text at a char index, asked to the server the first time a position is hovered
+    pub fn hover(&mut self, tab: &Tab, char_index: usize) -> Option<String> {
+        let client = self.client_mut(tab)?;
+        let uri = path_to_uri(&tab.path);
+        let position = char_to_position(&tab.code, char_index);
+        match &client.hover {
+            Some(hover) if hover.uri == uri && hover.position == position => hover.text.clone(),
+            _ => {
+                client.request_hover(&uri, position);
+                None
+            }
+        }
+    }
+
+    pub fn request_definition(&mut self, tab: &Tab, char_index: usize) {
+        if let Some(client) = self.client_mut(tab) {
+            client.request_definition(
+                &path_to_uri(&tab.path),
+                char_to_position(&tab.code, char_index),
+            );
+        }
+    }
+
+    pub fn take_definition(&mut self) -> Option<Location> {
+        self.clients
+            .values_mut()
+            .find_map(|client| client.definition.take())
+    }
+}
+
+/// Closest parent directory looking like a project root
+pub fn workspace_root(path: &Path) -> PathBuf {
+    let parent = path.parent().unwrap_or(path);
+    parent
+        .ancestors()
+        .find(|dir| ROOT_MARKERS.iter().any(|marker| dir.join(marker).exists()))
+        .unwrap_or(parent)
+        .to_path_buf()
+}
+
+pub fn path_to_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?;
+    let mut bytes: Vec<u8> = vec![];
+    let mut iter = encoded.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let hex: String = iter.by_ref().take(2).map(|b| b as char).collect();
+            bytes.push(u8::from_str_radix(&hex, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    Some(PathBuf::from(String::from_utf8(bytes).ok()?))
+}
+
+/// Protocol position (line, UTF-16 offset) of a char index
+pub fn char_to_position(text: &str, char_index: usize) -> (usize, usize) {
+    position::char_to_line_col(text, char_index, Column::Utf16)
+}
+
+/// Byte offset of a protocol position, clamped to the end of its line
+pub fn position_to_byte(text: &str, position: (usize, usize)) -> usize {
+    position::line_col_to_byte(text, position, Column::Utf16)
+}
+
+/// Char index of a protocol position, clamped to the end of its line
+pub fn position_to_char(text: &str, position: (usize, usize)) -> usize {
+    position::line_col_to_char(text, position, Column::Utf16)
+}
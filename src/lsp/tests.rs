@@ -0,0 +1,212 @@
+use super::*;
+use crate::editor::EditKind;
+use std::{env, fs, thread, time::Duration, time::Instant};
+
+// Stand-in server answering with canned messages, ids and uris are copied from the requests
+const FAKE_SERVER: &str = r#"#!/bin/sh
+send() {
+	printf 'Content-Length: %d\r\n\r\n%s' "${#1}" "$1"
+}
+while IFS= read -r header; do
+	case "$header" in
+		Content-Length:*) length=$(printf '%s' "$header" | tr -dc '0-9') ;;
+		*) continue ;;
+	esac
+	read -r blank
+	body=$(dd bs=1 count="$length" 2>/dev/null)
+	id=$(printf '%s' "$body" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+	uri=$(printf '%s' "$body" | sed -n 's/.*"uri":"\([^"]*\)".*/\1/p')
+	case "$body" in
+		*'"method":"initialize"'*)
+			send "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{\"capabilities\":{}}}" ;;
+		*'"method":"textDocument/didOpen"'*)
+			send "{\"jsonrpc\":\"2.0\",\"method\":\"textDocument/publishDiagnostics\",\"params\":{\"uri\":\"$uri\",\"diagnostics\":[{\"range\":{\"start\":{\"line\":1,\"character\":4},\"end\":{\"line\":1,\"character\":7}},\"severity\":2,\"message\":\"unused variable\"}]}}" ;;
+		*'"method":"textDocument/hover"'*)
+			send "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{\"contents\":{\"kind\":\"markdown\",\"value\":\"\`\`\`rust\nfn main()\n\`\`\`\"}}}" ;;
+		*'"method":"textDocument/definition"'*)
+			send "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":[{\"targetUri\":\"$uri\",\"targetRange\":{\"start\":{\"line\":0,\"character\":0},\"end\":{\"line\":2,\"character\":1}},\"targetSelectionRange\":{\"start\":{\"line\":0,\"character\":3},\"end\":{\"line\":0,\"character\":7}}}]}" ;;
+		*'"method":"shutdown"'*)
+			send "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":null}" ;;
+		*'"method":"exit"'*)
+			touch "$0.exited"
+			exit 0 ;;
+	esac
+done
+"#;
+
+fn fake_server(name: &str) -> ServerConfig {
+    let dir = env::temp_dir().join(format!("calcifer_lsp_{}_{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let script = dir.join("server.sh");
+    fs::write(&script, FAKE_SERVER).unwrap();
+    ServerConfig::new("sh", &[&script.to_string_lossy()], "rust")
+}
+
+fn remove_fake_server(config: &ServerConfig) {
+    if let Some(dir) = Path::new(&config.args[0]).parent() {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
+
+fn tab(path: &Path, language: &str) -> Tab {
+    Tab {
+        path: path.into(),
+        language: language.into(),
+        ..Default::default()
+    }
+}
+
+fn wait_for<T>(client: &mut LspClient, mut check: impl FnMut(&LspClient) -> Option<T>) -> T {
+    let start = Instant::now();
+    loop {
+        client.poll();
+        if let Some(value) = check(client) {
+            return value;
+        }
+        assert!(
+            start.elapsed() < Duration::from_secs(5),
+            "no answer from server"
+        );
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn positions_count_utf16_units() {
+    let text = "é😀x\nab";
+    assert_eq!(char_to_position(text, 2), (0, 3));
+    assert_eq!(char_to_position(text, 5), (1, 1));
+    assert_eq!(position_to_byte(text, (0, 3)), "é😀".len());
+    assert_eq!(position_to_char(text, (0, 3)), 2);
+    assert_eq!(position_to_char(text, (0, 99)), 3);
+    assert_eq!(position_to_byte(text, (5, 0)), text.len());
+}
+
+#[test]
+fn uri_round_trip() {
+    let path = Path::new("/home/user/my project/é.rs");
+    let uri = path_to_uri(path);
+    assert_eq!(uri, "file:///home/user/my%20project/%C3%A9.rs");
+    assert_eq!(uri_to_path(&uri), Some(path.to_path_buf()));
+}
+
+#[test]
+fn diagnostics_from_server() {
+    let config = fake_server("diagnostics");
+    let mut client =
+        LspClient::start(&config, &env::temp_dir(), &egui::Context::default()).unwrap();
+    let uri = "file:///tmp/main.rs";
+    client.update_document(uri, "rust", "fn main() {\n    let x = 1;\n}\n");
+
+    let diagnostics = wait_for(&mut client, |client| client.diagnostics.get(uri).cloned());
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic {
+            start: (1, 4),
+            end: (1, 7),
+            severity: 2,
+            message: "unused variable".into(),
+        }]
+    );
+    remove_fake_server(&config);
+}
+
+#[test]
+fn hover_and_definition_from_server() {
+    let config = fake_server("hover");
+    let mut client =
+        LspClient::start(&config, &env::temp_dir(), &egui::Context::default()).unwrap();
+    let uri = "file:///tmp/main.rs";
+    client.update_document(uri, "rust", "fn main() {\n    main();\n}\n");
+
+    client.request_hover(uri, (1, 5));
+    let hover = wait_for(&mut client, |client| client.hover.clone());
+    assert_eq!(hover.position, (1, 5));
+    assert_eq!(hover.text, Some("fn main()".into()));
+
+    client.request_definition(uri, (1, 5));
+    let definition = wait_for(&mut client, |client| client.definition.clone());
+    assert_eq!(
+        definition,
+        Location {
+            path: PathBuf::from("/tmp/main.rs"),
+            line: 0,
+            character: 3,
+        }
+    );
+    remove_fake_server(&config);
+}
+
+#[test]
+fn servers_are_shared_by_command_and_root() {
+    let config = fake_server("shared");
+    let projects = env::temp_dir().join(format!("calcifer_lsp_projects_{}", std::process::id()));
+    for project in ["first", "second"] {
+        fs::create_dir_all(projects.join(project).join(".git")).unwrap();
+    }
+    let mut manager = LspManager {
+        servers: HashMap::from([("c".into(), config.clone()), ("h".into(), config.clone())]),
+        clients: HashMap::new(),
+        documents: HashMap::new(),
+        failed: vec![],
+    };
+    let mut tabs = [
+        tab(&projects.join("first/main.c"), "c"),
+        tab(&projects.join("first/include/main.h"), "h"),
+        tab(&projects.join("second/main.c"), "c"),
+    ];
+
+    manager.sync(&egui::Context::default(), &tabs);
+    let mut roots: Vec<&PathBuf> = manager.clients.keys().map(|(_, root)| root).collect();
+    roots.sort();
+    assert_eq!(roots, [&projects.join("first"), &projects.join("second")]);
+    assert_eq!(
+        manager.documents[&tabs[0].path].key,
+        manager.documents[&tabs[1].path].key
+    );
+
+    // only the tabs whose history moved are sent again
+    tabs[0].code.push_str("int x;");
+    let code = tabs[0].code.clone();
+    tabs[0].history.record(&code, None, EditKind::Typing);
+    manager.sync(&egui::Context::default(), &tabs);
+    assert_eq!(manager.documents[&tabs[0].path].version, 1);
+    assert_eq!(manager.documents[&tabs[1].path].version, 0);
+
+    // a server is stopped with the last of its documents
+    manager.closed(&tabs[1]);
+    assert!(!manager.documents.contains_key(&tabs[1].path));
+    assert_eq!(manager.clients.len(), 2);
+    manager.closed(&tabs[2]);
+    assert_eq!(manager.clients.len(), 1);
+
+    let _ = fs::remove_dir_all(projects);
+    remove_fake_server(&config);
+}
+
+#[test]
+fn dropped_client_shuts_the_server_down() {
+    let config = fake_server("shutdown");
+    let exited = PathBuf::from(format!("{}.exited", config.args[0]));
+    let _ = fs::remove_file(&exited);
+    let mut client =
+        LspClient::start(&config, &env::temp_dir(), &egui::Context::default()).unwrap();
+    let uri = "file:///tmp/main.rs";
+    client.update_document(uri, "rust", "fn main() {}\n");
+    wait_for(&mut client, |client| client.diagnostics.get(uri).cloned());
+
+    let start = Instant::now();
+    drop(client);
+    assert!(
+        start.elapsed() < Duration::from_secs(1),
+        "drop waited on the server"
+    );
+    while !exited.exists() {
+        assert!(
+            start.elapsed() < Duration::from_secs(5),
+            "server did not exit"
+        );
+        thread::sleep(Duration::from_millis(10));
+    }
+    remove_fake_server(&config);
+}
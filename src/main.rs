@@ -5,11 +5,11 @@ use egui::{
 };
 use homedir::get_my_home;
 use std::env;
-use std::time::Duration;
 use std::{ops::Range, path::PathBuf, sync::Arc, thread, time};
 
 mod core;
 mod editor;
+mod lsp;
 mod panels;
 mod sub_windows;
 
@@ -23,7 +23,9 @@ const TITLE: &str = "";
 const PROJECT_EXTENSION: &str = "project";
 const TERMINAL_HEIGHT: f32 = 200.0;
 const TERMINAL_RANGE: Range<f32> = 100.0..600.0;
+const TERMINAL_INPUT: &str = "terminal_input";
 const RED: egui::Color32 = egui::Color32::from_rgb(235, 108, 99);
+const YELLOW: egui::Color32 = egui::Color32::from_rgb(235, 203, 99);
 const TIME_LABELS: [&str; 7] = [
 	"input",
 	"settings",
@@ -37,7 +39,7 @@ const ZOOM_FACTOR: f32 = 1.1;
 const MAX_FPS: f32 = 30.0;
 const DISPLAY_PATH_DEPTH: usize = 3;
 const MAX_PROJECT_COLUMNS: usize = 8;
-const RUNNING_COMMAND_REFRESH_DELAY: f32 = 0.2;
+const HOVER_DELAY: f32 = 0.5;
 
 fn main() -> Result<(), eframe::Error> {
 	let icon_data = core::load_icon().unwrap_or_default();
@@ -69,8 +71,6 @@ fn main() -> Result<(), eframe::Error> {
 		None
 	};
 
-	//panels::send_command("export RUSTFLAGS=--cfg=web_sys_unstable_apis".to_string());
-
 	eframe::run_native(
 		&format!("Calcifer{}", TITLE),
 		options,
@@ -88,10 +88,13 @@ struct Calcifer {
 	mouse_holder: panels::MouseHolder,
 
 	command: String,
-	command_history: Vec<panels::CommandEntry>,
-	running_command: bool,
+	command_history: panels::CommandHistory,
+	terminals: Vec<panels::TerminalSession>,
+	selected_terminal: usize,
+	terminal_selection: Option<panels::OutputSelection>,
 
 	theme: editor::ColorTheme,
+	languages: editor::LanguageRegistry,
 	font_size: f32,
 	zoom: f32,
 
@@ -110,10 +113,22 @@ struct Calcifer {
 	tab_to_close: usize,
 	refresh_confirm: sub_windows::ConfirmWindow,
 	exit_confirm: sub_windows::ConfirmWindow,
-
+	conflict_confirm: sub_windows::ConfirmWindow,
+	conflicts: Vec<PathBuf>,
+	conflict_path: Option<PathBuf>,
+	/// Conflicts put aside while their diff is open, with the path of the diff
+	conflict_diffs: Vec<(PathBuf, PathBuf)>,
+	file_watcher: core::FileWatcher,
+	lsp: lsp::LspManager,
+
+	problems: panels::Problems,
 	search_menu: sub_windows::SearchWindow,
 	settings_menu: sub_windows::SettingsWindow,
 	shortcuts_menu: sub_windows::ShortcutsWindow,
+	task_menu: sub_windows::TaskWindow,
+	last_task: Option<panels::Task>,
+	goto_menu: sub_windows::GotoWindow,
+	outline_menu: sub_windows::OutlineWindow,
 
 	time_watch: Vec<f32>,
 	next_frame: time::Instant,
@@ -121,6 +136,7 @@ struct Calcifer {
 
 impl Default for Calcifer {
 	fn default() -> Self {
+		let themes = editor::themes::load_themes(&config_path().join("themes"));
 		Self {
 			focused: true,
 			got_focus: false,
@@ -131,10 +147,14 @@ impl Default for Calcifer {
 			mouse_holder: panels::MouseHolder::None,
 
 			command: String::new(),
-			command_history: Vec::new(),
-			running_command: false,
-
-			theme: editor::themes::DEFAULT_THEMES[0],
+			command_history: panels::CommandHistory::default(),
+			terminals: vec![],
+			selected_terminal: 0,
+			terminal_selection: None,
+
+			theme: themes[0].clone(),
+			languages: editor::LanguageRegistry::default()
+				.with_definitions(&config_path().join("syntax")),
 			font_size: 14.0,
 			zoom: 1.0,
 
@@ -159,10 +179,22 @@ impl Default for Calcifer {
 				"Confirm Refresh",
 			),
 			exit_confirm: sub_windows::ConfirmWindow::new("", "Confirm Exit"),
-
+			conflict_confirm: sub_windows::ConfirmWindow::new("", "File Changed On Disk")
+				.with_choices(&["Keep mine", "Take theirs", "Open diff"]),
+			conflicts: vec![],
+			conflict_path: None,
+			conflict_diffs: vec![],
+			file_watcher: core::FileWatcher::new(),
+			lsp: lsp::LspManager::new(&config_path().join("lsp.json")),
+
+			problems: panels::Problems::default(),
 			search_menu: sub_windows::SearchWindow::default(),
-			settings_menu: sub_windows::SettingsWindow::new(editor::themes::DEFAULT_THEMES[0]),
+			settings_menu: sub_windows::SettingsWindow::new(themes[0].clone(), themes),
 			shortcuts_menu: sub_windows::ShortcutsWindow::new(),
+			task_menu: sub_windows::TaskWindow::new(),
+			last_task: None,
+			goto_menu: sub_windows::GotoWindow::new(),
+			outline_menu: sub_windows::OutlineWindow::new(),
 
 			time_watch: vec![0.0; TIME_LABELS.len()],
 			next_frame: time::Instant::now(),
@@ -224,8 +256,10 @@ impl eframe::App for Calcifer {
 			ctx.set_zoom_factor(self.zoom);
 		}
 
+		// Ctrl+R in the terminal input searches the command history
 		if ctx.input(|i| i.key_pressed(egui::Key::R) && i.modifiers.ctrl)
 			&& !self.refresh_confirm.visible
+			&& !ctx.memory(|m| m.has_focus(egui::Id::new(TERMINAL_INPUT)))
 		{
 			if self.tabs[self.selected_tab].saved {
 				self.tabs[self.selected_tab].refresh();
@@ -270,6 +304,19 @@ impl eframe::App for Calcifer {
 			self.search_menu.initialized = !self.search_menu.visible;
 		}
 
+		if ctx.input(|i| i.key_pressed(egui::Key::G) && i.modifiers.ctrl) {
+			self.goto_menu.visible = !self.goto_menu.visible;
+			self.goto_menu.initialized = !self.goto_menu.visible;
+		}
+
+		if ctx.input(|i| i.key_pressed(egui::Key::O) && i.modifiers.ctrl && i.modifiers.shift) {
+			if self.outline_menu.visible {
+				self.outline_menu.visible = false;
+			} else {
+				self.open_outline();
+			}
+		}
+
 		self.got_focus = false;
 		if ctx.input(|i| !i.viewport().focused.unwrap_or_default()) {
 			self.focused = false;
@@ -306,6 +353,32 @@ impl eframe::App for Calcifer {
 			return;
 		}
 
+		self.handle_file_changes(ctx);
+		self.lsp.sync(ctx, &self.tabs);
+
+		if ctx.input(|i| i.key_pressed(egui::Key::F12)) {
+			if let Some(range) = self.tabs[self.selected_tab].last_cursor {
+				self.lsp
+					.request_definition(&self.tabs[self.selected_tab], range.primary.index);
+			}
+		}
+
+		if let Some(location) = self.lsp.take_definition() {
+			self.go_to_location(location);
+		}
+
+		if ctx.input(|i| i.key_pressed(egui::Key::F8)) {
+			let forward = !ctx.input(|i| i.modifiers.shift);
+			self.go_to_problem(forward);
+		}
+
+		if ctx.input(|i| i.key_pressed(egui::Key::F5)) {
+			match self.last_task.clone() {
+				Some(task) => self.run_task(ctx, task),
+				None => self.open_task_menu(),
+			}
+		}
+
 		self.time_watch[0] = watch.elapsed().as_micros() as f32 / 1000.0;
 		watch = time::Instant::now();
 
@@ -315,12 +388,14 @@ impl eframe::App for Calcifer {
 		watch = time::Instant::now();
 
 		self.draw_tree_panel(ctx);
+		self.draw_search_panel(ctx);
 
 		self.time_watch[2] = watch.elapsed().as_micros() as f32 / 1000.0;
 		watch = time::Instant::now();
 
 		self.draw_bottom_tray(ctx);
 		self.draw_terminal_panel(ctx);
+		self.draw_problems_panel(ctx);
 
 		self.time_watch[3] = watch.elapsed().as_micros() as f32 / 1000.0;
 		watch = time::Instant::now();
@@ -339,13 +414,6 @@ impl eframe::App for Calcifer {
 		self.draw_mouse_drag(ctx);
 
 		self.time_watch[6] = watch.elapsed().as_micros() as f32 / 1000.0;
-
-		if self.running_command {
-			egui::Context::request_repaint_after(
-				ctx,
-				Duration::from_secs_f32(RUNNING_COMMAND_REFRESH_DELAY),
-			);
-		}
 	}
 
 	fn on_exit(&mut self, _gl: std::option::Option<&eframe::glow::Context>) {
@@ -375,3 +443,13 @@ fn save_path() -> PathBuf {
 			.to_path_buf()
 	}
 }
+
+//config directory
+fn config_path() -> PathBuf {
+	get_my_home()
+		.unwrap()
+		.unwrap()
+		.as_path()
+		.join(".config")
+		.join("calcifer")
+}
@@ -0,0 +1,157 @@
+#[cfg(test)]
+mod tests;
+
+use eframe::egui;
+use ignore::WalkBuilder;
+use regex::Regex;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+};
+
+use crate::editor::position;
+
+const MAX_HITS_PER_FILE: usize = 500;
+const MAX_PREVIEW_CHARS: usize = 200;
+
+#[derive(Clone, Debug, PartialEq)]
+/// A match in a file on disk, line and column count chars from zero
+pub struct FolderHit {
+    pub line: usize,
+    pub column: usize,
+    pub preview: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileHits {
+    pub path: PathBuf,
+    pub hits: Vec<FolderHit>,
+}
+
+/// Search of every file under a folder, skipping what .gitignore excludes.
+/// The walk runs on its own thread and sends the results file by file.
+pub struct FolderSearch {
+    pub visible: bool,
+    pub root: PathBuf,
+    pub results: Vec<FileHits>,
+    pub searching: bool,
+    receiver: Option<mpsc::Receiver<FileHits>>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl Default for FolderSearch {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            root: PathBuf::new(),
+            results: vec![],
+            searching: false,
+            receiver: None,
+            cancel: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+impl FolderSearch {
+    pub fn start(&mut self, ctx: &egui::Context, root: &Path, pattern: Regex) {
+        self.stop();
+        self.visible = true;
+        self.root = root.to_path_buf();
+        self.results.clear();
+        self.searching = true;
+
+        let (sender, receiver) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.receiver = Some(receiver);
+        self.cancel = cancel.clone();
+
+        let root = root.to_path_buf();
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            for entry in WalkBuilder::new(&root).build().flatten() {
+                if cancel.load(Ordering::Relaxed) {
+                    return;
+                }
+                if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+                    continue;
+                }
+                // binary files, with a nul byte or not utf-8, are skipped
+                let Ok(text) = fs::read_to_string(entry.path()) else {
+                    continue;
+                };
+                if text.contains('\0') {
+                    continue;
+                }
+                let hits = search_text(&pattern, &text);
+                if hits.is_empty() {
+                    continue;
+                }
+                let file_hits = FileHits {
+                    path: entry.into_path(),
+                    hits,
+                };
+                if sender.send(file_hits).is_err() {
+                    return;
+                }
+                ctx.request_repaint();
+            }
+            ctx.request_repaint();
+        });
+    }
+
+    pub fn stop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+        self.receiver = None;
+        self.searching = false;
+    }
+
+    /// Collect the results found since the last call
+    pub fn poll(&mut self) {
+        let Some(receiver) = &self.receiver else {
+            return;
+        };
+        loop {
+            match receiver.try_recv() {
+                Ok(file_hits) => self.results.push(file_hits),
+                Err(mpsc::TryRecvError::Empty) => return,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.receiver = None;
+                    self.searching = false;
+                    return;
+                }
+            }
+        }
+    }
+
+    pub fn hit_count(&self) -> usize {
+        self.results.iter().map(|file| file.hits.len()).sum()
+    }
+}
+
+impl Drop for FolderSearch {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn search_text(pattern: &Regex, text: &str) -> Vec<FolderHit> {
+    let mut hits = vec![];
+    for (line_number, line) in text.lines().enumerate() {
+        for found in pattern.find_iter(line).filter(|found| !found.is_empty()) {
+            hits.push(FolderHit {
+                line: line_number,
+                column: position::byte_to_char(line, found.start()),
+                preview: line.trim().chars().take(MAX_PREVIEW_CHARS).collect(),
+            });
+            if hits.len() >= MAX_HITS_PER_FILE {
+                return hits;
+            }
+        }
+    }
+    hits
+}
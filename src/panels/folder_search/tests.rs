@@ -0,0 +1,98 @@
+use super::*;
+use std::{env, time::Duration};
+
+/// Files under a temporary git folder, some of them ignored
+fn project(name: &str) -> PathBuf {
+    let root = env::temp_dir().join(format!("calcifer_search_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    for dir in [".git", "src", "target/debug"] {
+        fs::create_dir_all(root.join(dir)).unwrap();
+    }
+    fs::write(root.join(".gitignore"), "target/\n*.log\n").unwrap();
+    fs::write(
+        root.join("src/main.rs"),
+        "fn main() {\n    let needle = 1; // needle\n}\n",
+    )
+    .unwrap();
+    fs::write(root.join("src/notes.txt"), "é needle\n").unwrap();
+    fs::write(root.join("target/debug/out.rs"), "needle").unwrap();
+    fs::write(root.join("build.log"), "needle").unwrap();
+    fs::write(root.join("image.bin"), b"needle\0\x01\x02").unwrap();
+    fs::write(root.join("latin1.txt"), b"needle \xe9t\xe9").unwrap();
+    root
+}
+
+fn search(root: &Path, pattern: &str) -> Vec<FileHits> {
+    let mut search = FolderSearch::default();
+    search.start(
+        &egui::Context::default(),
+        root,
+        Regex::new(pattern).unwrap(),
+    );
+    for _ in 0..500 {
+        search.poll();
+        if !search.searching {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(!search.searching, "the search did not finish");
+    let mut results = search.results.clone();
+    results.sort_by(|a, b| a.path.cmp(&b.path));
+    results
+}
+
+#[test]
+fn ignored_and_binary_files_are_skipped() {
+    let root = project("ignored");
+    let results = search(&root, "needle");
+    let paths: Vec<PathBuf> = results
+        .iter()
+        .map(|file| file.path.strip_prefix(&root).unwrap().to_path_buf())
+        .collect();
+    assert_eq!(
+        paths,
+        [Path::new("src/main.rs"), Path::new("src/notes.txt")]
+    );
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn hits_have_their_line_and_char_column() {
+    let root = project("hits");
+    let results = search(&root, "needle");
+    let hits = &results[0].hits;
+    assert_eq!(
+        hits[0],
+        FolderHit {
+            line: 1,
+            column: 8,
+            preview: "let needle = 1; // needle".into(),
+        }
+    );
+    assert_eq!((hits[1].line, hits[1].column), (1, 23));
+    assert_eq!(results[1].hits[0].column, 2);
+
+    let mut total = FolderSearch::default();
+    total.results = results;
+    assert_eq!(total.hit_count(), 3);
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn hits_in_a_text() {
+    let pattern = Regex::new("a*").unwrap();
+    // empty matches are no hits
+    assert_eq!(search_text(&pattern, "bab\n\naa").len(), 2);
+
+    let long = format!("   {}x", "y".repeat(MAX_PREVIEW_CHARS));
+    let hits = search_text(&Regex::new("x").unwrap(), &long);
+    assert_eq!(hits[0].column, MAX_PREVIEW_CHARS + 3);
+    assert_eq!(hits[0].preview.chars().count(), MAX_PREVIEW_CHARS);
+
+    let many = "x".repeat(MAX_HITS_PER_FILE + 10);
+    assert_eq!(
+        search_text(&Regex::new("x").unwrap(), &many).len(),
+        MAX_HITS_PER_FILE
+    );
+}
@@ -10,3 +10,12 @@ pub use terminal::*;
 
 mod project_mode;
 pub use project_mode::*;
+
+mod folder_search;
+pub use folder_search::*;
+
+mod problems;
+pub use problems::*;
+
+mod tasks;
+pub use tasks::*;
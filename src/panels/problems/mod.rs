@@ -0,0 +1,239 @@
+#[cfg(test)]
+mod tests;
+
+use eframe::egui;
+use serde::Deserialize;
+use std::{
+    io::{self, BufRead, BufReader},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::mpsc,
+    thread,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+/// A compiler diagnostic, positions are zero based lines and char columns.
+/// Severities count like the language server ones: 1 error, 2 warning, 3 note, 4 help.
+pub struct Problem {
+    pub path: PathBuf,
+    pub start: (usize, usize),
+    pub end: (usize, usize),
+    pub severity: u8,
+    pub message: String,
+}
+
+#[derive(Deserialize)]
+struct CargoMessage {
+    reason: String,
+    #[serde(default)]
+    manifest_path: PathBuf,
+    message: Option<CompilerMessage>,
+}
+
+#[derive(Deserialize)]
+struct CompilerMessage {
+    message: String,
+    level: String,
+    spans: Vec<Span>,
+}
+
+#[derive(Deserialize)]
+struct Span {
+    file_name: PathBuf,
+    line_start: usize,
+    line_end: usize,
+    column_start: usize,
+    column_end: usize,
+    is_primary: bool,
+}
+
+/// Problems found by `cargo check --message-format=json` in the workspace of a file.
+/// Cargo runs on its own thread and the problems are sent as it prints them.
+pub struct Problems {
+    pub visible: bool,
+    pub root: PathBuf,
+    pub items: Vec<Problem>,
+    pub running: bool,
+    pub error: Option<String>,
+    pub selected: Option<usize>,
+    receiver: Option<mpsc::Receiver<Problem>>,
+    child: Option<Child>,
+}
+
+impl Default for Problems {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            root: PathBuf::new(),
+            items: vec![],
+            running: false,
+            error: None,
+            selected: None,
+            receiver: None,
+            child: None,
+        }
+    }
+}
+
+impl Problems {
+    /// Check the cargo project holding `path`, cargo finds the workspace from there
+    pub fn check(&mut self, ctx: &egui::Context, path: &Path) {
+        self.stop();
+        self.items.clear();
+        self.selected = None;
+        self.error = None;
+
+        let Some(root) = path
+            .ancestors()
+            .find(|dir| dir.join("Cargo.toml").is_file())
+        else {
+            self.error = Some(format!("No Cargo.toml above {}", path.display()));
+            return;
+        };
+        self.root = root.to_path_buf();
+        if let Err(err) = self.spawn(ctx) {
+            self.error = Some(format!("Could not run cargo : {}", err));
+        }
+    }
+
+    fn spawn(&mut self, ctx: &egui::Context) -> Result<(), io::Error> {
+        let mut child = Command::new("cargo")
+            .args(["check", "--message-format=json"])
+            .current_dir(&self.root)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| io::Error::other("Failed to open stdout"))?;
+
+        let (sender, receiver) = mpsc::channel();
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                let Some(problem) = parse_message(&line) else {
+                    continue;
+                };
+                if sender.send(problem).is_err() {
+                    return;
+                }
+                ctx.request_repaint();
+            }
+            ctx.request_repaint();
+        });
+
+        self.child = Some(child);
+        self.receiver = Some(receiver);
+        self.running = true;
+        Ok(())
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        self.receiver = None;
+        self.running = false;
+    }
+
+    /// Collect the problems found since the last call
+    pub fn poll(&mut self) {
+        let Some(receiver) = &self.receiver else {
+            return;
+        };
+        loop {
+            match receiver.try_recv() {
+                // the same message comes once per target sharing the file
+                Ok(problem) => {
+                    if !self.items.contains(&problem) {
+                        self.items.push(problem);
+                    }
+                }
+                Err(mpsc::TryRecvError::Empty) => return,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.stop();
+                    return;
+                }
+            }
+        }
+    }
+
+    pub fn count(&self, severity: u8) -> usize {
+        self.items
+            .iter()
+            .filter(|problem| problem.severity == severity)
+            .count()
+    }
+
+    pub fn in_file<'a>(&'a self, path: &'a Path) -> impl Iterator<Item = &'a Problem> {
+        self.items
+            .iter()
+            .filter(move |problem| problem.path == path)
+    }
+
+    /// Select the next or previous problem, wrapping around the list
+    pub fn select(&mut self, forward: bool) -> Option<&Problem> {
+        if self.items.is_empty() {
+            return None;
+        }
+        let len = self.items.len();
+        let index = match (self.selected, forward) {
+            (None, true) => 0,
+            (None, false) => len - 1,
+            (Some(index), true) => (index + 1) % len,
+            (Some(index), false) => (index + len - 1) % len,
+        };
+        self.selected = Some(index);
+        self.items.get(index)
+    }
+}
+
+impl Drop for Problems {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// The problem in a line of cargo json output, if it is a diagnostic pointing at a file
+pub fn parse_message(line: &str) -> Option<Problem> {
+    let cargo_message: CargoMessage = serde_json::from_str(line).ok()?;
+    if cargo_message.reason != "compiler-message" {
+        return None;
+    }
+    let message = cargo_message.message?;
+    let span = message.spans.iter().find(|span| span.is_primary)?;
+    let severity = match message.level.as_str() {
+        "error" | "error: internal compiler error" => 1,
+        "warning" => 2,
+        "note" | "failure-note" => 3,
+        _ => 4,
+    };
+
+    // span paths are relative to the workspace root, which may be above the package
+    let manifest_dir = cargo_message
+        .manifest_path
+        .parent()
+        .unwrap_or(Path::new(""));
+    let path = manifest_dir
+        .ancestors()
+        .map(|dir| dir.join(&span.file_name))
+        .find(|path| path.is_file())
+        .unwrap_or_else(|| manifest_dir.join(&span.file_name));
+
+    Some(Problem {
+        path,
+        start: (
+            span.line_start.saturating_sub(1),
+            span.column_start.saturating_sub(1),
+        ),
+        end: (
+            span.line_end.saturating_sub(1),
+            span.column_end.saturating_sub(1),
+        ),
+        severity,
+        message: message.message,
+    })
+}
@@ -0,0 +1,47 @@
+use super::*;
+
+const WARNING: &str = r#"{"reason":"compiler-message","package_id":"calcifer 1.4.0","manifest_path":"/nowhere/calcifer/Cargo.toml","target":{"name":"calcifer"},"message":{"rendered":"warning: unused variable: `x`\n","children":[],"code":{"code":"unused_variables","explanation":null},"level":"warning","message":"unused variable: `x`","spans":[{"byte_end":420,"byte_start":419,"column_end":10,"column_start":9,"expansion":null,"file_name":"src/main.rs","is_primary":true,"label":null,"line_end":12,"line_start":12,"suggested_replacement":null,"suggestion_applicability":null,"text":[]}]}}"#;
+
+#[test]
+fn compiler_message_to_problem() {
+    assert_eq!(
+        parse_message(WARNING),
+        Some(Problem {
+            path: PathBuf::from("/nowhere/calcifer/src/main.rs"),
+            start: (11, 8),
+            end: (11, 9),
+            severity: 2,
+            message: "unused variable: `x`".into(),
+        })
+    );
+}
+
+#[test]
+fn other_messages_are_skipped() {
+    let artifact = r#"{"reason":"compiler-artifact","package_id":"calcifer 1.4.0","manifest_path":"/nowhere/Cargo.toml"}"#;
+    assert_eq!(parse_message(artifact), None);
+    assert_eq!(
+        parse_message(r#"{"reason":"build-finished","success":true}"#),
+        None
+    );
+    assert_eq!(parse_message("   Compiling calcifer v1.4.0"), None);
+
+    // "aborting due to previous error" has no span
+    let summary = r#"{"reason":"compiler-message","manifest_path":"/nowhere/Cargo.toml","message":{"level":"error","message":"aborting due to 1 previous error","spans":[]}}"#;
+    assert_eq!(parse_message(summary), None);
+}
+
+#[test]
+fn navigation_wraps_around() {
+    let mut problems = Problems::default();
+    assert!(problems.select(true).is_none());
+
+    let problem = parse_message(WARNING).unwrap();
+    problems.items = vec![problem.clone(), problem.clone(), problem];
+    problems.select(false);
+    assert_eq!(problems.selected, Some(2));
+    problems.select(true);
+    assert_eq!(problems.selected, Some(0));
+    problems.select(true);
+    assert_eq!(problems.selected, Some(1));
+}
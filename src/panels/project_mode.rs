@@ -5,7 +5,6 @@ use std::{
     sync::atomic::{AtomicUsize, Ordering},
 };
 
-use crate::core::hex_str_to_color;
 use crate::editor::ColorTheme;
 use crate::sub_windows;
 use crate::MAX_PROJECT_COLUMNS;
@@ -122,7 +121,7 @@ fn get_id() -> usize {
     COUNTER.fetch_add(1, Ordering::Relaxed)
 }
 
-pub fn draw_project(ui: &mut egui::Ui, theme: ColorTheme, project: &mut Project) {
+pub fn draw_project(ui: &mut egui::Ui, theme: &ColorTheme, project: &mut Project) {
     ui.columns(
         max(MAX_PROJECT_COLUMNS, project.categories.len() + 1),
         |uis| {
@@ -153,20 +152,16 @@ pub fn draw_project(ui: &mut egui::Ui, theme: ColorTheme, project: &mut Project)
                             row: item_index,
                         })
                     {
-                        ui.style_mut().visuals.override_text_color =
-                            Some(hex_str_to_color(theme.bg));
+                        ui.style_mut().visuals.override_text_color = Some(theme.bg());
                         ui.add(
                             egui::Button::new(item.name.clone())
-                                .fill(hex_str_to_color(theme.functions)),
+                                .fill(theme.color(&theme.functions)),
                         );
                     } else {
                         ui.style_mut().visuals.override_text_color =
-                            Some(hex_str_to_color(theme.literals));
+                            Some(theme.color(&theme.literals));
                         if ui
-                            .add(
-                                egui::Button::new(item.name.clone())
-                                    .fill(hex_str_to_color(theme.bg)),
-                            )
+                            .add(egui::Button::new(item.name.clone()).fill(theme.bg()))
                             .clicked()
                         {
                             project.selected_item = Location {
@@ -176,7 +171,7 @@ pub fn draw_project(ui: &mut egui::Ui, theme: ColorTheme, project: &mut Project)
                         }
                     }
                 }
-                ui.style_mut().visuals.override_text_color = Some(hex_str_to_color(theme.literals));
+                ui.style_mut().visuals.override_text_color = Some(theme.color(&theme.literals));
                 if category.name != "+" && ui.add(egui::Button::new("+")).clicked() {
                     project.categories[category_index]
                         .content
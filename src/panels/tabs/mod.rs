@@ -0,0 +1,143 @@
+#[cfg(test)]
+mod tests;
+
+use eframe::egui::text_edit::CCursorRange;
+use std::{
+    collections::BTreeSet, fs, fs::read_to_string, path::Path, path::PathBuf, time::SystemTime,
+};
+
+use crate::editor::{Indentation, UndoHistory};
+
+#[derive(Clone, PartialEq)]
+pub struct Tab {
+    pub path: PathBuf,
+    pub code: String,
+    pub language: String,
+    pub language_override: Option<String>,
+    pub indentation: Indentation,
+    pub saved: bool,
+    pub last_modified: Option<SystemTime>,
+    pub scroll_offset: f32,
+    pub last_cursor: Option<CCursorRange>,
+    pub pending_cursor: Option<usize>,
+    pub history: UndoHistory,
+    /// Cursors besides `last_cursor`, the one the text edit shows
+    pub extra_cursors: Vec<CCursorRange>,
+    /// Zero based lines whose block is folded
+    pub folds: BTreeSet<usize>,
+}
+
+impl Default for Tab {
+    fn default() -> Self {
+        let code = "// Hello there, Master";
+        Self {
+            path: "untitled".into(),
+            code: code.into(),
+            language: "rs".into(),
+            language_override: None,
+            indentation: Indentation::Tabs,
+            saved: false,
+            last_modified: None,
+            scroll_offset: 0.0,
+            last_cursor: None,
+            pending_cursor: None,
+            history: UndoHistory::new(code),
+            extra_cursors: vec![],
+            folds: BTreeSet::new(),
+        }
+    }
+}
+
+impl Tab {
+    pub fn new(path: PathBuf) -> Self {
+        let text = read_file_contents(&path);
+        let file_path = format_file_path(&path, &text);
+        let extension = file_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default();
+
+        Self {
+            path: file_path.clone(),
+            indentation: Indentation::detect(&text),
+            history: UndoHistory::new(&text),
+            extra_cursors: vec![],
+            folds: BTreeSet::new(),
+            code: text,
+            language: extension.into(),
+            language_override: None,
+            saved: true,
+            last_modified: modified_time(&file_path),
+            scroll_offset: 0.0,
+            last_cursor: None,
+            pending_cursor: None,
+        }
+    }
+
+    pub fn get_name(&self) -> String {
+        self.path
+            .file_name()
+            .map_or("untitled".to_string(), |name| {
+                name.to_string_lossy().to_string()
+            })
+    }
+
+    pub fn refresh(&mut self) {
+        let text = read_file_contents(&self.path);
+        let file_path = format_file_path(&self.path, &text);
+
+        self.indentation = Indentation::detect(&text);
+        self.code = text;
+        self.last_modified = modified_time(&file_path);
+        self.path = file_path;
+        self.saved = true;
+    }
+
+    /// Keep the text of the tab over the file on disk, until the file changes again
+    pub fn keep_over_disk(&mut self) {
+        self.last_modified = modified_time(&self.path);
+    }
+
+    /// Whether the file on disk differs from the last version read or written by this tab
+    pub fn changed_on_disk(&mut self) -> bool {
+        let modified = modified_time(&self.path);
+        if modified.is_none() || modified == self.last_modified {
+            return false;
+        }
+        match read_to_string(&self.path) {
+            Ok(text) if text != self.code => true,
+            Ok(_) => {
+                self.last_modified = modified;
+                false
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+pub enum MouseHolder {
+    TabHolder(usize),
+    None,
+}
+
+fn read_file_contents(path: &Path) -> String {
+    let error_type = "reading file";
+    read_to_string(path)
+        .map_err(|err| format!("// Error {}: {}", error_type, err))
+        .unwrap_or_else(|err_msg| err_msg)
+}
+
+fn format_file_path(path: &Path, contents: &str) -> PathBuf {
+    let error_type = "reading file";
+    if contents.contains(&format!("Error {}", error_type)) {
+        "untitled".into()
+    } else {
+        path.to_path_buf()
+    }
+}
+
+pub fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
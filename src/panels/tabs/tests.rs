@@ -0,0 +1,45 @@
+use super::*;
+use std::{env, time::Duration};
+
+fn touch(path: &Path, text: &str, modified: SystemTime) {
+    fs::write(path, text).unwrap();
+    fs::File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(modified)
+        .unwrap();
+}
+
+#[test]
+fn changed_on_disk_only_for_other_text() {
+    let path = env::temp_dir().join(format!("calcifer_tab_{}.txt", std::process::id()));
+    let start = SystemTime::now() - Duration::from_secs(60);
+    touch(&path, "mine", start);
+    let mut tab = Tab::new(path.clone());
+    assert!(!tab.changed_on_disk());
+
+    // written again with the same text
+    touch(&path, "mine", start + Duration::from_secs(1));
+    assert!(!tab.changed_on_disk());
+    assert_eq!(tab.last_modified, modified_time(&path));
+
+    touch(&path, "theirs", start + Duration::from_secs(2));
+    assert!(tab.changed_on_disk());
+    // still reported until the tab takes the text
+    assert!(tab.changed_on_disk());
+    tab.refresh();
+    assert_eq!(tab.code, "theirs");
+    assert!(!tab.changed_on_disk());
+
+    // kept over the file, asked again only once it changes anew
+    touch(&path, "other", start + Duration::from_secs(3));
+    assert!(tab.changed_on_disk());
+    tab.keep_over_disk();
+    assert!(!tab.changed_on_disk());
+    touch(&path, "another", start + Duration::from_secs(4));
+    assert!(tab.changed_on_disk());
+
+    fs::remove_file(&path).unwrap();
+    assert!(!tab.changed_on_disk());
+}
@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod tests;
+
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use super::shell_quote;
+
+/// Where a project declares its tasks, relative to its root
+pub const TASKS_FILE: &str = ".calcifer/tasks.toml";
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default)]
+/// A named command declared by a project, as in
+/// ```toml
+/// [[task]]
+/// name = "test"
+/// command = "cargo test"
+/// cwd = "crates/core"
+/// env = { RUST_BACKTRACE = "1" }
+/// languages = ["rs"]
+/// ```
+pub struct Task {
+    pub name: String,
+    pub command: String,
+    /// Relative to the project root, the root itself when missing
+    pub cwd: Option<PathBuf>,
+    pub env: BTreeMap<String, String>,
+    /// Tab languages the task is offered for, all of them when empty
+    pub languages: Vec<String>,
+    /// Folder holding `.calcifer`, set when loading
+    #[serde(skip)]
+    pub root: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct TaskFile {
+    #[serde(default)]
+    task: Vec<Task>,
+}
+
+impl Task {
+    pub fn applies_to(&self, language: &str) -> bool {
+        self.languages.is_empty() || self.languages.iter().any(|name| name == language)
+    }
+
+    pub fn directory(&self) -> PathBuf {
+        match &self.cwd {
+            Some(cwd) => self.root.join(cwd),
+            None => self.root.clone(),
+        }
+    }
+
+    /// The line typed in the terminal, a subshell keeps the directory
+    /// and the variables of the task out of the session
+    pub fn shell_command(&self) -> String {
+        let mut line = format!("(cd {}", shell_quote(&self.directory().to_string_lossy()));
+        for (name, value) in &self.env {
+            line.push_str(&format!(" && export {}={}", name, shell_quote(value)));
+        }
+        format!("{} && {})", line, self.command)
+    }
+}
+
+/// The nearest tasks file in the folders above `path`
+pub fn find_tasks(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .map(|folder| folder.join(TASKS_FILE))
+        .find(|file| file.is_file())
+}
+
+pub fn load_tasks(file: &Path) -> Result<Vec<Task>, io::Error> {
+    let content = fs::read_to_string(file)?;
+    let root = file
+        .parent()
+        .and_then(|folder| folder.parent())
+        .unwrap_or(Path::new("/"));
+    parse_tasks(&content, root)
+}
+
+/// Tasks without a command are dropped, unnamed ones are called by their command
+pub fn parse_tasks(content: &str, root: &Path) -> Result<Vec<Task>, io::Error> {
+    let file: TaskFile = toml::from_str(content).map_err(io::Error::other)?;
+    Ok(file
+        .task
+        .into_iter()
+        .filter(|task| !task.command.trim().is_empty())
+        .map(|task| Task {
+            name: if task.name.is_empty() {
+                task.command.clone()
+            } else {
+                task.name.clone()
+            },
+            root: root.to_path_buf(),
+            ..task
+        })
+        .collect())
+}
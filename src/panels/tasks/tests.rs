@@ -0,0 +1,55 @@
+use super::*;
+use std::process::Command;
+
+const TASKS: &str = r#"
+[[task]]
+name = "test"
+command = "cargo test"
+cwd = "crates/core"
+env = { RUST_BACKTRACE = "1" }
+languages = ["rs"]
+
+[[task]]
+command = "make"
+
+[[task]]
+name = "nothing to run"
+"#;
+
+#[test]
+fn tasks_file_is_parsed() {
+    let tasks = parse_tasks(TASKS, Path::new("/project")).unwrap();
+    assert_eq!(tasks.len(), 2);
+    assert_eq!(tasks[0].name, "test");
+    assert_eq!(tasks[0].directory(), PathBuf::from("/project/crates/core"));
+    assert_eq!(tasks[0].env["RUST_BACKTRACE"], "1");
+    assert!(tasks[0].applies_to("rs"));
+    assert!(!tasks[0].applies_to("py"));
+
+    assert_eq!(tasks[1].name, "make");
+    assert_eq!(tasks[1].directory(), PathBuf::from("/project"));
+    assert!(tasks[1].applies_to("py"));
+
+    assert!(parse_tasks("[[task]]\ncommand = 3", Path::new("/")).is_err());
+}
+
+#[test]
+fn task_runs_in_a_subshell() {
+    let root = std::env::temp_dir();
+    let task = Task {
+        command: "echo \"$GREETING\" && pwd".into(),
+        env: BTreeMap::from([("GREETING".to_string(), "it's me".to_string())]),
+        root: root.clone(),
+        ..Task::default()
+    };
+    let script = format!(
+        "cd /; {}; echo \"[$GREETING]\" && pwd",
+        task.shell_command()
+    );
+    let output = Command::new("sh").args(["-c", &script]).output().unwrap();
+    let expected = format!(
+        "it's me\n{}\n[]\n/\n",
+        root.canonicalize().unwrap().display()
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), expected);
+}
@@ -0,0 +1,121 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// Commands kept for each working directory
+pub const HISTORY_LIMIT: usize = 500;
+
+#[derive(Default)]
+/// Commands typed in the terminal, oldest first for each working directory,
+/// browsed with Up/Down and searched backwards as with Ctrl+R in a shell
+pub struct CommandHistory {
+    commands: BTreeMap<PathBuf, Vec<String>>,
+    /// How many commands Up went back, 0 is the line being typed
+    back: usize,
+    /// The line being typed when browsing started, given back by Down
+    draft: String,
+    pub search: Option<HistorySearch>,
+}
+
+#[derive(Default)]
+pub struct HistorySearch {
+    pub query: String,
+    /// Newer matches passed over with Ctrl+R
+    skip: usize,
+}
+
+impl HistorySearch {
+    /// Back to the newest match, once the query changed
+    pub fn restart(&mut self) {
+        self.skip = 0;
+    }
+}
+
+impl CommandHistory {
+    pub fn new(commands: BTreeMap<PathBuf, Vec<String>>) -> Self {
+        Self {
+            commands,
+            ..Default::default()
+        }
+    }
+
+    pub fn commands(&self) -> &BTreeMap<PathBuf, Vec<String>> {
+        &self.commands
+    }
+
+    /// Like the shell, lines starting with a space are not kept,
+    /// and a command typed again moves to the end
+    pub fn push(&mut self, cwd: &Path, command: &str) {
+        self.back = 0;
+        self.search = None;
+        if command.trim().is_empty() || command.starts_with(' ') {
+            return;
+        }
+        let list = self.commands.entry(cwd.to_path_buf()).or_default();
+        list.retain(|old| old != command);
+        list.push(command.to_string());
+        if list.len() > HISTORY_LIMIT {
+            list.drain(..list.len() - HISTORY_LIMIT);
+        }
+    }
+
+    /// The command before the one shown, `line` is kept for when Down comes back
+    pub fn previous(&mut self, cwd: &Path, line: &str) -> Option<String> {
+        let command = self.get(cwd, self.back + 1)?;
+        if self.back == 0 {
+            self.draft = line.to_string();
+        }
+        self.back += 1;
+        Some(command)
+    }
+
+    pub fn next(&mut self, cwd: &Path) -> Option<String> {
+        if self.back == 0 {
+            return None;
+        }
+        self.back -= 1;
+        if self.back == 0 {
+            return Some(std::mem::take(&mut self.draft));
+        }
+        self.get(cwd, self.back)
+    }
+
+    /// Ctrl+R, starts a search or goes to the next older match
+    pub fn search_older(&mut self, cwd: &Path) {
+        match &mut self.search {
+            None => self.search = Some(HistorySearch::default()),
+            Some(search) => {
+                search.skip += 1;
+                if self.search_match(cwd).is_none() {
+                    self.search.as_mut().unwrap().skip -= 1;
+                }
+            }
+        }
+    }
+
+    /// The most recent command containing the query, past the skipped ones
+    pub fn search_match(&self, cwd: &Path) -> Option<&str> {
+        let search = self.search.as_ref()?;
+        self.commands
+            .get(cwd)?
+            .iter()
+            .rev()
+            .filter(|command| command.contains(&search.query))
+            .nth(search.skip)
+            .map(String::as_str)
+    }
+
+    /// Leave the search, with the command it found if any
+    pub fn end_search(&mut self, cwd: &Path) -> Option<String> {
+        let found = self.search_match(cwd).map(str::to_string);
+        self.search = None;
+        found
+    }
+
+    /// The command `back` steps before the line being typed
+    fn get(&self, cwd: &Path, back: usize) -> Option<String> {
+        let list = self.commands.get(cwd)?;
+        list.get(list.len().checked_sub(back)?).cloned()
+    }
+}
@@ -0,0 +1,60 @@
+use eframe::egui::{Event, Key, Modifiers};
+
+/// Bytes a terminal sends for an input event, `None` for events it ignores
+pub fn event_bytes(event: &Event, application_cursor: bool) -> Option<Vec<u8>> {
+    match event {
+        Event::Text(text) | Event::Paste(text) => Some(text.as_bytes().to_vec()),
+        // the platform turns Ctrl+C and Ctrl+X into clipboard events
+        Event::Copy => Some(vec![0x03]),
+        Event::Cut => Some(vec![0x18]),
+        Event::Key {
+            key,
+            pressed: true,
+            modifiers,
+            ..
+        } => key_bytes(*key, *modifiers, application_cursor),
+        _ => None,
+    }
+}
+
+fn key_bytes(key: Key, modifiers: Modifiers, application_cursor: bool) -> Option<Vec<u8>> {
+    let name = key.name().as_bytes();
+    if modifiers.ctrl && name.len() == 1 && name[0].is_ascii_alphabetic() {
+        return Some(vec![name[0].to_ascii_uppercase() - b'A' + 1]);
+    }
+    let cursor = |code: char| {
+        let prefix = if application_cursor { "\x1bO" } else { "\x1b[" };
+        format!("{}{}", prefix, code)
+    };
+    let sequence = match key {
+        Key::Enter => "\r".to_string(),
+        Key::Tab if modifiers.shift => "\x1b[Z".to_string(),
+        Key::Tab => "\t".to_string(),
+        Key::Backspace => "\x7f".to_string(),
+        Key::Escape => "\x1b".to_string(),
+        Key::ArrowUp => cursor('A'),
+        Key::ArrowDown => cursor('B'),
+        Key::ArrowRight => cursor('C'),
+        Key::ArrowLeft => cursor('D'),
+        Key::Home => cursor('H'),
+        Key::End => cursor('F'),
+        Key::Insert => "\x1b[2~".to_string(),
+        Key::Delete => "\x1b[3~".to_string(),
+        Key::PageUp => "\x1b[5~".to_string(),
+        Key::PageDown => "\x1b[6~".to_string(),
+        Key::F1 => "\x1bOP".to_string(),
+        Key::F2 => "\x1bOQ".to_string(),
+        Key::F3 => "\x1bOR".to_string(),
+        Key::F4 => "\x1bOS".to_string(),
+        Key::F5 => "\x1b[15~".to_string(),
+        Key::F6 => "\x1b[17~".to_string(),
+        Key::F7 => "\x1b[18~".to_string(),
+        Key::F8 => "\x1b[19~".to_string(),
+        Key::F9 => "\x1b[20~".to_string(),
+        Key::F10 => "\x1b[21~".to_string(),
+        Key::F11 => "\x1b[23~".to_string(),
+        Key::F12 => "\x1b[24~".to_string(),
+        _ => return None,
+    };
+    Some(sequence.into_bytes())
+}
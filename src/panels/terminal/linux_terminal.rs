@@ -1,193 +1,336 @@
 use crate::core::format_path;
 
+use super::{shell_quote, Line, Screen, PROMPT_MARKER};
 use arboard::Clipboard;
-use nix::fcntl::{fcntl, FcntlArg, OFlag};
+use eframe::egui;
+use nix::{
+    fcntl::{fcntl, FcntlArg, FdFlag},
+    pty::{openpty, Winsize},
+    sys::signal::{killpg, Signal},
+    unistd::{setsid, tcgetpgrp, Pid},
+};
 use std::{
-    env,
-    io::{BufRead, BufReader},
-    os::fd::AsRawFd,
+    fs::File,
+    io::{self, Read, Write},
+    os::{fd::AsRawFd, unix::process::CommandExt},
     path::{Path, PathBuf},
-    process::{Child, Command, Stdio},
+    process::{Child, Command},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
 };
 
-pub struct Buffer {
-    pub output_buffer: BufReader<std::process::ChildStdout>,
-    pub error_buffer: BufReader<std::process::ChildStderr>,
-    pub child: Child,
-}
+nix::ioctl_write_int_bad!(set_controlling_terminal, nix::libc::TIOCSCTTY);
+nix::ioctl_write_ptr_bad!(set_window_size, nix::libc::TIOCSWINSZ, Winsize);
 
-#[derive(Clone)]
-pub struct Line {
-    pub text: String,
-    pub error: bool,
-}
-
-impl Line {
-    fn output(text: String) -> Self {
-        Self {
-            text: remove_line_break(text),
-            error: false,
-        }
-    }
-    fn error(text: String) -> Self {
-        Self {
-            text: remove_line_break(text),
-            error: true,
-        }
-    }
-}
+const READ_BUFFER_SIZE: usize = 4096;
+/// How long an interrupted command has to exit before it is killed
+pub const STOP_TIMEOUT: Duration = Duration::from_secs(3);
 
 pub struct CommandEntry {
     pub env: String,
+    /// Where the command was typed, relative paths in its output start from there
+    pub cwd: PathBuf,
     pub command: String,
     pub result: Vec<Line>,
-    pub buffer: Option<Buffer>,
+    /// Lines dropped from the start of `result` to keep within the scrollback
+    pub dropped: usize,
     pub finished: bool,
+    /// `None` while running, or if the shell itself exited
+    pub status: Option<i32>,
 }
 
 impl CommandEntry {
-    pub fn new(env: String, command: String) -> Self {
-        let (buffer, result) = match execute(command.clone()) {
-            Ok(command_buffer) => (Some(command_buffer), vec![]),
-            Err(err) => (
-                None,
-                vec![Line::error(format!("failed to get results: {}", err))],
-            ),
-        };
-
+    pub fn new(cwd: &Path, command: String) -> Self {
         CommandEntry {
-            env,
+            env: format_path(cwd),
+            cwd: cwd.to_path_buf(),
             command,
-            result,
-            buffer,
+            result: vec![],
+            dropped: 0,
             finished: false,
+            status: None,
         }
     }
 
-    pub fn update(&mut self) {
-        if let Some(buffer) = &mut self.buffer {
-            let mut output = String::new();
-            loop {
-                let _ = buffer.output_buffer.read_line(&mut output);
-                if !output.to_string().is_empty() {
-                    self.result.push(Line::output(format!("{}\n", output)));
-                    output = "".to_string()
-                } else {
-                    break;
-                }
-            }
+    pub fn copy_output(&self) {
+        let text: Vec<String> = self.result.iter().map(|line| line.text()).collect();
+        let mut _clipboard = Clipboard::new().expect("Failed to initialize clipboard");
+        _clipboard.set_text(text.join("\n")).unwrap();
+    }
+}
+
+/// A shell running in a pseudo terminal, kept alive between commands
+/// so that `cd`, `export` and interactive programs behave as in any terminal
+pub struct TerminalSession {
+    pub name: String,
+    pub cwd: PathBuf,
+    pub entries: Vec<CommandEntry>,
+    pub screen: Screen,
+    pub exited: bool,
+    parser: vte::Parser,
+    master: File,
+    child: Child,
+    receiver: mpsc::Receiver<Vec<u8>>,
+    ready: bool,
+    /// Process group sent SIGINT, killed if still running after `STOP_TIMEOUT`
+    stopping: Option<(Pid, Instant)>,
+}
+
+impl TerminalSession {
+    /// `~` in the shell resolves to `home`
+    pub fn new(
+        ctx: &egui::Context,
+        name: String,
+        cwd: &Path,
+        home: &Path,
+    ) -> Result<Self, io::Error> {
+        let screen = Screen::default();
+        let pty = openpty(&window_size(screen.size), None)?;
+        let master = File::from(pty.master);
+        let slave = File::from(pty.slave);
+        for file in [&master, &slave] {
+            fcntl(file.as_raw_fd(), FcntlArg::F_SETFD(FdFlag::FD_CLOEXEC))?;
+        }
+
+        // without line editing the shell reads in cooked mode, the terminal does the echo
+        let (shell, args) = if Path::new("/bin/bash").exists() {
+            ("/bin/bash", vec!["--noediting", "-i"])
+        } else {
+            ("/bin/sh", vec!["-i"])
+        };
+        let mut command = Command::new(shell);
+        command
+            .args(args)
+            .current_dir(cwd)
+            .env("TERM", "xterm-256color")
+            .env("HOME", home)
+            .stdin(slave.try_clone()?)
+            .stdout(slave.try_clone()?)
+            .stderr(slave);
+        unsafe {
+            command.pre_exec(|| {
+                setsid()?;
+                set_controlling_terminal(0, 0)?;
+                Ok(())
+            });
+        }
+        let child = command.spawn()?;
+        drop(command);
 
-            let mut error = String::new();
+        let (sender, receiver) = mpsc::channel();
+        let mut reader = master.try_clone()?;
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            let mut buffer = [0; READ_BUFFER_SIZE];
             loop {
-                let _ = buffer.error_buffer.read_line(&mut error);
-                if !error.to_string().is_empty() {
-                    self.result.push(Line::error(format!("{}\n", error)));
-                    error = "".to_string()
-                } else {
-                    break;
+                match reader.read(&mut buffer) {
+                    Ok(0) => break,
+                    Ok(size) => {
+                        if sender.send(buffer[..size].to_vec()).is_err() {
+                            break;
+                        }
+                        ctx.request_repaint();
+                    }
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    // the shell exited and closed its side
+                    Err(_) => break,
                 }
             }
+            ctx.request_repaint();
+        });
 
-            if let Ok(Some(_exit_status)) = buffer.child.try_wait() {
-                //self.result.push(Line::output(format!("Command finished with status: {:?}\n", exit_status)));
-                self.finished = true;
-            }
+        let mut session = Self {
+            name,
+            cwd: cwd.to_path_buf(),
+            entries: vec![],
+            screen,
+            exited: false,
+            parser: vte::Parser::new(),
+            master,
+            child,
+            receiver,
+            ready: false,
+            stopping: None,
+        };
+        // the prompt becomes a marker carrying the exit status and the working directory,
+        // the leading space keeps this line out of the shell history
+        session.write(
+            format!(
+                " PS1=\"$(printf '\\033]{};{};')\"'$?;$PWD'\"$(printf '\\007')\"; PS2=''; unset PROMPT_COMMAND\n",
+                PROMPT_MARKER,
+                session.screen.prompt_token()
+            )
+            .as_bytes(),
+        );
+        Ok(session)
+    }
+
+    /// Whether the shell is busy, the input then goes to the running program
+    pub fn is_running(&self) -> bool {
+        !self.ready || self.entries.iter().any(|entry| !entry.finished)
+    }
+
+    pub fn send_command(&mut self, command: String) {
+        if !self.is_running() {
+            self.screen.expect_echo(&command);
         }
+        self.write(format!("{}\n", command).as_bytes());
+        self.entries.push(CommandEntry::new(&self.cwd, command));
     }
 
-    pub fn copy_error_code(&self) {
-        let mut txt: String = "".to_string();
-        for line in self.result.iter() {
-            if line.error {
-                txt.push_str(&format!("{}\n", line.text));
-            }
+    pub fn is_stopping(&self) -> bool {
+        self.stopping.is_some()
+    }
+
+    /// Interrupt the program running in the foreground, as Ctrl+C would,
+    /// `update` kills it if it ignores the signal
+    pub fn stop(&mut self) {
+        let Ok(group) = tcgetpgrp(self.master.as_raw_fd()) else {
+            return;
+        };
+        // the shell is in the foreground, nothing to stop
+        if group.as_raw() == self.child.id() as i32 {
+            return;
+        }
+        if killpg(group, Signal::SIGINT).is_ok() {
+            self.stopping = Some((group, Instant::now()));
         }
-        let mut _clipboard = Clipboard::new().expect("Failed to initialize clipboard");
-        _clipboard.set_text(txt).unwrap();
     }
-}
 
-pub fn send_command(command: String) -> CommandEntry {
-    let env = format_path(&env::current_dir().unwrap_or_else(|_| PathBuf::from("/")));
+    /// `cd` without adding an entry, its output is dropped with the next prompt
+    pub fn change_directory(&mut self, path: &Path) {
+        let quoted = shell_quote(&path.to_string_lossy());
+        self.write(format!(" cd {}\n", quoted).as_bytes());
+    }
 
-    if command.len() < 2 {
-        return CommandEntry::new(env, command);
+    pub fn write(&mut self, bytes: &[u8]) {
+        let _ = self.master.write_all(bytes);
     }
 
-    if &command[..2] != "cd" {
-        return CommandEntry::new(env, command);
+    /// Output of an entry, the running one is read from the screen
+    pub fn output(&self, index: usize) -> &[Line] {
+        let entry = &self.entries[index];
+        let running = self.entries.iter().position(|entry| !entry.finished);
+        if !entry.finished && running == Some(index) {
+            &self.screen.normal.lines
+        } else {
+            &entry.result
+        }
     }
 
-    if command.len() < 4 {
-        let mut entry =
-            CommandEntry::new(env, "echo Invalid cd, should provide path >&2".to_string());
-        entry.command = command;
-        return entry;
+    /// Keep about `lines` rows of output, counting one row per command
+    pub fn set_scrollback(&mut self, lines: usize) {
+        if self.screen.scrollback != lines {
+            self.screen.scrollback = lines;
+            self.trim_scrollback();
+        }
+    }
+
+    pub fn resize(&mut self, cols: usize, rows: usize) {
+        if self.screen.size == (cols.max(1), rows.max(1)) {
+            return;
+        }
+        self.screen.resize(cols, rows);
+        let size = window_size(self.screen.size);
+        unsafe {
+            let _ = set_window_size(self.master.as_raw_fd(), &size);
+        }
+    }
+
+    /// Parse what the shell wrote since the last call
+    pub fn update(&mut self) {
+        if let Some((group, since)) = self.stopping {
+            if since.elapsed() >= STOP_TIMEOUT {
+                let _ = killpg(group, Signal::SIGKILL);
+                self.stopping = None;
+            }
+        }
+        loop {
+            match self.receiver.try_recv() {
+                Ok(bytes) => self.feed(&bytes),
+                Err(mpsc::TryRecvError::Empty) => return,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    if !self.exited {
+                        self.exited = true;
+                        let _ = self.child.try_wait();
+                        self.finish_entry(None);
+                    }
+                    return;
+                }
+            }
+        }
     }
 
-    let path_append = command[3..].replace('~', "/home/penwing");
-    let path = Path::new(&path_append);
+    fn feed(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.parser.advance(&mut self.screen, *byte);
+            if let Some(prompt) = self.screen.take_prompt() {
+                self.cwd = prompt.cwd;
+                if self.ready {
+                    self.finish_entry(Some(prompt.status));
+                } else {
+                    // whatever the shell printed while starting
+                    self.screen.take_output();
+                    self.ready = true;
+                }
+                if let Some(entry) = self.entries.iter().find(|entry| !entry.finished) {
+                    self.screen.expect_echo(&entry.command);
+                }
+            }
+        }
+    }
 
-    if format!("{}", path.display()) == "/" {
-        let mut entry = CommandEntry::new(env, "echo Root access denied >&2".to_string());
-        entry.command = command;
-        return entry;
+    fn finish_entry(&mut self, status: Option<i32>) {
+        self.stopping = None;
+        let dropped = self.screen.dropped();
+        let output = self.screen.take_output();
+        if let Some(entry) = self.entries.iter_mut().find(|entry| !entry.finished) {
+            entry.result = output;
+            entry.dropped = dropped;
+            entry.finished = true;
+            entry.status = status;
+        }
+        self.trim_scrollback();
     }
 
-    if env::set_current_dir(path).is_ok() {
-        let mut entry = CommandEntry::new(env, format!("echo Moved to : {}", path.display()));
-        entry.command = command;
-        entry
-    } else {
-        let mut entry = CommandEntry::new(
-            env,
-            format!("echo Could not find path : {} >&2", path.display()),
-        );
-        entry.command = command;
-        entry
+    /// Drop the oldest output past the scrollback, then the commands left without any
+    fn trim_scrollback(&mut self) {
+        let limit = self.screen.scrollback;
+        let mut total: usize = self
+            .entries
+            .iter()
+            .map(|entry| entry.result.len() + 1)
+            .sum();
+        while total > limit {
+            let Some(entry) = self.entries.first_mut().filter(|entry| entry.finished) else {
+                break;
+            };
+            let excess = total - limit;
+            if excess >= entry.result.len() {
+                total -= entry.result.len() + 1;
+                self.entries.remove(0);
+            } else {
+                entry.result.drain(..excess);
+                entry.dropped += excess;
+                total -= excess;
+            }
+        }
     }
 }
 
-pub fn execute(command: String) -> Result<Buffer, std::io::Error> {
-    let mut child = Command::new("sh")
-        .arg("-c")
-        .arg(command.clone())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()?;
-
-    let stdout = child
-        .stdout
-        .take()
-        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::Other, "Failed to open stdout"))?;
-    let stderr = child
-        .stderr
-        .take()
-        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::Other, "Failed to open stderr"))?;
-
-    let stdout_fd = stdout.as_raw_fd();
-    let stderr_fd = stderr.as_raw_fd();
-
-    fcntl(stdout_fd, FcntlArg::F_SETFL(OFlag::O_NONBLOCK))?;
-    fcntl(stderr_fd, FcntlArg::F_SETFL(OFlag::O_NONBLOCK))?;
-
-    let output_buffer = BufReader::new(stdout);
-    let error_buffer = BufReader::new(stderr);
-
-    Ok(Buffer {
-        output_buffer,
-        error_buffer,
-        child,
-    })
+impl Drop for TerminalSession {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
 }
 
-fn remove_line_break(input: String) -> String {
-    let mut text = input.clone();
-    while text.ends_with('\n') {
-        text.pop();
-        if text.ends_with('\r') {
-            text.pop();
-        }
+fn window_size((cols, rows): (usize, usize)) -> Winsize {
+    Winsize {
+        ws_row: rows as u16,
+        ws_col: cols as u16,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
     }
-    text
 }
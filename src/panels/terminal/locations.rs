@@ -0,0 +1,67 @@
+use regex::Regex;
+use std::{ops::Range, path::PathBuf, sync::OnceLock};
+
+use crate::editor::position;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// A `path:line[:col]` mention in terminal output, line and column count from one
+pub struct FileLocation {
+    /// Chars of the line the location is written on
+    pub range: Range<usize>,
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// `src/main.rs:12:5` as printed by rustc, gcc or grep -n. The path needs a slash or an
+/// extension starting with a letter, so that times and addresses are not taken for files.
+fn colon_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(
+            r"(?P<path>(?:[\w.~+-]*/)*[\w.+-]*\w\.[A-Za-z]\w*|(?:[\w.~+-]*/)+[\w.+-]+):(?P<line>\d+)(?::(?P<column>\d+))?",
+        )
+        .unwrap()
+    })
+}
+
+/// `File "script.py", line 3` as printed in python tracebacks
+fn python_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r#"File "(?P<path>[^"]+)", line (?P<line>\d+)"#).unwrap())
+}
+
+pub fn find_locations(text: &str) -> Vec<FileLocation> {
+    let mut locations = vec![];
+    for pattern in [colon_pattern(), python_pattern()] {
+        for captures in pattern.captures_iter(text) {
+            let found = captures.get(0).unwrap();
+            // urls like http://host.com:8080
+            let word_start = text[..found.start()]
+                .char_indices()
+                .rev()
+                .find(|(_, c)| c.is_whitespace())
+                .map_or(0, |(index, c)| index + c.len_utf8());
+            if text[word_start..found.end()].contains("://") {
+                continue;
+            }
+            let number = |name: &str| {
+                captures
+                    .name(name)
+                    .and_then(|number| number.as_str().parse::<usize>().ok())
+            };
+            let Some(line) = number("line").filter(|&line| line > 0) else {
+                continue;
+            };
+            locations.push(FileLocation {
+                range: position::byte_to_char(text, found.start())
+                    ..position::byte_to_char(text, found.end()),
+                path: PathBuf::from(&captures["path"]),
+                line,
+                column: number("column").unwrap_or(1).max(1),
+            });
+        }
+    }
+    locations.sort_by_key(|location| location.range.start);
+    locations
+}
@@ -8,3 +8,26 @@ pub use linux_terminal::*;
 mod windows_terminal;
 #[cfg(target_os = "windows")]
 pub use windows_terminal::*;
+
+mod history;
+pub use history::*;
+
+mod keys;
+pub use keys::*;
+
+mod locations;
+pub use locations::*;
+
+mod output;
+pub use output::*;
+
+mod screen;
+pub use screen::*;
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests;
+
+/// `text` as a single shell word, whatever quotes it holds
+pub fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
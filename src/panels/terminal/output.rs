@@ -0,0 +1,99 @@
+use std::ops::Range;
+
+use super::TerminalSession;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// What one row of the terminal output shows. All rows have the same height,
+/// so only the visible ones need to be laid out.
+pub enum OutputRow {
+    /// Space before each command
+    Gap,
+    /// Command of an entry, with its links and exit status
+    Header(usize),
+    /// Lines dropped from the start of an entry output
+    Dropped(usize),
+    /// Entry and line index in its output
+    Line(usize, usize),
+}
+
+pub fn output_rows(terminal: &TerminalSession) -> Vec<OutputRow> {
+    let mut rows = vec![];
+    for (index, entry) in terminal.entries.iter().enumerate() {
+        rows.push(OutputRow::Gap);
+        rows.push(OutputRow::Header(index));
+        if entry.dropped > 0 {
+            rows.push(OutputRow::Dropped(index));
+        }
+        let lines = terminal.output(index).len();
+        rows.extend((0..lines).map(|line| OutputRow::Line(index, line)));
+    }
+    rows
+}
+
+/// Text of a row as copied, links and status are left out
+pub fn row_text(terminal: &TerminalSession, row: OutputRow) -> String {
+    match row {
+        OutputRow::Gap | OutputRow::Dropped(_) => String::new(),
+        OutputRow::Header(index) => {
+            let entry = &terminal.entries[index];
+            format!("{} {}", entry.env, entry.command)
+        }
+        OutputRow::Line(index, line) => terminal
+            .output(index)
+            .get(line)
+            .map(|line| line.text())
+            .unwrap_or_default(),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// Output selected with the mouse, as (row, char column) from where the drag started
+pub struct OutputSelection {
+    pub anchor: (usize, usize),
+    pub head: (usize, usize),
+}
+
+impl OutputSelection {
+    pub fn new(cell: (usize, usize)) -> Self {
+        Self {
+            anchor: cell,
+            head: cell,
+        }
+    }
+
+    pub fn ordered(&self) -> ((usize, usize), (usize, usize)) {
+        (self.anchor.min(self.head), self.anchor.max(self.head))
+    }
+
+    /// Columns selected in a row, the end is `usize::MAX` when the row is selected to its end
+    pub fn columns(&self, row: usize) -> Option<Range<usize>> {
+        let ((start_row, start_col), (end_row, end_col)) = self.ordered();
+        if row < start_row || row > end_row {
+            return None;
+        }
+        let start = if row == start_row { start_col } else { 0 };
+        let end = if row == end_row { end_col } else { usize::MAX };
+        Some(start..end)
+    }
+
+    /// Selected text, command rows are taken whole
+    pub fn text(&self, terminal: &TerminalSession, rows: &[OutputRow]) -> String {
+        let ((start_row, _), (end_row, _)) = self.ordered();
+        let mut lines = vec![];
+        for (index, row) in rows.iter().enumerate().take(end_row + 1).skip(start_row) {
+            let text = row_text(terminal, *row);
+            if !matches!(row, OutputRow::Line(..)) {
+                lines.push(text);
+                continue;
+            }
+            let columns = self.columns(index).unwrap_or_default();
+            lines.push(
+                text.chars()
+                    .skip(columns.start)
+                    .take(columns.end.saturating_sub(columns.start))
+                    .collect(),
+            );
+        }
+        lines.join("\n")
+    }
+}
@@ -0,0 +1,661 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    mem,
+    path::PathBuf,
+    time::SystemTime,
+};
+use vte::{Params, Perform};
+
+use super::{find_locations, FileLocation};
+
+/// OSC code the shell prompt is wrapped in, to know where a command output ends
+pub const PROMPT_MARKER: &str = "6973";
+
+/// Random token the prompt marker carries, so that programs printing the marker
+/// cannot pass for the end of their own output
+fn session_token() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default();
+    hasher.write_u128(now.as_nanos());
+    hasher.write_u32(std::process::id());
+    format!("{:016x}", hasher.finish())
+}
+
+const TAB_WIDTH: usize = 8;
+/// Output lines a terminal keeps, unless set otherwise
+pub const SCROLLBACK_LIMIT: usize = 10_000;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TermColor {
+    #[default]
+    Default,
+    /// One of the 256 xterm colors
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl TermColor {
+    /// `None` for the default color, which is left to the theme
+    pub fn rgb(&self) -> Option<(u8, u8, u8)> {
+        match *self {
+            TermColor::Default => None,
+            TermColor::Indexed(index) => Some(indexed_rgb(index)),
+            TermColor::Rgb(r, g, b) => Some((r, g, b)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Style {
+    pub foreground: TermColor,
+    pub background: TermColor,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub inverse: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cell {
+    pub c: char,
+    pub style: Style,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            c: ' ',
+            style: Style::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Line {
+    pub cells: Vec<Cell>,
+    /// File locations written on the line, found once it is complete
+    pub locations: Vec<FileLocation>,
+}
+
+impl Line {
+    pub fn text(&self) -> String {
+        self.cells.iter().map(|cell| cell.c).collect()
+    }
+
+    fn find_locations(&mut self) {
+        self.locations = find_locations(&self.text());
+    }
+
+    /// Consecutive cells sharing a style, to render them as one section
+    pub fn spans(&self) -> Vec<(String, Style)> {
+        let mut spans: Vec<(String, Style)> = vec![];
+        for cell in &self.cells {
+            match spans.last_mut() {
+                Some((text, style)) if *style == cell.style => text.push(cell.c),
+                _ => spans.push((cell.c.to_string(), cell.style)),
+            }
+        }
+        spans
+    }
+
+    fn put(&mut self, col: usize, cell: Cell) {
+        if self.cells.len() <= col {
+            self.cells.resize(col + 1, Cell::default());
+        }
+        self.cells[col] = cell;
+    }
+
+    fn blank(&mut self, range: std::ops::Range<usize>) {
+        let end = range.end.min(self.cells.len());
+        for cell in &mut self.cells[range.start.min(end)..end] {
+            *cell = Cell::default();
+        }
+    }
+}
+
+/// What the shell reported in its prompt once a command finished
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Prompt {
+    /// Exit status of the last command
+    pub status: i32,
+    pub cwd: PathBuf,
+}
+
+/// Lines and cursor of one screen. The normal page grows with the output of a command,
+/// the alternate page used by full screen programs has a fixed number of rows.
+#[derive(Clone, Debug, Default)]
+pub struct Page {
+    pub lines: Vec<Line>,
+    pub row: usize,
+    pub col: usize,
+    fixed: bool,
+    saved: (usize, usize),
+    scroll_top: usize,
+    scroll_bottom: usize,
+}
+
+impl Page {
+    fn fixed(rows: usize) -> Self {
+        Self {
+            lines: vec![Line::default(); rows],
+            fixed: true,
+            scroll_bottom: rows.saturating_sub(1),
+            ..Default::default()
+        }
+    }
+
+    fn line(&mut self) -> &mut Line {
+        if self.lines.len() <= self.row {
+            self.lines.resize(self.row + 1, Line::default());
+        }
+        &mut self.lines[self.row]
+    }
+
+    /// Drop the oldest lines past `limit`, returns how many went
+    fn trim(&mut self, limit: usize) -> usize {
+        if self.fixed || self.lines.len() <= limit {
+            return 0;
+        }
+        let excess = self.lines.len() - limit;
+        self.lines.drain(..excess);
+        self.row = self.row.saturating_sub(excess);
+        excess
+    }
+
+    /// First line of the visible part, cursor positions sent by programs are relative to it
+    fn origin(&self, rows: usize) -> usize {
+        if self.fixed {
+            0
+        } else {
+            self.lines.len().max(self.row + 1).saturating_sub(rows)
+        }
+    }
+
+    fn print(&mut self, c: char, style: Style, cols: usize) {
+        if self.fixed && self.col >= cols {
+            self.col = 0;
+            self.line_feed();
+        }
+        let col = self.col;
+        self.line().put(col, Cell { c, style });
+        self.col += 1;
+    }
+
+    fn line_feed(&mut self) {
+        if !self.fixed {
+            self.line().find_locations();
+            self.row += 1;
+            self.line();
+        } else if self.row == self.scroll_bottom {
+            self.scroll_up(1);
+        } else if self.row + 1 < self.lines.len() {
+            self.row += 1;
+        }
+    }
+
+    fn reverse_index(&mut self) {
+        if self.fixed && self.row == self.scroll_top {
+            self.scroll_down(1);
+        } else {
+            self.row = self.row.saturating_sub(1);
+        }
+    }
+
+    fn scroll_up(&mut self, count: usize) {
+        for _ in 0..count {
+            if self.scroll_top < self.lines.len() && self.scroll_bottom < self.lines.len() {
+                self.lines.remove(self.scroll_top);
+                self.lines.insert(self.scroll_bottom, Line::default());
+            }
+        }
+    }
+
+    fn scroll_down(&mut self, count: usize) {
+        for _ in 0..count {
+            if self.scroll_top < self.lines.len() && self.scroll_bottom < self.lines.len() {
+                self.lines.remove(self.scroll_bottom);
+                self.lines.insert(self.scroll_top, Line::default());
+            }
+        }
+    }
+
+    fn move_to(&mut self, row: usize, col: usize, (cols, rows): (usize, usize)) {
+        let origin = self.origin(rows);
+        self.row = origin + row.min(rows.saturating_sub(1));
+        self.col = col.min(cols.saturating_sub(1));
+    }
+
+    fn erase_line(&mut self, mode: u16) {
+        let col = self.col;
+        let line = self.line();
+        match mode {
+            0 => line.cells.truncate(col),
+            1 => line.blank(0..col + 1),
+            _ => line.cells.clear(),
+        }
+    }
+
+    fn erase_display(&mut self, mode: u16) {
+        match mode {
+            0 => {
+                self.erase_line(0);
+                for line in self.lines.iter_mut().skip(self.row + 1) {
+                    line.cells.clear();
+                }
+            }
+            1 => {
+                self.erase_line(1);
+                for line in self.lines.iter_mut().take(self.row) {
+                    line.cells.clear();
+                }
+            }
+            _ if self.fixed => {
+                for line in &mut self.lines {
+                    line.cells.clear();
+                }
+            }
+            // `clear` empties the output instead of pushing it out of view
+            _ => {
+                self.lines.clear();
+                self.row = 0;
+                self.col = 0;
+            }
+        }
+    }
+
+    fn insert_lines(&mut self, count: usize) {
+        if !self.fixed || self.row < self.scroll_top || self.row > self.scroll_bottom {
+            return;
+        }
+        for _ in 0..count.min(self.scroll_bottom - self.row + 1) {
+            self.lines.remove(self.scroll_bottom);
+            self.lines.insert(self.row, Line::default());
+        }
+    }
+
+    fn delete_lines(&mut self, count: usize) {
+        if !self.fixed || self.row < self.scroll_top || self.row > self.scroll_bottom {
+            return;
+        }
+        for _ in 0..count.min(self.scroll_bottom - self.row + 1) {
+            self.lines.remove(self.row);
+            self.lines.insert(self.scroll_bottom, Line::default());
+        }
+    }
+
+    fn resize(&mut self, (cols, rows): (usize, usize)) {
+        if !self.fixed {
+            return;
+        }
+        while self.lines.len() > rows {
+            self.lines.remove(0);
+            self.row = self.row.saturating_sub(1);
+        }
+        self.lines.resize(rows, Line::default());
+        self.row = self.row.min(rows.saturating_sub(1));
+        self.col = self.col.min(cols.saturating_sub(1));
+        self.scroll_top = 0;
+        self.scroll_bottom = rows.saturating_sub(1);
+    }
+}
+
+/// Terminal state fed by the vte parser with what the shell writes
+#[derive(Clone, Debug)]
+pub struct Screen {
+    pub normal: Page,
+    /// Set while a full screen program is running
+    pub alternate: Option<Page>,
+    /// Columns and rows of the terminal
+    pub size: (usize, usize),
+    /// Arrow keys are sent as `ESC O x` instead of `ESC [ x`
+    pub application_cursor: bool,
+    pub cursor_visible: bool,
+    /// Lines the normal page keeps, the oldest are dropped past it
+    pub scrollback: usize,
+    /// Lines dropped from the normal page since the last prompt
+    dropped: usize,
+    style: Style,
+    echo: Option<String>,
+    prompt: Option<Prompt>,
+    token: String,
+}
+
+impl Default for Screen {
+    fn default() -> Self {
+        Self {
+            normal: Page::default(),
+            alternate: None,
+            size: (80, 24),
+            application_cursor: false,
+            cursor_visible: true,
+            scrollback: SCROLLBACK_LIMIT,
+            dropped: 0,
+            style: Style::default(),
+            echo: None,
+            prompt: None,
+            token: session_token(),
+        }
+    }
+}
+
+impl Screen {
+    fn page_mut(&mut self) -> &mut Page {
+        self.alternate.as_mut().unwrap_or(&mut self.normal)
+    }
+
+    pub fn resize(&mut self, cols: usize, rows: usize) {
+        self.size = (cols.max(1), rows.max(1));
+        let size = self.size;
+        if let Some(page) = &mut self.alternate {
+            page.resize(size);
+        }
+    }
+
+    /// The terminal echoes a command back before running it,
+    /// that line is dropped from the output if it matches
+    pub fn expect_echo(&mut self, command: &str) {
+        self.echo = Some(command.to_string());
+    }
+
+    /// Token the shell puts after `PROMPT_MARKER`, other markers are ignored
+    pub fn prompt_token(&self) -> &str {
+        &self.token
+    }
+
+    /// The prompt printed since the last call, if any
+    pub fn take_prompt(&mut self) -> Option<Prompt> {
+        self.prompt.take()
+    }
+
+    /// How many lines of the output were dropped to keep within `scrollback`
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Lines written since the last prompt, the normal page starts over empty
+    pub fn take_output(&mut self) -> Vec<Line> {
+        self.dropped = 0;
+        let mut lines = mem::take(&mut self.normal).lines;
+        while lines.last().is_some_and(|line| line.cells.is_empty()) {
+            lines.pop();
+        }
+        // lines may have been written over after their line feed
+        for line in &mut lines {
+            line.find_locations();
+        }
+        self.echo = None;
+        lines
+    }
+
+    fn line_feed(&mut self) {
+        if self.alternate.is_none() {
+            if let Some(echo) = self.echo.take() {
+                let page = &mut self.normal;
+                if page.row == 0 && page.line().text().trim_end() == echo.trim_end() {
+                    page.lines.clear();
+                    page.col = 0;
+                    return;
+                }
+            }
+        }
+        self.page_mut().line_feed();
+        if self.alternate.is_none() {
+            self.dropped += self.normal.trim(self.scrollback);
+        }
+    }
+
+    fn set_alternate(&mut self, enabled: bool) {
+        if enabled && self.alternate.is_none() {
+            let mut page = Page::fixed(self.size.1);
+            page.resize(self.size);
+            self.alternate = Some(page);
+        } else if !enabled {
+            self.alternate = None;
+        }
+    }
+
+    fn select_graphic_rendition(&mut self, params: &Params) {
+        if params.is_empty() {
+            self.style = Style::default();
+        }
+        let mut params = params.iter().map(|param| param[0]);
+        while let Some(param) = params.next() {
+            let style = &mut self.style;
+            match param {
+                0 => *style = Style::default(),
+                1 => style.bold = true,
+                3 => style.italic = true,
+                4 => style.underline = true,
+                7 => style.inverse = true,
+                22 => style.bold = false,
+                23 => style.italic = false,
+                24 => style.underline = false,
+                27 => style.inverse = false,
+                30..=37 => style.foreground = TermColor::Indexed(param as u8 - 30),
+                38 => style.foreground = extended_color(&mut params),
+                39 => style.foreground = TermColor::Default,
+                40..=47 => style.background = TermColor::Indexed(param as u8 - 40),
+                48 => style.background = extended_color(&mut params),
+                49 => style.background = TermColor::Default,
+                90..=97 => style.foreground = TermColor::Indexed(param as u8 - 90 + 8),
+                100..=107 => style.background = TermColor::Indexed(param as u8 - 100 + 8),
+                _ => (),
+            }
+        }
+    }
+}
+
+/// `5;index` or `2;r;g;b` following a 38 or 48
+fn extended_color(params: &mut impl Iterator<Item = u16>) -> TermColor {
+    match params.next() {
+        Some(5) => params
+            .next()
+            .map_or(TermColor::Default, |index| TermColor::Indexed(index as u8)),
+        Some(2) => {
+            let mut channel = || params.next().unwrap_or(0) as u8;
+            TermColor::Rgb(channel(), channel(), channel())
+        }
+        _ => TermColor::Default,
+    }
+}
+
+/// The xterm palette: 16 base colors, a 6x6x6 cube and 24 grays
+pub fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    const BASE: [(u8, u8, u8); 16] = [
+        (0, 0, 0),
+        (205, 49, 49),
+        (13, 188, 121),
+        (229, 229, 16),
+        (36, 114, 200),
+        (188, 63, 188),
+        (17, 168, 205),
+        (229, 229, 229),
+        (102, 102, 102),
+        (241, 76, 76),
+        (35, 209, 139),
+        (245, 245, 67),
+        (59, 142, 234),
+        (214, 112, 214),
+        (41, 184, 219),
+        (255, 255, 255),
+    ];
+    match index {
+        0..=15 => BASE[index as usize],
+        16..=231 => {
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            let index = index - 16;
+            (level(index / 36), level(index / 6 % 6), level(index % 6))
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
+
+impl Perform for Screen {
+    fn print(&mut self, c: char) {
+        let (style, cols) = (self.style, self.size.0);
+        self.page_mut().print(c, style, cols);
+    }
+
+    fn execute(&mut self, byte: u8) {
+        match byte {
+            b'\n' | 0x0b | 0x0c => self.line_feed(),
+            b'\r' => self.page_mut().col = 0,
+            0x08 => {
+                let page = self.page_mut();
+                page.col = page.col.saturating_sub(1);
+            }
+            b'\t' => {
+                let cols = self.size.0;
+                let page = self.page_mut();
+                page.col = ((page.col / TAB_WIDTH + 1) * TAB_WIDTH).min(cols.saturating_sub(1));
+            }
+            _ => (),
+        }
+    }
+
+    fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], _ignore: bool, action: char) {
+        let values: Vec<u16> = params.iter().map(|param| param[0]).collect();
+        let value = |index: usize| values.get(index).copied().unwrap_or(0);
+        // counts and positions of 0 mean 1
+        let count = |index: usize| value(index).max(1) as usize;
+        let size = self.size;
+
+        if intermediates == [b'?'] {
+            let enabled = match action {
+                'h' => true,
+                'l' => false,
+                _ => return,
+            };
+            for mode in &values {
+                match mode {
+                    1 => self.application_cursor = enabled,
+                    25 => self.cursor_visible = enabled,
+                    47 | 1047 | 1049 => self.set_alternate(enabled),
+                    _ => (),
+                }
+            }
+            return;
+        }
+        if !intermediates.is_empty() {
+            return;
+        }
+        if action == 'm' {
+            self.select_graphic_rendition(params);
+            return;
+        }
+
+        let page = self.page_mut();
+        let origin = page.origin(size.1);
+        match action {
+            'A' => page.row = page.row.saturating_sub(count(0)).max(origin),
+            'B' | 'e' => page.row += count(0),
+            'C' | 'a' => page.col = (page.col + count(0)).min(size.0.saturating_sub(1)),
+            'D' => page.col = page.col.saturating_sub(count(0)),
+            'E' => {
+                page.row += count(0);
+                page.col = 0;
+            }
+            'F' => {
+                page.row = page.row.saturating_sub(count(0)).max(origin);
+                page.col = 0;
+            }
+            'G' | '`' => page.col = (count(0) - 1).min(size.0.saturating_sub(1)),
+            'H' | 'f' => page.move_to(count(0) - 1, count(1) - 1, size),
+            'd' => {
+                let col = page.col;
+                page.move_to(count(0) - 1, col, size);
+            }
+            'J' => page.erase_display(value(0)),
+            'K' => page.erase_line(value(0)),
+            'L' => page.insert_lines(count(0)),
+            'M' => page.delete_lines(count(0)),
+            'P' => {
+                let col = page.col;
+                let line = page.line();
+                let end = (col + count(0)).min(line.cells.len());
+                line.cells.drain(col.min(end)..end);
+            }
+            '@' => {
+                let col = page.col;
+                let line = page.line();
+                if col < line.cells.len() {
+                    line.cells.splice(col..col, vec![Cell::default(); count(0)]);
+                }
+            }
+            'X' => {
+                let col = page.col;
+                page.line().blank(col..col + count(0));
+            }
+            'S' => page.scroll_up(count(0)),
+            'T' => page.scroll_down(count(0)),
+            'r' if page.fixed => {
+                let bottom = if value(1) == 0 {
+                    size.1
+                } else {
+                    value(1) as usize
+                };
+                page.scroll_top = count(0) - 1;
+                page.scroll_bottom = bottom.clamp(1, size.1) - 1;
+                page.move_to(0, 0, size);
+            }
+            's' => page.saved = (page.row, page.col),
+            'u' => (page.row, page.col) = page.saved,
+            _ => (),
+        }
+        let page = self.page_mut();
+        if page.fixed {
+            page.row = page.row.min(size.1.saturating_sub(1));
+        }
+    }
+
+    fn esc_dispatch(&mut self, intermediates: &[u8], _ignore: bool, byte: u8) {
+        if !intermediates.is_empty() {
+            return;
+        }
+        match byte {
+            b'7' => {
+                let page = self.page_mut();
+                page.saved = (page.row, page.col);
+            }
+            b'8' => {
+                let page = self.page_mut();
+                (page.row, page.col) = page.saved;
+            }
+            b'D' => self.line_feed(),
+            b'E' => {
+                self.line_feed();
+                self.page_mut().col = 0;
+            }
+            b'M' => self.page_mut().reverse_index(),
+            b'c' => {
+                *self = Screen {
+                    size: self.size,
+                    token: mem::take(&mut self.token),
+                    ..Default::default()
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn osc_dispatch(&mut self, params: &[&[u8]], _bell_terminated: bool) {
+        // the marker is followed by `token;status;cwd`, the cwd may itself contain `;`
+        if params.len() < 4
+            || params[0] != PROMPT_MARKER.as_bytes()
+            || params[1] != self.token.as_bytes()
+        {
+            return;
+        }
+        let cwd = params[3..].join(&b';');
+        self.prompt = Some(Prompt {
+            status: String::from_utf8_lossy(params[2]).parse().unwrap_or(0),
+            cwd: PathBuf::from(String::from_utf8_lossy(&cwd).to_string()),
+        });
+    }
+}
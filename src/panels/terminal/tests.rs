@@ -0,0 +1,402 @@
+use super::*;
+use eframe::egui;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+/// Run a command and wait for its prompt
+fn run(terminal: &mut TerminalSession, command: &str) -> Vec<String> {
+    terminal.send_command(command.to_string());
+    wait(terminal);
+    let index = terminal.entries.len() - 1;
+    terminal
+        .output(index)
+        .iter()
+        .map(|line| line.text())
+        .collect()
+}
+
+fn wait(terminal: &mut TerminalSession) {
+    for _ in 0..200 {
+        thread::sleep(Duration::from_millis(25));
+        terminal.update();
+        if !terminal.is_running() {
+            return;
+        }
+    }
+    panic!("the shell did not answer");
+}
+
+fn session(name: &str) -> (TerminalSession, PathBuf) {
+    let home = env::temp_dir().join(format!("calcifer-terminal-{}", name));
+    fs::create_dir_all(home.join("src")).unwrap();
+    let mut terminal = TerminalSession::new(
+        &egui::Context::default(),
+        name.into(),
+        Path::new("/"),
+        &home,
+    )
+    .unwrap();
+    wait(&mut terminal);
+    (terminal, home)
+}
+
+#[test]
+fn cd_expands_like_a_shell() {
+    let (mut terminal, home) = session("cd");
+
+    run(&mut terminal, "cd ~");
+    assert_eq!(terminal.cwd, home);
+    run(&mut terminal, "cd src");
+    assert_eq!(terminal.cwd, home.join("src"));
+    run(&mut terminal, "cd /");
+    assert_eq!(terminal.cwd, Path::new("/"));
+    run(&mut terminal, "cd -");
+    assert_eq!(terminal.cwd, home.join("src"));
+    run(&mut terminal, "export TARGET=/usr; cd $TARGET");
+    assert_eq!(terminal.cwd, Path::new("/usr"));
+    let other_home = home.join("src");
+    run(
+        &mut terminal,
+        &format!("export HOME={}; cd ~", other_home.display()),
+    );
+    assert_eq!(terminal.cwd, other_home);
+}
+
+#[test]
+fn cd_chained_with_other_commands() {
+    let (mut terminal, home) = session("chain");
+
+    let output = run(&mut terminal, "cd ~/src && pwd");
+    assert_eq!(output, vec![home.join("src").to_string_lossy()]);
+    assert_eq!(terminal.cwd, home.join("src"));
+    assert_eq!(terminal.entries[0].status, Some(0));
+
+    run(&mut terminal, "cd missing && pwd");
+    assert_eq!(terminal.cwd, home.join("src"));
+    assert_ne!(terminal.entries[1].status, Some(0));
+}
+
+#[test]
+fn scrollback_drops_the_oldest_output() {
+    let (mut terminal, _) = session("scrollback");
+    terminal.set_scrollback(10);
+
+    let output = run(&mut terminal, "seq 1 30");
+    // one row for the command, nine for its last lines
+    assert_eq!(output, (22..=30).map(|n| n.to_string()).collect::<Vec<_>>());
+    assert_eq!(terminal.entries[0].dropped, 21);
+
+    run(&mut terminal, "seq 1 5");
+    assert_eq!(terminal.entries.len(), 2);
+    assert_eq!(terminal.entries[0].result.len(), 3);
+    run(&mut terminal, "seq 1 8");
+    assert_eq!(terminal.entries.len(), 1);
+    assert_eq!(terminal.entries[0].command, "seq 1 8");
+}
+
+#[test]
+fn selected_output_text() {
+    let (mut terminal, _) = session("selection");
+    run(&mut terminal, "printf 'alpha\\nbeta\\ngamma\\n'");
+
+    let rows = output_rows(&terminal);
+    assert_eq!(rows[0], OutputRow::Gap);
+    assert_eq!(rows[1], OutputRow::Header(0));
+    assert_eq!(rows[2..], [0, 1, 2].map(|line| OutputRow::Line(0, line)));
+
+    // from "ha" to "gam", dragged upwards
+    let selection = OutputSelection {
+        anchor: (4, 3),
+        head: (2, 3),
+    };
+    assert_eq!(selection.text(&terminal, &rows), "ha\nbeta\ngam");
+    let whole = OutputSelection {
+        anchor: (1, 0),
+        head: (2, 99),
+    };
+    let entry = &terminal.entries[0];
+    let header = format!("{} {}", entry.env, entry.command);
+    assert_eq!(whole.text(&terminal, &rows), format!("{}\nalpha", header));
+}
+
+#[test]
+fn locations_in_compiler_output() {
+    let rustc = find_locations("  --> src/core/ui.rs:123:45");
+    assert_eq!(
+        rustc,
+        vec![FileLocation {
+            range: 6..27,
+            path: PathBuf::from("src/core/ui.rs"),
+            line: 123,
+            column: 45,
+        }]
+    );
+
+    let gcc = find_locations("main.c:10:5: error: expected ';'");
+    assert_eq!(gcc[0].path, PathBuf::from("main.c"));
+    assert_eq!((gcc[0].line, gcc[0].column), (10, 5));
+
+    let grep = find_locations("Cargo.toml:3:version = \"1.4.0\"");
+    assert_eq!(grep.len(), 1);
+    assert_eq!((grep[0].line, grep[0].column), (3, 1));
+
+    let python = find_locations("  File \"/tmp/script.py\", line 7, in <module>");
+    assert_eq!(python[0].path, PathBuf::from("/tmp/script.py"));
+    assert_eq!((python[0].line, python[0].range.start), (7, 2));
+}
+
+#[test]
+fn no_locations_in_times_and_urls() {
+    assert!(find_locations("finished at 12:30:45").is_empty());
+    assert!(find_locations("listening on 127.0.0.1:8080").is_empty());
+    assert!(find_locations("see https://example.com:443/page").is_empty());
+    assert!(find_locations("error: 3 warnings emitted").is_empty());
+}
+
+#[test]
+fn locations_after_wide_whitespace() {
+    // a no-break space and an ideographic space, several bytes long
+    let locations = find_locations("a\u{a0}src/main.rs:1 b\u{3000}lib.rs:2:3");
+    assert_eq!(locations.len(), 2);
+    assert_eq!(locations[0].path, PathBuf::from("src/main.rs"));
+    assert_eq!(locations[0].range, 2..15);
+    assert_eq!(locations[1].path, PathBuf::from("lib.rs"));
+    assert_eq!((locations[1].line, locations[1].column), (2, 3));
+    assert!(find_locations("\u{a0}http://host.com:80").is_empty());
+}
+
+#[test]
+fn history_browsing_per_directory() {
+    let (here, there) = (Path::new("/here"), Path::new("/there"));
+    let mut history = CommandHistory::default();
+    for command in ["make", "ls", " secret", "make", ""] {
+        history.push(here, command);
+    }
+    history.push(there, "pwd");
+
+    assert_eq!(history.commands()[here], vec!["ls", "make"]);
+    assert_eq!(history.previous(here, "ma").as_deref(), Some("make"));
+    assert_eq!(history.previous(here, "make").as_deref(), Some("ls"));
+    assert_eq!(history.previous(here, "ls"), None);
+    assert_eq!(history.next(here).as_deref(), Some("make"));
+    assert_eq!(history.next(here).as_deref(), Some("ma"));
+    assert_eq!(history.next(here), None);
+    assert_eq!(history.previous(there, "").as_deref(), Some("pwd"));
+}
+
+#[test]
+fn history_reverse_search() {
+    let cwd = Path::new("/here");
+    let mut history = CommandHistory::default();
+    for command in ["cargo build", "ls", "cargo test", "git status"] {
+        history.push(cwd, command);
+    }
+
+    history.search_older(cwd);
+    history.search.as_mut().unwrap().query = "cargo".into();
+    assert_eq!(history.search_match(cwd), Some("cargo test"));
+    history.search_older(cwd);
+    assert_eq!(history.search_match(cwd), Some("cargo build"));
+    // no older match, stays on the last one
+    history.search_older(cwd);
+    assert_eq!(history.search_match(cwd), Some("cargo build"));
+    assert_eq!(history.end_search(cwd).as_deref(), Some("cargo build"));
+    assert!(history.search.is_none());
+}
+
+/// Screen after parsing `bytes`
+fn screen_of(size: (usize, usize), bytes: &str) -> Screen {
+    let mut screen = Screen::default();
+    screen.resize(size.0, size.1);
+    feed(&mut screen, bytes);
+    screen
+}
+
+fn feed(screen: &mut Screen, bytes: &str) {
+    let mut parser = vte::Parser::new();
+    for byte in bytes.bytes() {
+        parser.advance(screen, byte);
+    }
+}
+
+fn texts(page: &Page) -> Vec<String> {
+    page.lines.iter().map(|line| line.text()).collect()
+}
+
+#[test]
+fn screen_prints_lines_and_tabs() {
+    let screen = screen_of((80, 24), "ab\r\ncd\te\r\n\r\nlast");
+    assert_eq!(texts(&screen.normal), ["ab", "cd      e", "", "last"]);
+    assert_eq!((screen.normal.row, screen.normal.col), (3, 4));
+
+    // a carriage return writes over the line
+    let screen = screen_of((80, 24), "50%\r100%");
+    assert_eq!(texts(&screen.normal), ["100%"]);
+}
+
+#[test]
+fn screen_graphic_rendition() {
+    let screen = screen_of(
+        (80, 24),
+        "\x1b[1;31mA\x1b[0mB\x1b[38;5;200;4mC\x1b[24;48;2;1;2;3mD\x1b[39;49;92mE\x1b[mF",
+    );
+    let styles: Vec<Style> = screen.normal.lines[0]
+        .cells
+        .iter()
+        .map(|cell| cell.style)
+        .collect();
+    let red_bold = Style {
+        foreground: TermColor::Indexed(1),
+        bold: true,
+        ..Default::default()
+    };
+    assert_eq!(styles[0], red_bold);
+    assert_eq!(styles[1], Style::default());
+    let underlined = Style {
+        foreground: TermColor::Indexed(200),
+        underline: true,
+        ..Default::default()
+    };
+    assert_eq!(styles[2], underlined);
+    assert_eq!(styles[3].background, TermColor::Rgb(1, 2, 3));
+    assert!(!styles[3].underline);
+    assert_eq!(styles[4].foreground, TermColor::Indexed(10));
+    assert_eq!(styles[4].background, TermColor::Default);
+    assert_eq!(styles[5], Style::default());
+}
+
+#[test]
+fn screen_cursor_moves_and_erases() {
+    let screen = screen_of((80, 24), "hello\x1b[2DXY");
+    assert_eq!(texts(&screen.normal), ["helXY"]);
+
+    let screen = screen_of((80, 24), "abcdef\x1b[3D\x1b[K");
+    assert_eq!(texts(&screen.normal), ["abc"]);
+    assert_eq!(screen.normal.col, 3);
+
+    let screen = screen_of((80, 24), "abcdef\x1b[3D\x1b[1K");
+    assert_eq!(texts(&screen.normal), ["    ef"]);
+
+    let screen = screen_of((80, 24), "abcdef\x1b[2G\x1b[2P\x1b[1@");
+    assert_eq!(texts(&screen.normal), ["a def"]);
+
+    // positions sent by programs count from the first visible line
+    let screen = screen_of((10, 3), "1\r\n2\r\n3\r\n4\x1b[1;2Hx\x1b[5;20Hy");
+    assert_eq!(texts(&screen.normal), ["1", "2x", "3", "4        y"]);
+    assert_eq!((screen.normal.row, screen.normal.col), (3, 10));
+
+    let screen = screen_of((80, 24), "one\r\ntwo\x1b[2J");
+    assert!(screen.normal.lines.is_empty());
+    assert_eq!((screen.normal.row, screen.normal.col), (0, 0));
+}
+
+#[test]
+fn screen_alternate_page_and_scroll_region() {
+    let mut screen = screen_of((10, 4), "kept\r\n\x1b[?1049h");
+    let page = screen.alternate.as_ref().unwrap();
+    assert_eq!(texts(page), ["", "", "", ""]);
+
+    // rows 2 and 3 scroll, the last row stays
+    feed(
+        &mut screen,
+        "\x1b[2;3r\x1b[4Hbottom\x1b[2Hone\r\ntwo\r\nthree\x1b[Hheader",
+    );
+    let page = screen.alternate.as_ref().unwrap();
+    assert_eq!(texts(page), ["header", "two", "three", "bottom"]);
+
+    // text past the last column wraps on a fixed page
+    feed(&mut screen, "\x1b[r\x1b[2J\x1b[H0123456789ab");
+    let page = screen.alternate.as_ref().unwrap();
+    assert_eq!(texts(page)[..2], ["0123456789", "ab"]);
+
+    feed(&mut screen, "\x1b[?1049l");
+    assert!(screen.alternate.is_none());
+    assert_eq!(texts(&screen.normal), ["kept", ""]);
+}
+
+#[test]
+fn screen_prompt_needs_the_session_token() {
+    let mut screen = Screen::default();
+    feed(&mut screen, "\x1b]6973;0;/tmp\x07");
+    feed(&mut screen, "\x1b]6973;forged;0;/tmp\x07");
+    assert_eq!(screen.take_prompt(), None);
+
+    let marker = format!("\x1b]6973;{};2;/a;b\x07", screen.prompt_token());
+    feed(&mut screen, &marker);
+    let prompt = Prompt {
+        status: 2,
+        cwd: PathBuf::from("/a;b"),
+    };
+    assert_eq!(screen.take_prompt(), Some(prompt));
+    assert_ne!(Screen::default().prompt_token(), screen.prompt_token());
+}
+
+#[test]
+fn screen_lines_keep_their_locations() {
+    let mut screen = screen_of((80, 24), "error at src/main.rs:3:7\r\nsrc/lib.rs:1");
+    assert_eq!(screen.normal.lines[0].locations[0].line, 3);
+    let output = screen.take_output();
+    assert_eq!(output[1].locations[0].path, PathBuf::from("src/lib.rs"));
+}
+
+fn key(key: egui::Key, modifiers: egui::Modifiers) -> egui::Event {
+    egui::Event::Key {
+        key,
+        physical_key: None,
+        pressed: true,
+        repeat: false,
+        modifiers,
+    }
+}
+
+#[test]
+fn key_event_bytes() {
+    use egui::{Key, Modifiers};
+    let cases: Vec<(egui::Event, bool, Option<&[u8]>)> = vec![
+        (egui::Event::Text("é".into()), false, Some("é".as_bytes())),
+        (egui::Event::Paste("ls -a".into()), false, Some(b"ls -a")),
+        (egui::Event::Copy, false, Some(b"\x03")),
+        (egui::Event::Cut, false, Some(b"\x18")),
+        (key(Key::C, Modifiers::CTRL), false, Some(b"\x03")),
+        (key(Key::D, Modifiers::CTRL), false, Some(b"\x04")),
+        (key(Key::Z, Modifiers::CTRL), false, Some(b"\x1a")),
+        (key(Key::Enter, Modifiers::NONE), false, Some(b"\r")),
+        (key(Key::Tab, Modifiers::NONE), false, Some(b"\t")),
+        (key(Key::Tab, Modifiers::SHIFT), false, Some(b"\x1b[Z")),
+        (key(Key::Backspace, Modifiers::NONE), false, Some(b"\x7f")),
+        (key(Key::Escape, Modifiers::NONE), false, Some(b"\x1b")),
+        (key(Key::ArrowUp, Modifiers::NONE), false, Some(b"\x1b[A")),
+        (key(Key::ArrowUp, Modifiers::NONE), true, Some(b"\x1bOA")),
+        (key(Key::ArrowLeft, Modifiers::NONE), true, Some(b"\x1bOD")),
+        (key(Key::Home, Modifiers::NONE), false, Some(b"\x1b[H")),
+        (key(Key::End, Modifiers::NONE), true, Some(b"\x1bOF")),
+        (key(Key::Delete, Modifiers::NONE), false, Some(b"\x1b[3~")),
+        (key(Key::PageDown, Modifiers::NONE), false, Some(b"\x1b[6~")),
+        (key(Key::F1, Modifiers::NONE), false, Some(b"\x1bOP")),
+        (key(Key::F12, Modifiers::NONE), false, Some(b"\x1b[24~")),
+        // letters come as text events
+        (key(Key::A, Modifiers::NONE), false, None),
+        (egui::Event::PointerGone, false, None),
+    ];
+    for (event, application_cursor, bytes) in cases {
+        assert_eq!(
+            event_bytes(&event, application_cursor).as_deref(),
+            bytes,
+            "{:?}",
+            event
+        );
+    }
+
+    let released = egui::Event::Key {
+        key: Key::Enter,
+        physical_key: None,
+        pressed: false,
+        repeat: false,
+        modifiers: Modifiers::NONE,
+    };
+    assert_eq!(event_bytes(&released, false), None);
+}
@@ -1,50 +1,71 @@
 // Placeholder
 
-pub struct Line {
-    pub text: String,
-    pub error: bool,
-}
+use super::{Line, Screen};
+use eframe::egui;
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
-impl Line {
-    fn error(text: String) -> Self {
-        Self {
-            text: remove_line_break(text),
-            error: true,
-        }
-    }
-}
+pub const STOP_TIMEOUT: Duration = Duration::from_secs(3);
 
 pub struct CommandEntry {
     pub env: String,
+    /// Where the command was typed, relative paths in its output start from there
+    pub cwd: PathBuf,
     pub command: String,
     pub result: Vec<Line>,
+    pub dropped: usize,
+    pub finished: bool,
+    pub status: Option<i32>,
 }
 
 impl CommandEntry {
-    pub fn new(env: String, command: String) -> Self {
-        CommandEntry {
-            env,
-            command,
-            result: vec![Line::error("General Kenobi".to_string())],
-        }
+    pub fn copy_output(&self) {}
+}
+
+pub struct TerminalSession {
+    pub name: String,
+    pub cwd: PathBuf,
+    pub entries: Vec<CommandEntry>,
+    pub screen: Screen,
+    pub exited: bool,
+}
+
+impl TerminalSession {
+    pub fn new(
+        _ctx: &egui::Context,
+        _name: String,
+        _cwd: &Path,
+        _home: &Path,
+    ) -> Result<Self, io::Error> {
+        Err(io::Error::other("General Kenobi"))
     }
 
-    pub fn update(&mut self) {
-        return;
+    pub fn is_running(&self) -> bool {
+        false
     }
-}
 
-pub fn send_command(_command: String) -> CommandEntry {
-    return CommandEntry::new("windows>".to_string(), "hello there".to_string());
-}
+    pub fn is_stopping(&self) -> bool {
+        false
+    }
+
+    pub fn stop(&mut self) {}
+
+    pub fn send_command(&mut self, _command: String) {}
+
+    pub fn change_directory(&mut self, _path: &Path) {}
 
-fn remove_line_break(input: String) -> String {
-    let mut text = input.clone();
-    while text.ends_with('\n') {
-        text.pop();
-        if text.ends_with('\r') {
-            text.pop();
-        }
+    pub fn write(&mut self, _bytes: &[u8]) {}
+
+    pub fn output(&self, index: usize) -> &[Line] {
+        &self.entries[index].result
     }
-    text
+
+    pub fn set_scrollback(&mut self, _lines: usize) {}
+
+    pub fn resize(&mut self, _cols: usize, _rows: usize) {}
+
+    pub fn update(&mut self) {}
 }
@@ -4,6 +4,8 @@ pub struct ConfirmWindow {
     pub visible: bool,
     pub proceed: bool,
     pub prompt: String,
+    pub choice: usize,
+    choices: Vec<String>,
     id: String,
 }
 
@@ -13,10 +15,20 @@ impl ConfirmWindow {
             visible: false,
             proceed: false,
             prompt: prompt.to_string(),
+            choice: 0,
+            choices: vec![],
             id: id.to_string(),
         }
     }
 
+    /// Replace the Yes/No buttons, the index of the clicked one is stored in `choice`
+    pub fn with_choices(self, choices: &[&str]) -> Self {
+        Self {
+            choices: choices.iter().map(|choice| choice.to_string()).collect(),
+            ..self
+        }
+    }
+
     pub fn show(&mut self, ctx: &egui::Context) {
         let mut visible = self.visible;
         egui::Window::new(self.id.clone())
@@ -31,6 +43,16 @@ impl ConfirmWindow {
         ui.set_min_width(250.0);
         ui.label(self.prompt.clone());
         ui.vertical_centered(|ui| {
+            if !self.choices.is_empty() {
+                for (index, choice) in self.choices.clone().iter().enumerate() {
+                    if ui.add(egui::Button::new(choice)).clicked() {
+                        self.proceed = true;
+                        self.choice = index;
+                    }
+                }
+                return;
+            }
+
             if ui.add(egui::Button::new("Yes")).clicked() {
                 self.proceed = true;
             }
@@ -44,6 +66,7 @@ impl ConfirmWindow {
     pub fn ask(&mut self) {
         self.visible = true;
         self.proceed = false;
+        self.choice = 0;
     }
 
     pub fn close(&mut self) {
@@ -0,0 +1,65 @@
+use eframe::egui;
+
+use crate::editor::position;
+use crate::RED;
+
+/// Asks for a `line` or `line:col`, the zero based position typed is stored in `chosen`
+pub struct GotoWindow {
+    pub visible: bool,
+    pub initialized: bool,
+    pub chosen: Option<(usize, usize)>,
+    input: String,
+    invalid: bool,
+}
+
+impl GotoWindow {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            initialized: false,
+            chosen: None,
+            input: String::new(),
+            invalid: false,
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) {
+        let mut visible = self.visible;
+        egui::Window::new("Go to line")
+            .open(&mut visible)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| self.ui(ui));
+        self.visible = self.visible && visible;
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut self.input)
+                .hint_text("line:col")
+                .desired_width(120.0),
+        );
+        if !self.initialized {
+            response.request_focus();
+            self.initialized = true;
+        }
+        if response.changed() {
+            self.invalid = false;
+        }
+        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            match position::parse_line_col(&self.input) {
+                Some(line_col) => {
+                    self.chosen = Some(line_col);
+                    self.visible = false;
+                }
+                None => {
+                    self.invalid = true;
+                    response.request_focus();
+                }
+            }
+        }
+        if self.invalid {
+            ui.colored_label(RED, "Expected a line, or line:col");
+        }
+    }
+}
@@ -2,6 +2,12 @@
 mod confirm;
 pub use confirm::*;
 
+mod goto;
+pub use goto::*;
+
+mod outline;
+pub use outline::*;
+
 mod search;
 pub use search::*;
 
@@ -13,3 +19,6 @@ pub use shortcuts::*;
 
 mod project_item;
 pub use project_item::*;
+
+mod tasks;
+pub use tasks::*;
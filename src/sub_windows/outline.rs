@@ -0,0 +1,123 @@
+use eframe::egui;
+
+use crate::editor::symbols::{self, Symbol};
+
+/// Lists the symbols of the current tab filtered by a fuzzy query,
+/// the char index of the one picked is stored in `chosen`
+pub struct OutlineWindow {
+    pub visible: bool,
+    pub initialized: bool,
+    pub chosen: Option<usize>,
+    query: String,
+    symbols: Vec<Symbol>,
+    selected: usize,
+}
+
+impl OutlineWindow {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            initialized: false,
+            chosen: None,
+            query: String::new(),
+            symbols: vec![],
+            selected: 0,
+        }
+    }
+
+    /// Offer `symbols`, with an empty query
+    pub fn load(&mut self, symbols: Vec<Symbol>) {
+        self.symbols = symbols;
+        self.query.clear();
+        self.selected = 0;
+        self.initialized = false;
+    }
+
+    /// Symbols matching the query, best first, in the order of the file otherwise
+    fn matches(&self) -> Vec<Symbol> {
+        let mut scored: Vec<(usize, &Symbol)> = self
+            .symbols
+            .iter()
+            .filter_map(|symbol| Some((symbols::fuzzy_score(&self.query, &symbol.name)?, symbol)))
+            .collect();
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored
+            .into_iter()
+            .map(|(_, symbol)| symbol.clone())
+            .collect()
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) {
+        let mut visible = self.visible;
+        egui::Window::new("Outline")
+            .open(&mut visible)
+            .vscroll(false)
+            .show(ctx, |ui| self.ui(ui));
+        self.visible = self.visible && visible;
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.set_min_width(250.0);
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut self.query)
+                .hint_text("filter")
+                .desired_width(f32::INFINITY),
+        );
+        if !self.initialized {
+            response.request_focus();
+            self.initialized = true;
+        }
+        // the list follows the selection only when it moves, it scrolls freely otherwise
+        let mut moved = response.changed();
+        if moved {
+            self.selected = 0;
+        }
+
+        let matches = self.matches();
+        let pressed = |key| ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, key));
+        if pressed(egui::Key::ArrowDown) {
+            self.selected = (self.selected + 1).min(matches.len().saturating_sub(1));
+            moved = true;
+        }
+        if pressed(egui::Key::ArrowUp) {
+            self.selected = self.selected.saturating_sub(1);
+            moved = true;
+        }
+        let mut chosen = None;
+        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            chosen = matches.get(self.selected).map(|symbol| symbol.index);
+        }
+
+        ui.separator();
+        if self.symbols.is_empty() {
+            ui.label("No symbol in this file");
+        }
+        egui::ScrollArea::vertical()
+            .max_height(400.0)
+            .show(ui, |ui| {
+                for (index, symbol) in matches.iter().enumerate() {
+                    let label = format!(
+                        "{}{} {}",
+                        "  ".repeat(symbol.depth),
+                        symbol.kind,
+                        symbol.name
+                    );
+                    ui.horizontal(|ui| {
+                        let row = ui.selectable_label(index == self.selected, label);
+                        if moved && index == self.selected {
+                            row.scroll_to_me(None);
+                        }
+                        if row.clicked() {
+                            chosen = Some(symbol.index);
+                        }
+                        ui.weak((symbol.line + 1).to_string());
+                    });
+                }
+            });
+
+        if chosen.is_some() {
+            self.chosen = chosen;
+            self.visible = false;
+        }
+    }
+}
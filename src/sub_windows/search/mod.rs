@@ -0,0 +1,469 @@
+#[cfg(test)]
+mod tests;
+
+use eframe::egui;
+use regex::{NoExpand, Regex, RegexBuilder};
+use std::cmp::min;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::editor::position::{self, Column};
+use crate::panels::{FolderSearch, Tab};
+use crate::RED;
+
+enum Action {
+    Next,
+    Previous,
+    Replace,
+    ReplaceAll,
+    Update,
+    None,
+}
+
+#[derive(Clone, Default)]
+/// A match, as byte offsets in the text of a tab
+pub struct Selection {
+    pub tab: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A compiled search and the text it runs on, hashed by the source of the pattern
+#[derive(Clone, Copy)]
+pub struct MatchQuery<'a> {
+    pattern: &'a Regex,
+    case_insensitive: bool,
+    text: &'a str,
+}
+
+impl Hash for MatchQuery<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.pattern.as_str().hash(state);
+        self.case_insensitive.hash(state);
+        self.text.hash(state);
+    }
+}
+
+#[derive(Default)]
+pub struct MatchFinder;
+
+impl<'a> egui::util::cache::ComputerMut<MatchQuery<'a>, Vec<Range<usize>>> for MatchFinder {
+    fn compute(&mut self, query: MatchQuery<'a>) -> Vec<Range<usize>> {
+        find_matches(query.pattern, query.text)
+    }
+}
+
+pub type MatchCache = egui::util::cache::FrameCache<Vec<Range<usize>>, MatchFinder>;
+
+/// Byte ranges of the non empty matches of `pattern` in `text`
+fn find_matches(pattern: &Regex, text: &str) -> Vec<Range<usize>> {
+    pattern
+        .find_iter(text)
+        .filter(|found| !found.is_empty())
+        .map(|found| found.range())
+        .collect()
+}
+
+pub struct SearchWindow {
+    pub visible: bool,
+
+    search_text: String,
+    searched_text: String,
+    replace_text: String,
+
+    pub initialized: bool,
+
+    across_documents: bool,
+    in_folder: bool,
+    folder: String,
+    pub folder_search: FolderSearch,
+    regex_mode: bool,
+    whole_word: bool,
+    case_insensitive: bool,
+
+    pattern: Option<Regex>,
+    pattern_error: Option<String>,
+    results: Vec<Selection>,
+    current_result: usize,
+
+    pub result_selected: bool,
+
+    row_height: f32,
+}
+
+impl Default for SearchWindow {
+    fn default() -> Self {
+        Self {
+            visible: false,
+
+            search_text: "".into(),
+            searched_text: "".into(),
+            replace_text: "".into(),
+
+            initialized: false,
+
+            across_documents: false,
+            in_folder: false,
+            folder: "".into(),
+            folder_search: FolderSearch::default(),
+            regex_mode: false,
+            whole_word: false,
+            case_insensitive: false,
+
+            pattern: None,
+            pattern_error: None,
+            results: vec![],
+            current_result: 0,
+
+            result_selected: true,
+
+            row_height: 0.0,
+        }
+    }
+}
+
+impl SearchWindow {
+    /// `root` is the folder searched by default in folder mode
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        tabs: &mut Vec<Tab>,
+        selected_tab: &mut usize,
+        root: &Path,
+    ) {
+        if self.folder.is_empty() {
+            self.folder = root.to_string_lossy().to_string();
+        }
+        let mut visible = self.visible;
+        egui::Window::new("Search")
+            .open(&mut visible) //I want it to be able to change its visibility (if user close manually)
+            .vscroll(true)
+            .hscroll(true)
+            .show(ctx, |ui| self.ui(ui, tabs, selected_tab)); //but I want to edit the rest of the parameters and maybe close automatically
+        self.visible = self.visible && visible;
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, tabs: &mut Vec<Tab>, selected_tab: &mut usize) {
+        ui.set_min_width(250.0);
+
+        let font_id = egui::TextStyle::Body.resolve(ui.style());
+        self.row_height = ui.fonts(|f| f.row_height(&font_id)); //+ ui.spacing().item_spacing.y;
+
+        let mut action: Action = Action::None;
+
+        ui.horizontal(|ui| {
+            let Self { search_text, .. } = self;
+
+            let response = ui.add(
+                egui::TextEdit::singleline(search_text)
+                    .desired_width(120.0)
+                    .lock_focus(true),
+            );
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                action = Action::Update;
+            }
+            if !self.initialized {
+                response.request_focus();
+                self.initialized = true;
+            }
+
+            if ui.add(egui::Button::new("Update")).clicked() {
+                action = Action::Update;
+            }
+
+            if ui.add(egui::Button::new("<")).clicked() {
+                action = Action::Previous;
+            }
+
+            if let (true, Some(error)) =
+                (self.search_text == self.searched_text, &self.pattern_error)
+            {
+                ui.colored_label(RED, " invalid ").on_hover_text(error);
+            } else if self.in_folder {
+                ui.label(format!(" {} ", self.folder_search.hit_count()));
+            } else if self.search_text == self.searched_text
+                && !self.search_text.is_empty()
+                && self.results.is_empty()
+            {
+                ui.colored_label(RED, " 0/0 ");
+            } else {
+                ui.label(format!(
+                    " {}/{} ",
+                    min(self.current_result + 1, self.results.len()),
+                    self.results.len()
+                ));
+            }
+
+            if ui.add(egui::Button::new(">")).clicked() {
+                action = Action::Next;
+            }
+        });
+
+        let previous_bool_state = (
+            self.across_documents,
+            self.in_folder,
+            self.regex_mode,
+            self.whole_word,
+            self.case_insensitive,
+        );
+        ui.add_enabled(
+            !self.in_folder,
+            egui::Checkbox::new(&mut self.across_documents, "Across documents"),
+        );
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.in_folder, "In folder");
+            if self.in_folder {
+                ui.add(egui::TextEdit::singleline(&mut self.folder).desired_width(160.0));
+                if ui.button("📁").clicked() {
+                    if let Some(folder) =
+                        tinyfiledialogs::select_folder_dialog("Search in folder", &self.folder)
+                    {
+                        self.folder = folder;
+                    }
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.regex_mode, "Regex");
+            ui.checkbox(&mut self.whole_word, "Whole word");
+            ui.checkbox(&mut self.case_insensitive, "Ignore case");
+        });
+        if previous_bool_state
+            != (
+                self.across_documents,
+                self.in_folder,
+                self.regex_mode,
+                self.whole_word,
+                self.case_insensitive,
+            )
+        {
+            self.searched_text = "".into();
+        }
+
+        egui::CollapsingHeader::new("Replace")
+            .default_open(false)
+            .enabled(!self.in_folder)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    let Self { replace_text, .. } = self;
+                    ui.add(
+                        egui::TextEdit::singleline(replace_text)
+                            .desired_width(120.0)
+                            .lock_focus(true),
+                    );
+                    if ui.add(egui::Button::new("Replace")).clicked() {
+                        action = Action::Replace;
+                    }
+                    if ui.add(egui::Button::new("Replace all")).clicked() {
+                        action = Action::ReplaceAll;
+                    }
+                });
+            });
+
+        match action {
+            Action::Update if self.in_folder => self.search_folder(ui.ctx()),
+            Action::Next | Action::Previous if self.in_folder => (),
+            Action::Update => self.search(tabs, selected_tab),
+            Action::Next => self.find_result(tabs, selected_tab, 1),
+            Action::Previous => self.find_result(tabs, selected_tab, -1),
+            Action::Replace => self.replace(tabs, selected_tab),
+            Action::ReplaceAll => self.replace_all(tabs, selected_tab),
+            Action::None => (),
+        }
+    }
+
+    /// Char indices of the current match, as expected by the editor cursor
+    pub fn get_cursor_range(&self, text: &str) -> (usize, usize) {
+        let result = &self.results[self.current_result];
+        (
+            position::byte_to_char(text, result.start),
+            position::byte_to_char(text, result.end),
+        )
+    }
+
+    /// Byte ranges of the matches in a text, to highlight them while the window is open.
+    /// They are found again only when the text or the search changes.
+    pub fn highlights(&self, ctx: &egui::Context, text: &str) -> Vec<Range<usize>> {
+        let Some(pattern) = &self.pattern else {
+            return vec![];
+        };
+        if !self.visible || self.searched_text.is_empty() {
+            return vec![];
+        }
+        let query = MatchQuery {
+            pattern,
+            case_insensitive: self.case_insensitive,
+            text,
+        };
+        ctx.memory_mut(|mem| mem.caches.cache::<MatchCache>().get(query))
+    }
+
+    /// The search text as a regex, escaped unless in regex mode
+    fn build_pattern(&self) -> Result<Regex, regex::Error> {
+        let mut pattern = if self.regex_mode {
+            self.search_text.clone()
+        } else {
+            regex::escape(&self.search_text)
+        };
+        if self.whole_word {
+            pattern = format!(r"\b(?:{})\b", pattern);
+        }
+        RegexBuilder::new(&pattern)
+            .case_insensitive(self.case_insensitive)
+            .multi_line(true)
+            .build()
+    }
+
+    /// Results are streamed to the search panel
+    fn search_folder(&mut self, ctx: &egui::Context) {
+        if self.search_text.is_empty() {
+            return;
+        }
+        self.searched_text = self.search_text.clone();
+        self.results.clear();
+        match self.build_pattern() {
+            Ok(pattern) => {
+                self.pattern = Some(pattern.clone());
+                self.pattern_error = None;
+                self.folder_search
+                    .start(ctx, &PathBuf::from(&self.folder), pattern);
+            }
+            Err(err) => {
+                self.pattern = None;
+                self.pattern_error = Some(err.to_string());
+            }
+        }
+    }
+
+    fn search(&mut self, tabs: &mut Vec<Tab>, selected_tab: &mut usize) {
+        if self.search_text.is_empty() {
+            return;
+        }
+
+        self.searched_text = self.search_text.clone();
+        match self.build_pattern() {
+            Ok(pattern) => {
+                self.pattern = Some(pattern);
+                self.pattern_error = None;
+            }
+            Err(err) => {
+                self.pattern = None;
+                self.pattern_error = Some(err.to_string());
+                self.results.clear();
+                return;
+            }
+        }
+
+        let mut search_results: Vec<Selection> = vec![];
+
+        if self.across_documents {
+            for (index, tab) in tabs.iter().enumerate() {
+                search_results.extend(self.match_text(&tab.code, index));
+            }
+        } else {
+            search_results.extend(self.match_text(&tabs[*selected_tab].code, *selected_tab));
+        }
+
+        self.results = search_results.clone();
+
+        self.current_result = 0;
+        if !self.results.is_empty() {
+            self.find_result(tabs, selected_tab, 0);
+        }
+    }
+
+    fn match_text(&self, tab_text: &str, tab_number: usize) -> Vec<Selection> {
+        let Some(pattern) = &self.pattern else {
+            return vec![];
+        };
+        find_matches(pattern, tab_text)
+            .into_iter()
+            .map(|found| Selection {
+                tab: tab_number,
+                start: found.start,
+                end: found.end,
+            })
+            .collect()
+    }
+
+    fn find_result(&mut self, tabs: &mut Vec<Tab>, selected_tab: &mut usize, direction: i32) {
+        if self.searched_text != self.search_text {
+            self.search(tabs, &mut *selected_tab);
+        } else if !self.results.is_empty() {
+            self.current_result =
+                (self.current_result as i32 + direction + self.results.len() as i32) as usize
+                    % self.results.len();
+            self.result_selected = false;
+            *selected_tab = self.results[self.current_result].tab;
+
+            let target = self.results[self.current_result].start;
+            let (row, _) =
+                position::byte_to_line_col(&tabs[*selected_tab].code, target, Column::Chars);
+            tabs[*selected_tab].scroll_offset = self.row_height * row.saturating_sub(5) as f32;
+        }
+    }
+
+    /// Replace the current match, then select the next one
+    fn replace(&mut self, tabs: &mut Vec<Tab>, selected_tab: &mut usize) {
+        if self.searched_text != self.search_text {
+            self.search(tabs, &mut *selected_tab);
+        }
+        let (Some(pattern), Some(result)) = (&self.pattern, self.results.get(self.current_result))
+        else {
+            return;
+        };
+
+        let tab = &mut tabs[result.tab];
+        // the text may have been edited since the search
+        let Some(captures) = pattern
+            .captures_at(&tab.code, result.start)
+            .filter(|captures| {
+                captures.get(0).map(|found| found.range()) == Some(result.start..result.end)
+            })
+        else {
+            self.search(tabs, &mut *selected_tab);
+            return;
+        };
+        let mut replacement = String::new();
+        if self.regex_mode {
+            captures.expand(&self.replace_text, &mut replacement);
+        } else {
+            replacement = self.replace_text.clone();
+        }
+        tab.code
+            .replace_range(result.start..result.end, &replacement);
+        tab.saved = false;
+
+        let current_result = self.current_result;
+        self.search(tabs, &mut *selected_tab);
+        if !self.results.is_empty() {
+            self.current_result = min(current_result, self.results.len() - 1);
+            self.find_result(tabs, selected_tab, 0);
+        }
+    }
+
+    fn replace_all(&mut self, tabs: &mut Vec<Tab>, selected_tab: &mut usize) {
+        self.search(tabs, &mut *selected_tab);
+        let Some(pattern) = self.pattern.clone() else {
+            return;
+        };
+
+        let mut done: Vec<usize> = vec![];
+        for element in &self.results {
+            if done.contains(&element.tab) {
+                continue;
+            }
+            let code = &tabs[element.tab].code;
+            let replaced = if self.regex_mode {
+                pattern.replace_all(code, self.replace_text.as_str())
+            } else {
+                pattern.replace_all(code, NoExpand(&self.replace_text))
+            };
+            tabs[element.tab].code = replaced.into_owned();
+            tabs[element.tab].saved = false;
+            done.push(element.tab)
+        }
+        self.search(tabs, selected_tab);
+    }
+}
@@ -0,0 +1,132 @@
+use super::*;
+
+fn window(search: &str) -> SearchWindow {
+    SearchWindow {
+        visible: true,
+        search_text: search.into(),
+        ..Default::default()
+    }
+}
+
+fn tabs(texts: &[&str]) -> Vec<Tab> {
+    texts
+        .iter()
+        .map(|text| Tab {
+            code: text.to_string(),
+            saved: true,
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Matched texts of a search in a single tab
+fn found(search: &mut SearchWindow, text: &str) -> Vec<String> {
+    let mut tabs = tabs(&[text]);
+    search.search(&mut tabs, &mut 0);
+    search
+        .results
+        .iter()
+        .map(|result| text[result.start..result.end].to_string())
+        .collect()
+}
+
+#[test]
+fn plain_text_is_escaped() {
+    let mut search = window("a.b");
+    assert_eq!(found(&mut search, "axb a.b"), ["a.b"]);
+
+    search.regex_mode = true;
+    assert_eq!(found(&mut search, "axb a.b"), ["axb", "a.b"]);
+}
+
+#[test]
+fn whole_word_and_case_options() {
+    let mut search = window("cat");
+    assert_eq!(found(&mut search, "cat concat Cat cat_x cat.").len(), 4);
+
+    search.whole_word = true;
+    assert_eq!(
+        found(&mut search, "cat concat Cat cat_x cat."),
+        ["cat", "cat"]
+    );
+
+    search.case_insensitive = true;
+    assert_eq!(
+        found(&mut search, "cat concat Cat cat_x cat."),
+        ["cat", "Cat", "cat"]
+    );
+
+    // the whole word applies to every alternative of a regex
+    let mut search = window("a|b");
+    search.regex_mode = true;
+    search.whole_word = true;
+    assert_eq!(found(&mut search, "a ab b"), ["a", "b"]);
+}
+
+#[test]
+fn invalid_regex_is_reported() {
+    let mut search = window("(unclosed");
+    search.regex_mode = true;
+    assert!(found(&mut search, "(unclosed").is_empty());
+    assert!(search.pattern_error.is_some());
+
+    search.regex_mode = false;
+    assert_eq!(found(&mut search, "(unclosed"), ["(unclosed"]);
+    assert!(search.pattern_error.is_none());
+}
+
+#[test]
+fn replace_expands_groups_in_regex_mode_only() {
+    let mut search = window(r"(\w+)@(\w+)");
+    search.regex_mode = true;
+    search.replace_text = "$2 at $1".into();
+    let mut mails = tabs(&["me@home, you@work"]);
+    search.replace(&mut mails, &mut 0);
+    assert_eq!(mails[0].code, "home at me, you@work");
+    assert!(!mails[0].saved);
+    search.replace(&mut mails, &mut 0);
+    assert_eq!(mails[0].code, "home at me, work at you");
+
+    let mut search = window("@");
+    search.replace_text = "$1".into();
+    let mut plain = tabs(&["me@home"]);
+    search.replace(&mut plain, &mut 0);
+    assert_eq!(plain[0].code, "me$1home");
+}
+
+#[test]
+fn replace_all_in_every_document() {
+    let mut search = window(r"(\d+)");
+    search.regex_mode = true;
+    search.across_documents = true;
+    search.replace_text = "<$1>".into();
+    let mut all = tabs(&["1 and 22", "no digit", "333"]);
+    search.replace_all(&mut all, &mut 0);
+    let codes: Vec<&str> = all.iter().map(|tab| tab.code.as_str()).collect();
+    assert_eq!(codes, ["<1> and <22>", "no digit", "<333>"]);
+    assert!(!all[0].saved && all[1].saved);
+
+    let mut search = window("$");
+    search.replace_text = "$0".into();
+    let mut one = tabs(&["a$b$"]);
+    search.replace_all(&mut one, &mut 0);
+    assert_eq!(one[0].code, "a$0b$0");
+}
+
+#[test]
+fn highlights_follow_the_text() {
+    let ctx = egui::Context::default();
+    let mut search = window("ab");
+    assert!(search.highlights(&ctx, "ab ab").is_empty());
+
+    found(&mut search, "ab");
+    assert_eq!(search.highlights(&ctx, "ab ab"), [0..2, 3..5]);
+    assert_eq!(search.highlights(&ctx, "xab ab"), [1..3, 4..6]);
+
+    search.case_insensitive = true;
+    found(&mut search, "ab");
+    assert_eq!(search.highlights(&ctx, "AB aB"), [0..2, 3..5]);
+
+    search.visible = false;
+    assert!(search.highlights(&ctx, "ab").is_empty());
+}
@@ -1,18 +1,24 @@
-use crate::editor::{themes::DEFAULT_THEMES, ColorTheme};
+use crate::editor::ColorTheme;
+use crate::panels::SCROLLBACK_LIMIT;
 use eframe::egui;
 
 pub struct SettingsWindow {
     pub visible: bool,
     pub updated: bool,
     pub theme: ColorTheme,
+    pub themes: Vec<ColorTheme>,
+    /// Output lines kept by each terminal
+    pub scrollback: usize,
 }
 
 impl SettingsWindow {
-    pub fn new(theme: ColorTheme) -> Self {
+    pub fn new(theme: ColorTheme, themes: Vec<ColorTheme>) -> Self {
         Self {
             visible: false,
             updated: false,
             theme,
+            themes,
+            scrollback: SCROLLBACK_LIMIT,
         }
     }
 
@@ -31,19 +37,28 @@ impl SettingsWindow {
         ui.horizontal(|ui| {
             ui.label("Theme ");
 
-            let previous_theme = self.theme;
+            let previous_theme = self.theme.clone();
             egui::ComboBox::from_label("")
                 .selected_text(self.theme.name.to_string())
                 .show_ui(ui, |ui| {
                     ui.style_mut().wrap = Some(false);
                     ui.set_min_width(60.0);
-                    for theme in DEFAULT_THEMES {
-                        ui.selectable_value(&mut self.theme, theme, theme.name);
+                    for theme in &self.themes {
+                        ui.selectable_value(&mut self.theme, theme.clone(), &theme.name);
                     }
                 });
             if self.theme != previous_theme {
                 self.updated = true;
             }
         });
+        ui.horizontal(|ui| {
+            ui.label("Terminal scrollback ");
+            ui.add(
+                egui::DragValue::new(&mut self.scrollback)
+                    .clamp_range(100..=1_000_000)
+                    .speed(100)
+                    .suffix(" lines"),
+            );
+        });
     }
 }
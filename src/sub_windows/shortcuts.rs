@@ -30,13 +30,31 @@ impl ShortcutsWindow {
         ui.label("Ctrl+T : reload tree");
         ui.separator();
         ui.label("Ctrl+Z : undo");
-        ui.label("Ctrl+Y : redo");
+        ui.label("Ctrl+Y / Ctrl+Shift+Z : redo");
         ui.label("Tab on selection : add indent of selection");
         ui.label("Shift+Tab on selection : remove indent of selection");
-        ui.label("Ctrl+E : comment selection");
+        ui.label("Ctrl+E : comment / uncomment lines");
+        ui.label("Ctrl+Shift+E : block comment around the selection");
+        ui.label("Ctrl+D : add a cursor at the next occurrence of the selection");
+        ui.label("Alt+Click : add a cursor");
+        ui.label("Alt+Drag : column selection");
+        ui.label("Escape : keep only the first cursor");
+        ui.label("Ctrl+M : go to the matching bracket");
+        ui.label("Ctrl+G : go to line:col");
+        ui.label("Ctrl+Shift+O : go to a symbol of the file");
+        ui.label("Ctrl+Shift+[ / Ctrl+Shift+] : fold / unfold the block at the cursor");
+        ui.label("Ctrl+Alt+[ / Ctrl+Alt+] : fold / unfold all");
+        ui.label("F12 : go to definition");
+        ui.label("F8 / Shift+F8 : go to next / previous problem");
+        ui.label("F5 : run the last task again");
         ui.separator();
         ui.label("Alt+Arrows : move between tabs");
         ui.separator();
+        ui.label("Up / Down (terminal input) : previous / next command in this folder");
+        ui.label(
+            "Ctrl+R (terminal input) : search the commands backwards, Escape to edit the match",
+        );
+        ui.separator();
         ui.label("Enter (project_mode) : edit item");
         ui.label("Arrows (project_mode) : change selected item");
         ui.label("Shift+Arrows (project_mode) : move selected item");
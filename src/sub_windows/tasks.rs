@@ -0,0 +1,84 @@
+use eframe::egui;
+use std::path::{Path, PathBuf};
+
+use crate::panels::{self, Task};
+use crate::RED;
+
+/// Lists the tasks of the project of the current tab, the clicked one is stored in `chosen`
+pub struct TaskWindow {
+    pub visible: bool,
+    pub chosen: Option<Task>,
+    file: Option<PathBuf>,
+    tasks: Vec<Task>,
+    error: Option<String>,
+}
+
+impl TaskWindow {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            chosen: None,
+            file: None,
+            tasks: vec![],
+            error: None,
+        }
+    }
+
+    /// Read the tasks file above `path` again, keeping the tasks offered for `language`
+    pub fn load(&mut self, path: &Path, language: &str) {
+        self.file = panels::find_tasks(path);
+        self.tasks = vec![];
+        self.error = None;
+        let Some(file) = &self.file else {
+            return;
+        };
+        match panels::load_tasks(file) {
+            Ok(tasks) => {
+                self.tasks = tasks
+                    .into_iter()
+                    .filter(|task| task.applies_to(language))
+                    .collect()
+            }
+            Err(err) => self.error = Some(err.to_string()),
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) {
+        let mut visible = self.visible;
+        egui::Window::new("Tasks")
+            .open(&mut visible)
+            .vscroll(true)
+            .hscroll(true)
+            .show(ctx, |ui| self.ui(ui));
+        self.visible = self.visible && visible;
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.set_min_width(250.0);
+        let Some(file) = &self.file else {
+            ui.label(format!(
+                "No {} in the folders of this file",
+                panels::TASKS_FILE
+            ));
+            return;
+        };
+        ui.label(file.display().to_string());
+        ui.separator();
+        if let Some(error) = &self.error {
+            ui.colored_label(RED, error);
+            return;
+        }
+        if self.tasks.is_empty() {
+            ui.label("No task for this language");
+        }
+        for task in self.tasks.iter() {
+            let response = ui
+                .add(egui::Button::new(&task.name))
+                .on_hover_text(&task.command);
+            if response.clicked() {
+                self.chosen = Some(task.clone());
+                self.visible = false;
+            }
+        }
+    }
+}